@@ -0,0 +1,66 @@
+use bento::{App, BentoApp, Color, Ctx, FillRule, Height, Path, StrokeStyle, Width};
+use winit::keyboard::KeyCode;
+
+struct Graph {
+    nodes: Vec<(f32, f32)>,
+}
+
+impl BentoApp for Graph {
+    fn once(&mut self, ctx: &mut Ctx) {
+        ctx.ui.canvas(
+            "graph",
+            20.0,
+            20.0,
+            Width::Percent(0.95),
+            Height::Percent(0.9),
+            Color::rgb(0.09, 0.10, 0.12),
+        );
+    }
+
+    fn update(&mut self, ctx: &mut Ctx) {
+        if ctx.input.just_pressed(KeyCode::Escape) {
+            ctx.exit();
+        }
+
+        if ctx.is_clicked("graph")
+            && let Some(pos) = ctx.canvas_mouse("graph")
+        {
+            self.nodes.push(pos);
+            ctx.mark_dirty();
+        }
+
+        let nodes = &self.nodes;
+        let font = ctx.ui.fonts.default();
+        ctx.ui.draw_canvas("graph", |ui| {
+            for pair in nodes.windows(2) {
+                let (a, b) = (pair[0], pair[1]);
+                let mut wire = Path::new();
+                wire.move_to(a.0, a.1)
+                    .cubic_to(a.0 + 80.0, a.1, b.0 - 80.0, b.1, b.0, b.1);
+                ui.stroke_path(&wire, &StrokeStyle::new(2.0), [0.35, 0.75, 0.95, 1.0]);
+            }
+            for (i, &(x, y)) in nodes.iter().enumerate() {
+                ui.rounded_rect(
+                    x - 40.0,
+                    y - 20.0,
+                    80.0,
+                    40.0,
+                    6.0,
+                    [0.15, 0.17, 0.22, 1.0],
+                    [0.25, 0.28, 0.35, 1.0],
+                    2.0,
+                );
+                let mut dot = Path::new();
+                dot.circle(x, y, 4.0);
+                ui.fill_path(&dot, FillRule::NonZero, [0.95, 0.65, 0.25, 1.0]);
+                ui.draw_text(&format!("node {i}"), font, x - 30.0, y - 16.0, Color::WHITE);
+            }
+        });
+    }
+}
+
+fn main() {
+    App::new("Bento Canvas", 900, 700).run(Graph {
+        nodes: vec![(100.0, 100.0), (320.0, 220.0), (560.0, 140.0)],
+    });
+}
@@ -1,6 +1,8 @@
 #![allow(dead_code, unused)]
+use std::time::Duration;
+
 use winit::keyboard::KeyCode;
-use bento::{App, BentoApp, Color, Ctx, Height, Width, Rect, Text, Button};
+use bento::{App, BentoApp, Color, Ctx, Easing, FontRole, Height, Property, Style, Theme, ThemeMode, ThemeToken, Width, Rect, Text, Button};
 
 struct Demo {
     counter: i32,
@@ -10,7 +12,9 @@ struct Demo {
 
 impl BentoApp for Demo {
     fn once(&mut self, ctx: &mut Ctx) {
-        let font = ctx.ui.fonts.default();
+        let font = ctx.ui.font(FontRole::Body);
+        let heading = ctx.ui.font(FontRole::Heading);
+        let theme = ctx.ui.theme().clone();
 
         ctx.ui.rect(
             "header",
@@ -18,19 +22,14 @@ impl BentoApp for Demo {
             0.0,
             Width::Full,
             Height::Fixed(50.0),
-            Color::rgb(0.12, 0.14, 0.18),
+            theme.surface,
             Color::TRANSPARENT,
             0.0,
         );
+        ctx.ui.set_theme_color("header", ThemeToken::Surface);
 
-        ctx.ui.text(
-            "title",
-            "Bento UI Demo",
-            font,
-            20.0,
-            15.0,
-            Color::rgb(0.9, 0.92, 0.95),
-        );
+        ctx.ui.text("title", "Bento UI Demo", heading, 20.0, 11.0, theme.text);
+        ctx.ui.set_theme_color("title", ThemeToken::Text);
 
         ctx.ui.rect(
             "content",
@@ -38,10 +37,11 @@ impl BentoApp for Demo {
             50.0,
             Width::Full,
             Height::Percent(1.0),
-            Color::rgb(0.09, 0.10, 0.12),
+            theme.background,
             Color::TRANSPARENT,
             0.0,
         );
+        ctx.ui.set_theme_color("content", ThemeToken::Background);
 
         ctx.ui.rect(
             "counter_box",
@@ -49,10 +49,12 @@ impl BentoApp for Demo {
             70.0,
             Width::Fixed(300.0),
             Height::Fixed(100.0),
-            Color::rgb(0.15, 0.17, 0.22),
-            Color::rgb(0.25, 0.28, 0.35),
+            theme.surface_raised,
+            theme.border,
             2.0,
         );
+        ctx.ui.set_theme_color("counter_box", ThemeToken::SurfaceRaised);
+        ctx.ui.set_theme_outline("counter_box", ThemeToken::Border);
 
         ctx.ui.text(
             "counter_label",
@@ -60,8 +62,9 @@ impl BentoApp for Demo {
             font,
             30.0,
             80.0,
-            Color::rgb(0.65, 0.68, 0.75),
+            theme.text_muted,
         );
+        ctx.ui.set_theme_color("counter_label", ThemeToken::TextMuted);
 
         ctx.ui.text(
             "counter_value",
@@ -69,8 +72,9 @@ impl BentoApp for Demo {
             font,
             30.0,
             110.0,
-            Color::rgb(0.35, 0.75, 0.95),
+            theme.focus,
         );
+        ctx.ui.set_theme_color("counter_value", ThemeToken::Focus);
 
         ctx.ui.button("btn_increment", "+ Increment", 20.0, 190.0);
         ctx.ui.button("btn_decrement", "- Decrement", 150.0, 190.0);
@@ -82,14 +86,24 @@ impl BentoApp for Demo {
             font,
             20.0,
             250.0,
-            Color::rgb(0.65, 0.68, 0.75),
+            theme.text_muted,
         );
+        ctx.ui.set_theme_color("color_label", ThemeToken::TextMuted);
 
         ctx.ui.button("btn_red", "Red", 20.0, 280.0);
         ctx.ui.button("btn_green", "Green", 90.0, 280.0);
         ctx.ui.button("btn_blue", "Blue", 170.0, 280.0);
 
         ctx.ui.button("btn_sidebar", "Toggle Sidebar", 20.0, 340.0);
+        ctx.ui.button("btn_theme", "Toggle Theme", 170.0, 340.0);
+
+        ctx.ui.define_style("demo-button", Style::new().transition(Duration::from_millis(150)));
+        ctx.ui.define_style("danger", Style::new().background(Color::rgb(0.7, 0.27, 0.27)));
+        let ids: Vec<_> = ctx.ui.buttons.iter().map(|b| b.id.clone()).collect();
+        for id in ids {
+            ctx.ui.add_class(&id, "demo-button");
+        }
+        ctx.ui.add_class("btn_reset", "danger");
 
         ctx.ui.text(
             "info",
@@ -97,8 +111,9 @@ impl BentoApp for Demo {
             font,
             20.0,
             420.0,
-            Color::rgb(0.45, 0.48, 0.55),
+            theme.text_muted,
         );
+        ctx.ui.set_theme_color("info", ThemeToken::TextMuted);
     }
 
     fn update(&mut self, ctx: &mut Ctx) {
@@ -154,33 +169,43 @@ impl BentoApp for Demo {
             }
         }
 
+        if ctx.is_clicked("btn_theme") {
+            let theme = match ctx.ui.theme().mode {
+                ThemeMode::Dark => Theme::light(),
+                ThemeMode::Light => Theme::dark(),
+            };
+            // picked by hand, so stop following the os
+            ctx.follow_system_theme = false;
+            ctx.set_theme(theme);
+        }
+
         if ctx.is_clicked("btn_sidebar") {
             if !self.sidebar_open {
                 if !ctx.ui.exists::<Rect>("sidebar") {
+                    let theme = ctx.ui.theme().clone();
                     ctx.ui.rect(
                         "sidebar",
                         0.0,
                         50.0,
                         Width::Fixed(250.0),
                         Height::Percent(1.0),
-                        Color::rgb(0.13, 0.15, 0.19),
-                        Color::rgb(0.20, 0.23, 0.28),
+                        theme.surface,
+                        theme.border,
                         2.0,
                     );
+                    ctx.ui.set_theme_color("sidebar", ThemeToken::Surface);
+                    ctx.ui.set_theme_outline("sidebar", ThemeToken::Border);
 
-                    let font = ctx.ui.fonts.default();
-                    ctx.ui.text(
-                        "sidebar_title",
-                        "Sidebar Menu",
-                        font,
-                        20.0,
-                        70.0,
-                        Color::rgb(0.9, 0.92, 0.95),
-                    );
+                    let font = ctx.ui.font(FontRole::Body);
+                    ctx.ui.text("sidebar_title", "Sidebar Menu", font, 20.0, 70.0, theme.text);
+                    ctx.ui.set_theme_color("sidebar_title", ThemeToken::Text);
 
                     ctx.ui.button("sidebar_btn1", "Option 1", 20.0, 110.0);
                     ctx.ui.button("sidebar_btn2", "Option 2", 20.0, 150.0);
                     ctx.ui.button("sidebar_btn3", "Option 3", 20.0, 190.0);
+                    for id in ["sidebar_btn1", "sidebar_btn2", "sidebar_btn3"] {
+                        ctx.ui.add_class(id, "demo-button");
+                    }
                 } else {
                     ctx.ui.show("sidebar");
                     ctx.ui.show("sidebar_title");
@@ -190,48 +215,7 @@ impl BentoApp for Demo {
                 }
 
                 self.sidebar_open = true;
-
-                let offset = 250.0;
-                if let Some(content) = ctx.ui.get_mut::<Rect>("content") {
-                    content.x = offset;
-                }
-                if let Some(box_) = ctx.ui.get_mut::<Rect>("counter_box") {
-                    box_.x = 20.0 + offset;
-                }
-                if let Some(text) = ctx.ui.get_mut::<Text>("counter_label") {
-                    text.x = 30.0 + offset;
-                }
-                if let Some(text) = ctx.ui.get_mut::<Text>("counter_value") {
-                    text.x = 30.0 + offset;
-                }
-                if let Some(btn) = ctx.ui.get_mut::<Button>("btn_increment") {
-                    btn.x = 20.0 + offset;
-                }
-                if let Some(btn) = ctx.ui.get_mut::<Button>("btn_decrement") {
-                    btn.x = 150.0 + offset;
-                }
-                if let Some(btn) = ctx.ui.get_mut::<Button>("btn_reset") {
-                    btn.x = 280.0 + offset;
-                }
-                if let Some(text) = ctx.ui.get_mut::<Text>("color_label") {
-                    text.x = 20.0 + offset;
-                }
-                if let Some(btn) = ctx.ui.get_mut::<Button>("btn_red") {
-                    btn.x = 20.0 + offset;
-                }
-                if let Some(btn) = ctx.ui.get_mut::<Button>("btn_green") {
-                    btn.x = 90.0 + offset;
-                }
-                if let Some(btn) = ctx.ui.get_mut::<Button>("btn_blue") {
-                    btn.x = 170.0 + offset;
-                }
-                if let Some(btn) = ctx.ui.get_mut::<Button>("btn_sidebar") {
-                    btn.x = 20.0 + offset;
-                }
-                if let Some(text) = ctx.ui.get_mut::<Text>("info") {
-                    text.x = 20.0 + offset;
-                }
-                ctx.mark_dirty();
+                slide_content(ctx, 250.0);
             } else {
                 ctx.ui.hide("sidebar");
                 ctx.ui.hide("sidebar_title");
@@ -240,47 +224,7 @@ impl BentoApp for Demo {
                 ctx.ui.hide("sidebar_btn3");
 
                 self.sidebar_open = false;
-
-                if let Some(content) = ctx.ui.get_mut::<Rect>("content") {
-                    content.x = 0.0;
-                }
-                if let Some(box_) = ctx.ui.get_mut::<Rect>("counter_box") {
-                    box_.x = 20.0;
-                }
-                if let Some(text) = ctx.ui.get_mut::<Text>("counter_label") {
-                    text.x = 30.0;
-                }
-                if let Some(text) = ctx.ui.get_mut::<Text>("counter_value") {
-                    text.x = 30.0;
-                }
-                if let Some(btn) = ctx.ui.get_mut::<Button>("btn_increment") {
-                    btn.x = 20.0;
-                }
-                if let Some(btn) = ctx.ui.get_mut::<Button>("btn_decrement") {
-                    btn.x = 150.0;
-                }
-                if let Some(btn) = ctx.ui.get_mut::<Button>("btn_reset") {
-                    btn.x = 280.0;
-                }
-                if let Some(text) = ctx.ui.get_mut::<Text>("color_label") {
-                    text.x = 20.0;
-                }
-                if let Some(btn) = ctx.ui.get_mut::<Button>("btn_red") {
-                    btn.x = 20.0;
-                }
-                if let Some(btn) = ctx.ui.get_mut::<Button>("btn_green") {
-                    btn.x = 90.0;
-                }
-                if let Some(btn) = ctx.ui.get_mut::<Button>("btn_blue") {
-                    btn.x = 170.0;
-                }
-                if let Some(btn) = ctx.ui.get_mut::<Button>("btn_sidebar") {
-                    btn.x = 20.0;
-                }
-                if let Some(text) = ctx.ui.get_mut::<Text>("info") {
-                    text.x = 20.0;
-                }
-                ctx.mark_dirty();
+                slide_content(ctx, 0.0);
             }
         }
 
@@ -294,7 +238,7 @@ impl BentoApp for Demo {
             println!("Sidebar Option 3 clicked!");
         }
 
-        if ctx.input.keys_just_pressed.contains(&KeyCode::ArrowUp) {
+        if ctx.input.just_pressed_or_repeated(KeyCode::ArrowUp) {
             self.counter += 1;
             if let Some(text) = ctx.ui.get_mut::<Text>("counter_value") {
                 text.text = self.counter.to_string();
@@ -302,7 +246,7 @@ impl BentoApp for Demo {
             }
         }
 
-        if ctx.input.keys_just_pressed.contains(&KeyCode::ArrowDown) {
+        if ctx.input.just_pressed_or_repeated(KeyCode::ArrowDown) {
             self.counter -= 1;
             if let Some(text) = ctx.ui.get_mut::<Text>("counter_value") {
                 text.text = self.counter.to_string();
@@ -320,6 +264,30 @@ impl BentoApp for Demo {
     }
 }
 
+/// where each element sits with the sidebar closed
+const CONTENT_X: [(&str, f32); 14] = [
+    ("content", 0.0),
+    ("counter_box", 20.0),
+    ("counter_label", 30.0),
+    ("counter_value", 30.0),
+    ("btn_increment", 20.0),
+    ("btn_decrement", 150.0),
+    ("btn_reset", 280.0),
+    ("color_label", 20.0),
+    ("btn_red", 20.0),
+    ("btn_green", 90.0),
+    ("btn_blue", 170.0),
+    ("btn_sidebar", 20.0),
+    ("btn_theme", 170.0),
+    ("info", 20.0),
+];
+
+fn slide_content(ctx: &mut Ctx, offset: f32) {
+    for (id, x) in CONTENT_X {
+        ctx.animate(id, Property::X, x + offset, Duration::from_millis(250), Easing::CubicOut);
+    }
+}
+
 fn main() {
     App::new("Bento UI Demo - Interactive Example", 800, 600).run(Demo {
         counter: 0,
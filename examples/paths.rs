@@ -0,0 +1,64 @@
+use bento::{App, BentoApp, Ctx, FillRule, LineCap, LineJoin, Path, StrokeStyle, Svg};
+use winit::keyboard::KeyCode;
+
+const GEAR_ICON: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 24 24">
+  <path fill-rule="evenodd" d="M10 2h4l.6 3 2.2 1.3 2.9-1 2 3.4-2.3 2v2.6l2.3 2-2 3.4-2.9-1-2.2 1.3-.6 3h-4l-.6-3-2.2-1.3-2.9 1-2-3.4 2.3-2v-2.6l-2.3-2 2-3.4 2.9 1L9.4 5zM12 9a3 3 0 1 0 0 6 3 3 0 1 0 0-6z"/>
+</svg>"#;
+
+struct Paths {
+    icon: Svg,
+}
+
+impl BentoApp for Paths {
+    fn once(&mut self, _ctx: &mut Ctx) {}
+
+    fn update(&mut self, ctx: &mut Ctx) {
+        if ctx.input.just_pressed(KeyCode::Escape) {
+            ctx.exit();
+        }
+
+        let points = [
+            [40.0, 200.0],
+            [120.0, 80.0],
+            [200.0, 160.0],
+            [280.0, 60.0],
+            [360.0, 140.0],
+        ];
+        let style = StrokeStyle::new(4.0).join(LineJoin::Round).cap(LineCap::Round);
+        ctx.ui.stroke_path(&Path::polyline(&points), &style, [0.35, 0.75, 0.95, 1.0]);
+
+        let mut curve = Path::new();
+        curve
+            .move_to(40.0, 300.0)
+            .cubic_to(120.0, 220.0, 280.0, 380.0, 360.0, 300.0);
+        let dashed = StrokeStyle::new(2.0).dashes(&[10.0, 6.0], 0.0);
+        ctx.ui.stroke_path(&curve, &dashed, [0.9, 0.92, 0.95, 1.0]);
+
+        let mut star = Path::new();
+        for i in 0..5 {
+            let angle = i as f32 * std::f32::consts::TAU * 2.0 / 5.0 - std::f32::consts::FRAC_PI_2;
+            let (x, y) = (560.0 + 80.0 * angle.cos(), 160.0 + 80.0 * angle.sin());
+            if i == 0 {
+                star.move_to(x, y);
+            } else {
+                star.line_to(x, y);
+            }
+        }
+        star.close();
+        ctx.ui.fill_path(&star, FillRule::EvenOdd, [0.95, 0.65, 0.25, 1.0]);
+
+        let mut pie = Path::new();
+        pie.move_to(560.0, 380.0).arc(560.0, 380.0, 80.0, 0.0, 4.0).close();
+        ctx.ui.fill_path(&pie, FillRule::NonZero, [0.27, 0.51, 0.50, 1.0]);
+        ctx.ui.stroke_path(&pie, &StrokeStyle::new(2.0), [1.0, 1.0, 1.0, 1.0]);
+
+        ctx.ui.svg_tinted(&self.icon, 40.0, 420.0, 24.0, 24.0, [0.9, 0.92, 0.95, 1.0]);
+        ctx.ui.svg_tinted(&self.icon, 80.0, 404.0, 48.0, 48.0, [0.35, 0.75, 0.95, 1.0]);
+        ctx.ui.svg(&self.icon, 150.0, 380.0, 96.0, 96.0);
+    }
+}
+
+fn main() {
+    let icon = Svg::from_bytes(GEAR_ICON.as_bytes()).unwrap();
+    App::new("Bento Paths", 800, 600).run(Paths { icon });
+}
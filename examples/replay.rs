@@ -0,0 +1,58 @@
+use bento::{App, BentoApp, Color, Ctx, Harness, Recording, Text};
+use winit::keyboard::KeyCode;
+
+/// cargo run --example replay -- record session.json
+/// cargo run --example replay -- play session.json
+/// cargo run --example replay -- step session.json   (F10 advances)
+/// cargo run --example replay -- check session.json  (headless, prints the result)
+struct Counter {
+    count: i32,
+}
+
+impl BentoApp for Counter {
+    fn once(&mut self, ctx: &mut Ctx) {
+        let font = ctx.ui.fonts.default();
+        ctx.ui.text("count", "0", font, 20.0, 20.0, Color::WHITE);
+        ctx.ui.button("inc", "+1", 20.0, 60.0);
+    }
+
+    fn update(&mut self, ctx: &mut Ctx) {
+        if ctx.input.keys_just_pressed.contains(&KeyCode::Escape) {
+            ctx.exit();
+        }
+
+        if ctx.is_clicked("inc") {
+            self.count += 1;
+            if let Some(text) = ctx.ui.get_mut::<Text>("count") {
+                text.text = self.count.to_string();
+                ctx.mark_dirty();
+            }
+        }
+    }
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let mode = args.next().unwrap_or_else(|| "record".to_string());
+    let path = args.next().unwrap_or_else(|| "session.json".to_string());
+    let app = App::new("Replay", 300, 200);
+
+    match mode.as_str() {
+        "record" => app.record(&path).run(Counter { count: 0 }),
+        "play" => app.replay(Recording::load(&path).unwrap()).run(Counter { count: 0 }),
+        "step" => app
+            .replay_stepped(Recording::load(&path).unwrap())
+            .run(Counter { count: 0 }),
+        "check" => {
+            let recording = Recording::load(&path).unwrap();
+            let mut harness = Harness::new(
+                Counter { count: 0 },
+                recording.width,
+                recording.height,
+            );
+            harness.replay(&recording);
+            println!("count after replay: {}", harness.app.count);
+        }
+        other => eprintln!("unknown mode '{other}', expected record, play, step or check"),
+    }
+}
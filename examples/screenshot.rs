@@ -0,0 +1,34 @@
+use bento::{BentoApp, Color, Ctx, Headless, Height, Width};
+
+struct Card;
+
+impl BentoApp for Card {
+    fn once(&mut self, ctx: &mut Ctx) {
+        let font = ctx.ui.fonts.default();
+        ctx.ui.rect(
+            "card",
+            20.0,
+            20.0,
+            Width::Fixed(260.0),
+            Height::Fixed(120.0),
+            Color::rgb(0.15, 0.17, 0.22),
+            Color::rgb(0.25, 0.28, 0.35),
+            2.0,
+        );
+        ctx.ui.text("title", "Rendered offscreen", font, 36.0, 36.0, Color::WHITE);
+        ctx.ui.button("ok", "OK", 36.0, 80.0);
+    }
+
+    fn update(&mut self, _ctx: &mut Ctx) {}
+}
+
+fn main() {
+    let path = std::env::args().nth(1).unwrap_or_else(|| "screenshot.png".to_string());
+
+    let mut headless = Headless::new(300, 160, 2.0).expect("no adapter available");
+    let mut app = Card;
+    headless.start(&mut app);
+    let image = headless.render(&mut app);
+    image.save_png(&path).unwrap();
+    println!("wrote {}x{} to {path}", image.width, image.height);
+}
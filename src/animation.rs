@@ -0,0 +1,364 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ui::Ui;
+use crate::{Color, Ctx, Height, InputEvent, Width};
+
+/// maps linear progress in 0..=1 to eased progress. springs and bounces
+/// overshoot or rebound but always end at 1
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Easing {
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    /// a damped spring settling by the end. `damping` below 1 overshoots
+    /// and wobbles, 1 and above don't
+    Spring { damping: f32 },
+    /// drops onto the target and bounces a few times
+    Bounce,
+}
+
+impl Easing {
+    pub const SPRING: Self = Self::Spring { damping: 0.5 };
+
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::QuadInOut if t < 0.5 => 2.0 * t * t,
+            Easing::QuadInOut => 1.0 - (-2.0 * t + 2.0).powi(2) * 0.5,
+            Easing::CubicIn => t * t * t,
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut if t < 0.5 => 4.0 * t * t * t,
+            Easing::CubicInOut => 1.0 - (-2.0 * t + 2.0).powi(3) * 0.5,
+            Easing::Spring { .. } if t >= 1.0 => 1.0,
+            Easing::Spring { damping } => spring(t, damping.max(0.01)),
+            Easing::Bounce => bounce(t),
+        }
+    }
+}
+
+/// step response of a damped spring, tuned so it's within 0.1% at t = 1
+fn spring(t: f32, damping: f32) -> f32 {
+    // e^-6.9 is about 0.001
+    if damping >= 1.0 {
+        let w = 9.2;
+        return 1.0 - (-w * t).exp() * (1.0 + w * t);
+    }
+    let w = 6.9 / damping;
+    let wd = w * (1.0 - damping * damping).sqrt();
+    let decay = (-damping * w * t).exp();
+    1.0 - decay * ((wd * t).cos() + damping * w / wd * (wd * t).sin())
+}
+
+fn bounce(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+/// what `Ctx::animate` can change. `Color` is the fill of rects, buttons
+/// and canvases and the color of text
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Property {
+    X,
+    Y,
+    Width,
+    Height,
+    Color,
+    TextColor,
+    OutlineColor,
+    /// the element's `opacity`
+    Opacity,
+}
+
+/// a value a property animates to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AnimValue {
+    Float(f32),
+    Color(Color),
+}
+
+impl From<f32> for AnimValue {
+    fn from(value: f32) -> Self {
+        AnimValue::Float(value)
+    }
+}
+
+impl From<Color> for AnimValue {
+    fn from(value: Color) -> Self {
+        AnimValue::Color(value)
+    }
+}
+
+impl AnimValue {
+    fn lerp(self, to: AnimValue, t: f32) -> AnimValue {
+        match (self, to) {
+            (AnimValue::Float(a), AnimValue::Float(b)) => AnimValue::Float(a + (b - a) * t),
+            (AnimValue::Color(a), AnimValue::Color(b)) => AnimValue::Color(Color::new(
+                a.r + (b.r - a.r) * t,
+                a.g + (b.g - a.g) * t,
+                a.b + (b.b - a.b) * t,
+                a.a + (b.a - a.a) * t,
+            )),
+            _ => to,
+        }
+    }
+}
+
+struct Tween {
+    id: String,
+    property: Property,
+    from: AnimValue,
+    to: AnimValue,
+    start: Duration,
+    duration: Duration,
+    easing: Easing,
+}
+
+/// running tweens, on `Ctx` and advanced once per frame
+#[derive(Default)]
+pub(crate) struct Animations {
+    tweens: Vec<Tween>,
+    /// tweens that reached their target this frame
+    finished: Vec<(String, Property)>,
+}
+
+impl Ctx {
+    /// moves a property of element `id` to `target` over `duration`.
+    /// animating a property that's already moving retargets it from where
+    /// it is now. returns false and does nothing if there's no such element
+    /// or it lacks the property, panics if `target` is the wrong kind of value
+    pub fn animate(
+        &mut self,
+        id: &str,
+        property: Property,
+        target: impl Into<AnimValue>,
+        duration: Duration,
+        easing: Easing,
+    ) -> bool {
+        let Some(from) = read(&self.ui, id, property) else {
+            return false;
+        };
+        let to = target.into();
+        if std::mem::discriminant(&from) != std::mem::discriminant(&to) {
+            panic!("{property:?} of '{id}' can't animate to {to:?}");
+        }
+
+        self.animations
+            .tweens
+            .retain(|t| !(t.id == id && t.property == property));
+        self.animations.tweens.push(Tween {
+            id: id.to_string(),
+            property,
+            from,
+            to,
+            start: self.time(),
+            duration,
+            easing,
+        });
+        self.request_animation_frame();
+        true
+    }
+
+    /// stops a tween where it is, without a finished event
+    pub fn stop_animation(&mut self, id: &str, property: Property) {
+        self.animations
+            .tweens
+            .retain(|t| !(t.id == id && t.property == property));
+    }
+
+    pub fn stop_animations(&mut self, id: &str) {
+        self.animations.tweens.retain(|t| t.id != id);
+    }
+
+    pub fn is_animating(&self, id: &str) -> bool {
+        self.animations.tweens.iter().any(|t| t.id == id)
+    }
+
+    /// whether the tween reached its target during this frame
+    pub fn animation_finished(&self, id: &str, property: Property) -> bool {
+        self.animations
+            .finished
+            .iter()
+            .any(|(i, p)| i == id && *p == property)
+    }
+
+    /// applies every tween at the frame time and keeps frames coming while
+    /// any are left. finished tweens show up as events in this frame's update
+    pub(crate) fn update_animations(&mut self) {
+        self.animations.finished.clear();
+        if self.animations.tweens.is_empty() {
+            return;
+        }
+        let now = self.frame_time();
+        let tweens = std::mem::take(&mut self.animations.tweens);
+        let mut running = Vec::with_capacity(tweens.len());
+
+        for tween in tweens {
+            let elapsed = now.saturating_sub(tween.start);
+            let t = if tween.duration.is_zero() {
+                1.0
+            } else {
+                elapsed.as_secs_f32() / tween.duration.as_secs_f32()
+            };
+            let value = tween.from.lerp(tween.to, tween.easing.apply(t));
+            // removed elements just drop their tweens
+            if !write(&mut self.ui, &tween.id, tween.property, value) {
+                continue;
+            }
+            if t >= 1.0 {
+                self.events.push(InputEvent::AnimationFinished {
+                    id: tween.id.clone(),
+                    property: tween.property,
+                });
+                self.animations.finished.push((tween.id, tween.property));
+            } else {
+                running.push(tween);
+            }
+        }
+
+        self.animations.tweens = running;
+        if !self.animations.tweens.is_empty() {
+            self.request_animation_frame();
+        }
+    }
+}
+
+fn read(ui: &Ui, id: &str, property: Property) -> Option<AnimValue> {
+    let float = |v: f32| Some(AnimValue::Float(v));
+    let color = |c: Color| Some(AnimValue::Color(c));
+
+    if let Some(r) = ui.rects.iter().find(|r| r.id == id) {
+        return match property {
+            Property::X => float(r.x),
+            Property::Y => float(r.y),
+            Property::Width => float(r.w),
+            Property::Height => float(r.h),
+            Property::Color => color(r.color),
+            Property::OutlineColor => color(r.outline_color),
+            Property::Opacity => float(r.opacity),
+            Property::TextColor => None,
+        };
+    }
+    if let Some(t) = ui.texts.iter().find(|t| t.id == id) {
+        return match property {
+            Property::X => float(t.x),
+            Property::Y => float(t.y),
+            Property::Color | Property::TextColor => color(t.color),
+            Property::Opacity => float(t.opacity),
+            Property::Width | Property::Height | Property::OutlineColor => None,
+        };
+    }
+    if let Some(b) = ui.buttons.iter().find(|b| b.id == id) {
+        return match property {
+            Property::X => float(b.x),
+            Property::Y => float(b.y),
+            Property::Width => float(b.w),
+            Property::Height => float(b.h),
+            Property::Color => color(b.bg_color),
+            Property::TextColor => color(b.text_color),
+            Property::OutlineColor => color(b.outline_color),
+            Property::Opacity => float(b.opacity),
+        };
+    }
+    if let Some(c) = ui.canvases.iter().find(|c| c.id == id) {
+        return match property {
+            Property::X => float(c.x),
+            Property::Y => float(c.y),
+            Property::Width => float(c.w),
+            Property::Height => float(c.h),
+            Property::Color => color(c.color),
+            Property::OutlineColor => color(c.outline_color),
+            Property::Opacity => float(c.opacity),
+            Property::TextColor => None,
+        };
+    }
+    None
+}
+
+/// sets a property, false when the element is gone
+fn write(ui: &mut Ui, id: &str, property: Property, value: AnimValue) -> bool {
+    let (f, c) = match value {
+        AnimValue::Float(f) => (f, Color::TRANSPARENT),
+        AnimValue::Color(c) => (0.0, c),
+    };
+    let opacity = f.clamp(0.0, 1.0);
+
+    if let Some(r) = ui.rects.iter_mut().find(|r| r.id == id) {
+        match property {
+            Property::X => r.x = f,
+            Property::Y => r.y = f,
+            // a fixed size so resizing the window doesn't undo it
+            Property::Width => {
+                r.w = f;
+                r.width_mode = Width::Fixed(f);
+            }
+            Property::Height => {
+                r.h = f;
+                r.height_mode = Height::Fixed(f);
+            }
+            Property::Color => r.color = c,
+            Property::OutlineColor => r.outline_color = c,
+            Property::Opacity => r.opacity = opacity,
+            Property::TextColor => {}
+        }
+    } else if let Some(t) = ui.texts.iter_mut().find(|t| t.id == id) {
+        match property {
+            Property::X => t.x = f,
+            Property::Y => t.y = f,
+            Property::Color | Property::TextColor => t.color = c,
+            Property::Opacity => t.opacity = opacity,
+            Property::Width | Property::Height | Property::OutlineColor => {}
+        }
+    } else if let Some(b) = ui.buttons.iter_mut().find(|b| b.id == id) {
+        match property {
+            Property::X => b.x = f,
+            Property::Y => b.y = f,
+            Property::Width => b.w = f,
+            Property::Height => b.h = f,
+            Property::Color => b.bg_color = c,
+            Property::TextColor => b.text_color = c,
+            Property::OutlineColor => b.outline_color = c,
+            Property::Opacity => b.opacity = opacity,
+        }
+    } else if let Some(cv) = ui.canvases.iter_mut().find(|c| c.id == id) {
+        match property {
+            Property::X => cv.x = f,
+            Property::Y => cv.y = f,
+            Property::Width => {
+                cv.w = f;
+                cv.width_mode = Width::Fixed(f);
+            }
+            Property::Height => {
+                cv.h = f;
+                cv.height_mode = Height::Fixed(f);
+            }
+            Property::Color => cv.color = c,
+            Property::OutlineColor => cv.outline_color = c,
+            Property::Opacity => cv.opacity = opacity,
+            Property::TextColor => {}
+        }
+    } else {
+        return false;
+    }
+    true
+}
@@ -1,13 +1,21 @@
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Instant;
 use winit::{
     application::ApplicationHandler,
-    event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent},
+    event::{ElementState, MouseScrollDelta, WindowEvent},
     event_loop::{ActiveEventLoop, ControlFlow, EventLoop},
-    keyboard::PhysicalKey,
-    window::{Window, WindowId},
+    keyboard::{KeyCode, PhysicalKey},
+    window::{CursorGrabMode, CursorIcon, Window, WindowId},
 };
 
-use crate::{Ctx, Fonts, GpuContext, ShapeRenderer, TextRenderer};
+use crate::{
+    Ctx, Fonts, GpuContext, InputEvent, Layer, Modifiers, Recorder, Recording, Replay, ShapeRenderer, TextRenderer,
+};
+use crate::ui::Ui;
+
+/// key that advances a stepped replay by one event
+const REPLAY_STEP_KEY: KeyCode = KeyCode::F10;
 
 pub trait BentoApp: 'static {
     fn once(&mut self, ctx: &mut Ctx);
@@ -18,6 +26,8 @@ struct WindowState {
     gpu: GpuContext,
     window: Arc<Window>,
     scale_factor: f64,
+    /// last pointer state given to the window, to skip redundant calls
+    cursor: Option<(CursorIcon, bool, CursorGrabMode)>,
 }
 
 impl WindowState {
@@ -29,7 +39,23 @@ impl WindowState {
             window,
             gpu,
             scale_factor,
+            cursor: None,
+        }
+    }
+
+    fn apply_cursor(&mut self, ctx: &Ctx) {
+        let wanted = (ctx.cursor(), ctx.is_cursor_visible(), ctx.cursor_grab());
+        if self.cursor == Some(wanted) {
+            return;
         }
+        let (icon, visible, grab) = wanted;
+        self.window.set_cursor(icon);
+        self.window.set_cursor_visible(visible);
+        let grabbed = self.window.set_cursor_grab(grab);
+        if grabbed.is_err() && grab == CursorGrabMode::Locked {
+            let _ = self.window.set_cursor_grab(CursorGrabMode::Confined);
+        }
+        self.cursor = Some(wanted);
     }
 
     fn logical_size(&self) -> (f32, f32) {
@@ -39,36 +65,30 @@ impl WindowState {
         )
     }
 
-    fn on_resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>, ctx: &mut Ctx) {
+    /// resizes the surface and returns the event telling the ui about it
+    fn on_resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) -> InputEvent {
         self.gpu.resize(new_size.width, new_size.height);
-        let (w, h) = self.logical_size();
-        ctx.ui.shape_renderer.resize(w, h);
-        ctx.ui.text_renderer.resize(w, h, self.scale_factor);
-        ctx.resize(w, h);
+        let (width, height) = self.logical_size();
+        InputEvent::Resized { width, height }
     }
 
-    fn on_scale_change(
-        &mut self,
-        scale_factor: f64,
-        new_inner_size: winit::dpi::PhysicalSize<u32>,
-        ctx: &mut Ctx,
-    ) {
+    /// the scale changes the logical size too, so that's reported as well
+    fn on_scale_change(&mut self, scale_factor: f64, ctx: &Ctx) -> Vec<InputEvent> {
         self.scale_factor = scale_factor;
-        self.gpu.resize(new_inner_size.width, new_inner_size.height);
-        let (w, h) = self.logical_size();
-        ctx.ui.shape_renderer.resize(w, h);
-        ctx.ui.text_renderer.resize(w, h, self.scale_factor);
-        ctx.resize(w, h);
+        let resized = self.on_resize(self.window.inner_size());
+        let mut events = vec![InputEvent::ScaleFactorChanged { scale_factor }];
+        if let InputEvent::Resized { width, height } = resized
+            && (width != ctx.window_width || height != ctx.window_height)
+        {
+            events.push(resized);
+        }
+        events
     }
 
     fn render<T: BentoApp>(&mut self, ctx: &mut Ctx, app: &mut T) {
         println!("render");
-        ctx.ui.shape_renderer.clear();
-        ctx.ui.text_renderer.clear();
-
-        ctx.render_all();
-
-        app.update(ctx);
+        build_frame(ctx, app);
+        self.apply_cursor(ctx);
 
         let frame = match self.gpu.begin_frame() {
             Ok(frame) => frame,
@@ -77,42 +97,136 @@ impl WindowState {
 
         let (mut encoder, finisher, view, msaa_view) = frame.begin();
 
-        {
-            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Main Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &msaa_view,
-                    resolve_target: Some(&view),
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
-
-            let (width, height) = self.logical_size();
-            ctx.ui
-                .shape_renderer
-                .render(&self.gpu.device, &self.gpu.queue, &mut pass);
-            ctx.ui.text_renderer.render(
-                &mut ctx.ui.fonts.font_system,
-                width,
-                height,
-                self.scale_factor,
-                &self.gpu.device,
-                &self.gpu.queue,
-                &mut pass,
-            );
-        }
+        let (width, height) = self.logical_size();
+        encode_frame(
+            ctx,
+            &self.gpu.device,
+            &self.gpu.queue,
+            &mut encoder,
+            &msaa_view,
+            &view,
+            width,
+            height,
+            self.scale_factor,
+        );
 
-        ctx.ui.text_renderer.trim_atlas();
         finisher.present(encoder, &self.gpu.queue);
     }
 }
 
+/// clears last frame's draw calls and lets the ui and the app issue new ones
+pub(crate) fn build_frame<T: BentoApp>(ctx: &mut Ctx, app: &mut T) {
+    ctx.begin_frame();
+    ctx.update_animations();
+    ctx.ui.shape_renderer.clear();
+    ctx.ui.text_renderer.clear();
+
+    ctx.render_all();
+
+    ctx.run_update(app);
+
+    ctx.render_overlay();
+    ctx.end_frame();
+}
+
+/// records the main pass for the frame built by `build_frame`
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn encode_frame(
+    ctx: &mut Ctx,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    encoder: &mut wgpu::CommandEncoder,
+    msaa_view: &wgpu::TextureView,
+    view: &wgpu::TextureView,
+    width: f32,
+    height: f32,
+    scale_factor: f64,
+) {
+    let [r, g, b, a] = ctx.ui.theme.background.to_array().map(f64::from);
+    let background = wgpu::Color { r, g, b, a };
+
+    let ui = &mut ctx.ui;
+    ui.shape_renderer.prepare(device, queue);
+    ui.text_renderer
+        .prepare(&mut ui.fonts.font_system, width, height, scale_factor, device, queue);
+    let mut load = wgpu::LoadOp::Clear(background);
+    for layer in [Layer::Base, Layer::Overlay] {
+        let range = ui.shape_renderer.layer_range(layer);
+        draw_target(encoder, ui, None, range, (msaa_view, view), load, &|pass| {
+            ui.text_renderer.render_layer(pass, layer)
+        });
+        load = wgpu::LoadOp::Load;
+    }
+
+    ctx.ui.text_renderer.trim_atlas();
+}
+
+/// draws the vertices in `range` that go onto `target` (`None` for the
+/// screen), then `text` on top. every faded group on the way is drawn into
+/// its layer first, so the target's pass is split around each composite
+fn draw_target(
+    encoder: &mut wgpu::CommandEncoder,
+    ui: &Ui,
+    target: Option<usize>,
+    (lo, hi): (u32, u32),
+    (msaa_view, view): (&wgpu::TextureView, &wgpu::TextureView),
+    mut load: wgpu::LoadOp<wgpu::Color>,
+    text: &dyn Fn(&mut wgpu::RenderPass<'_>),
+) {
+    let shapes = &ui.shape_renderer;
+    let mut from = lo;
+    for (group, after) in shapes.groups_in(target, lo, hi) {
+        if let Some(views) = shapes.group_views(group) {
+            let clear = wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT);
+            draw_target(encoder, ui, Some(group), shapes.group_range(group), views, clear, &|pass| {
+                ui.text_renderer.render_group(pass, group)
+            });
+        }
+        let mut pass = begin_pass(encoder, msaa_view, view, load);
+        shapes.draw_range(&mut pass, target, from, after);
+        load = wgpu::LoadOp::Load;
+        from = after;
+    }
+    let mut pass = begin_pass(encoder, msaa_view, view, load);
+    shapes.draw_range(&mut pass, target, from, hi);
+    text(&mut pass);
+}
+
+fn begin_pass<'a>(
+    encoder: &'a mut wgpu::CommandEncoder,
+    msaa_view: &wgpu::TextureView,
+    view: &wgpu::TextureView,
+    load: wgpu::LoadOp<wgpu::Color>,
+) -> wgpu::RenderPass<'a> {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Main Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: msaa_view,
+            resolve_target: Some(view),
+            ops: wgpu::Operations {
+                load,
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    })
+}
+
+/// the fonts every context starts with
+pub(crate) fn default_fonts() -> Fonts {
+    let mut fonts = Fonts::new();
+    fonts.add("default", "Arial", 14.0);
+    fonts
+}
+
+struct WindowReplay {
+    replay: Replay,
+    start: Instant,
+    stepped: bool,
+}
+
 struct WinitHandler<T: BentoApp> {
     title: String,
     width: u32,
@@ -121,6 +235,9 @@ struct WinitHandler<T: BentoApp> {
     ctx: Option<Ctx>,
     window_state: Option<WindowState>,
     setup_done: bool,
+    record_path: Option<PathBuf>,
+    recorder: Option<Recorder>,
+    replay: Option<WindowReplay>,
 }
 
 impl<T: BentoApp> WinitHandler<T> {
@@ -133,6 +250,42 @@ impl<T: BentoApp> WinitHandler<T> {
             ctx: None,
             window_state: None,
             setup_done: false,
+            record_path: None,
+            recorder: None,
+            replay: None,
+        }
+    }
+
+    /// hands an event to the app and exits if it asked to
+    fn play(&mut self, event_loop: &ActiveEventLoop, input: &InputEvent) {
+        let (Some(ws), Some(ctx)) = (self.window_state.as_mut(), self.ctx.as_mut()) else {
+            return;
+        };
+
+        ctx.dispatch(&mut self.app, input);
+        ws.apply_cursor(ctx);
+        ws.window.request_redraw();
+
+        if ctx.exit {
+            self.window_state = None;
+            event_loop.exit();
+        }
+    }
+
+    /// plays an event from a replay. the os owns the window size and scale,
+    /// so those can only be asked for, the resize that follows is played
+    /// like any other
+    fn play_recorded(&mut self, event_loop: &ActiveEventLoop, input: &InputEvent) {
+        match input {
+            InputEvent::Resized { width, height } => {
+                if let Some(ws) = self.window_state.as_ref() {
+                    let _ = ws
+                        .window
+                        .request_inner_size(winit::dpi::LogicalSize::new(*width, *height));
+                }
+            }
+            InputEvent::ScaleFactorChanged { .. } => {}
+            _ => self.play(event_loop, input),
         }
     }
 }
@@ -143,9 +296,18 @@ impl<T: BentoApp> ApplicationHandler for WinitHandler<T> {
             return;
         }
 
+        // replays open at the recorded size so recorded positions land on
+        // the same layout
+        let size = match &self.replay {
+            Some(replay) => {
+                let recording = &replay.replay.recording;
+                winit::dpi::LogicalSize::new(recording.width as f64, recording.height as f64)
+            }
+            None => winit::dpi::LogicalSize::new(self.width as f64, self.height as f64),
+        };
         let attrs = Window::default_attributes()
             .with_title(&self.title)
-            .with_inner_size(winit::dpi::LogicalSize::new(self.width, self.height));
+            .with_inner_size(size);
         let window = Arc::new(event_loop.create_window(attrs).unwrap());
 
         let ws = pollster::block_on(WindowState::new(window.clone()));
@@ -156,21 +318,32 @@ impl<T: BentoApp> ApplicationHandler for WinitHandler<T> {
         let height = (physical.height as f64 / scale_factor) as f32;
 
         let mut text_renderer = TextRenderer::new(&ws.gpu.device, &ws.gpu.queue, ws.gpu.format);
-        let shape_renderer = ShapeRenderer::new(&ws.gpu.device, ws.gpu.format, width, height);
+        let mut shape_renderer = ShapeRenderer::new(&ws.gpu.device, ws.gpu.format, width, height);
+        shape_renderer.resize(width, height, scale_factor);
         text_renderer.resize(width, height, scale_factor);
 
         self.window_state = Some(ws);
 
-        let mut fonts = Fonts::new();
-        fonts.add("default", "Arial", 14.0);
+        let fonts = default_fonts();
         let mut ctx = Ctx::new(fonts, text_renderer, shape_renderer);
         ctx.resize(width, height);
+        // before `once` so the app's elements start out in the os theme
+        if let Some(theme) = window.theme() {
+            ctx.system_theme_changed(theme.into());
+        }
 
         self.app.once(&mut ctx);
         self.app.update(&mut ctx);
         self.ctx = Some(ctx);
         self.setup_done = true;
 
+        if self.record_path.is_some() {
+            self.recorder = Some(Recorder::new(width, height, scale_factor));
+        }
+        if let Some(replay) = self.replay.as_mut() {
+            replay.start = Instant::now();
+        }
+
         self.window_state.as_ref().unwrap().window.request_redraw();
     }
 
@@ -191,139 +364,119 @@ impl<T: BentoApp> ApplicationHandler for WinitHandler<T> {
 
         event_loop.set_control_flow(ControlFlow::Wait);
 
-        match event {
-            WindowEvent::CursorMoved { position, .. } => {
-                let new_x = (position.x / ws.scale_factor) as f32;
-                let new_y = (position.y / ws.scale_factor) as f32;
-                ctx.mouse.dx = new_x - ctx.mouse.x;
-                ctx.mouse.dy = new_y - ctx.mouse.y;
-                ctx.mouse.x = new_x;
-                ctx.mouse.y = new_y;
-
-                let mouse_snap = ctx.mouse;
-
-                ctx.mark_dirty();
-                self.app.update(ctx);
-                ws.window.request_redraw();
-
-                if ctx.exit {
-                    self.window_state = None;
-                    event_loop.exit();
-                    return;
-                }
-                ctx.mouse.dx = 0.0;
-                ctx.mouse.dy = 0.0;
+        let mut inputs = match event {
+            WindowEvent::Resized(new_size) => vec![ws.on_resize(new_size)],
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                ws.on_scale_change(scale_factor, ctx)
             }
-            WindowEvent::MouseInput { state, button, .. } => {
-                let pressed = state == ElementState::Pressed;
-                match button {
-                    MouseButton::Left => {
-                        ctx.mouse.left_just_pressed = pressed && !ctx.mouse.left_pressed;
-                        ctx.mouse.left_just_released = !pressed && ctx.mouse.left_pressed;
-                        ctx.mouse.left_pressed = pressed;
-                    }
-                    MouseButton::Right => {
-                        ctx.mouse.right_just_pressed = pressed && !ctx.mouse.right_pressed;
-                        ctx.mouse.right_just_released = !pressed && ctx.mouse.right_pressed;
-                        ctx.mouse.right_pressed = pressed;
-                    }
-                    MouseButton::Middle => {
-                        ctx.mouse.middle_just_pressed = pressed && !ctx.mouse.middle_pressed;
-                        ctx.mouse.middle_just_released = !pressed && ctx.mouse.middle_pressed;
-                        ctx.mouse.middle_pressed = pressed;
-                    }
-                    _ => {}
-                }
-                let mouse_snap = ctx.mouse;
-
-                self.app.update(ctx);
-                ws.window.request_redraw();
-
-                if ctx.exit {
-                    self.window_state = None;
-                    event_loop.exit();
-                    return;
-                }
-                ctx.mouse.left_just_pressed = false;
-                ctx.mouse.left_just_released = false;
-                ctx.mouse.right_just_pressed = false;
-                ctx.mouse.right_just_released = false;
-                ctx.mouse.middle_just_pressed = false;
-                ctx.mouse.middle_just_released = false;
+            _ => Vec::new(),
+        };
+        let input = match event {
+            WindowEvent::CursorMoved { position, .. } => Some(InputEvent::CursorMoved {
+                x: (position.x / ws.scale_factor) as f32,
+                y: (position.y / ws.scale_factor) as f32,
+            }),
+            WindowEvent::MouseInput { state, button, .. } => Some(InputEvent::MouseButton {
+                button,
+                pressed: state == ElementState::Pressed,
+            }),
+            WindowEvent::MouseWheel { delta, .. } => Some(match delta {
+                MouseScrollDelta::LineDelta(x, y) => InputEvent::Scroll { x, y },
+                MouseScrollDelta::PixelDelta(pos) => InputEvent::Scroll {
+                    x: pos.x as f32,
+                    y: pos.y as f32,
+                },
+            }),
+            WindowEvent::KeyboardInput { ref event, .. } => match event.physical_key {
+                PhysicalKey::Code(key) => Some(InputEvent::Key {
+                    key,
+                    logical: Some(event.logical_key.clone()),
+                    pressed: event.state == ElementState::Pressed,
+                    repeat: event.repeat,
+                }),
+                _ => None,
+            },
+            WindowEvent::ModifiersChanged(modifiers) => {
+                Some(InputEvent::Modifiers(Modifiers::from_winit(modifiers.state())))
             }
-            WindowEvent::MouseWheel { delta, .. } => {
-                match delta {
-                    MouseScrollDelta::LineDelta(x, y) => {
-                        ctx.mouse.scroll_x = x;
-                        ctx.mouse.scroll_y = y;
-                    }
-                    MouseScrollDelta::PixelDelta(pos) => {
-                        ctx.mouse.scroll_x = pos.x as f32;
-                        ctx.mouse.scroll_y = pos.y as f32;
-                    }
-                }
-                let mouse_snap = ctx.mouse;
-
-                self.app.update(ctx);
-                ws.window.request_redraw();
+            WindowEvent::Ime(winit::event::Ime::Commit(ref text)) => {
+                Some(InputEvent::Text(text.clone()))
+            }
+            WindowEvent::Touch(touch) => Some(InputEvent::Touch {
+                id: touch.id,
+                phase: touch.phase,
+                x: (touch.location.x / ws.scale_factor) as f32,
+                y: (touch.location.y / ws.scale_factor) as f32,
+            }),
+            WindowEvent::Focused(focused) => Some(InputEvent::Focused(focused)),
+            WindowEvent::HoveredFile(ref path) => Some(InputEvent::FileHovered(path.clone())),
+            WindowEvent::HoveredFileCancelled => Some(InputEvent::FileHoverCancelled),
+            WindowEvent::DroppedFile(ref path) => Some(InputEvent::FileDropped(path.clone())),
+            WindowEvent::ThemeChanged(theme) => Some(InputEvent::ThemeChanged(theme.into())),
+            _ => None,
+        };
 
-                if ctx.exit {
-                    self.window_state = None;
-                    event_loop.exit();
-                    return;
-                }
-                ctx.mouse.scroll_x = 0.0;
-                ctx.mouse.scroll_y = 0.0;
+        // without ime, typed text only comes with key presses
+        let typed = match &event {
+            WindowEvent::KeyboardInput { event, .. }
+                if event.state == ElementState::Pressed
+                    && !ctx.input.modifiers.ctrl
+                    && !ctx.input.modifiers.logo =>
+            {
+                event
+                    .text
+                    .as_ref()
+                    .filter(|t| !t.chars().any(char::is_control))
+                    .map(|t| InputEvent::Text(t.to_string()))
             }
-            WindowEvent::KeyboardInput { event, .. } => {
-                let pressed = event.state == ElementState::Pressed;
-                if let PhysicalKey::Code(key) = event.physical_key {
-                    if pressed {
-                        ctx.input.keys_just_pressed.insert(key);
-                        ctx.input.keys_pressed.insert(key);
-                    } else {
-                        ctx.input.keys_just_released.insert(key);
-                        ctx.input.keys_pressed.remove(&key);
-                    }
-                }
-                let mouse_snap = ctx.mouse;
+            _ => None,
+        };
 
-                self.app.update(ctx);
-                ws.window.request_redraw();
+        inputs.extend(input.into_iter().chain(typed));
 
-                if ctx.exit {
-                    self.window_state = None;
-                    event_loop.exit();
-                    return;
+        for input in inputs {
+            let window_change = matches!(
+                input,
+                InputEvent::Resized { .. } | InputEvent::ScaleFactorChanged { .. }
+            );
+            match self.replay.as_mut() {
+                // live input would make the replay diverge, only stepping and
+                // the window's own size changes get through
+                Some(replay) if !window_change => {
+                    let step = replay.stepped
+                        && matches!(
+                            input,
+                            InputEvent::Key {
+                                key: REPLAY_STEP_KEY,
+                                pressed: true,
+                                repeat: false,
+                                ..
+                            }
+                        );
+                    if let Some(recorded) = step.then(|| replay.replay.step()).flatten() {
+                        let recorded = recorded.event.clone();
+                        self.play_recorded(event_loop, &recorded);
+                    }
                 }
-                ctx.input.keys_just_pressed.clear();
-                ctx.input.keys_just_released.clear();
-            }
-            WindowEvent::Ime(winit::event::Ime::Commit(text)) => {
-                for c in text.chars() {}
-                if ctx.exit {
-                    self.window_state = None;
-                    event_loop.exit();
-                    return;
+                _ => {
+                    if let Some(recorder) = self.recorder.as_mut() {
+                        recorder.record(&input);
+                    }
+                    self.play(event_loop, &input);
                 }
             }
-            WindowEvent::ScaleFactorChanged {
-                scale_factor,
-                inner_size_writer: _,
-            } => {
-                let new_inner = ws.window.inner_size();
-                ws.on_scale_change(scale_factor, new_inner, ctx);
-                ctx.mark_dirty();
-                ws.window.request_redraw();
-            }
-            WindowEvent::Resized(new_size) => {
-                ws.on_resize(new_size, ctx);
-                ctx.mark_dirty();
-                ws.window.request_redraw();
-            }
+        }
+        let (Some(ws), Some(ctx)) = (self.window_state.as_mut(), self.ctx.as_mut()) else {
+            return;
+        };
+
+        match event {
             WindowEvent::RedrawRequested => {
                 if ctx.take_dirty() {
                     ws.render(ctx, &mut self.app);
+                } else {
+                    // nothing to draw, but the frame is over all the same
+                    ctx.end_frame();
                 }
             }
             WindowEvent::CloseRequested => {
@@ -332,12 +485,68 @@ impl<T: BentoApp> ApplicationHandler for WinitHandler<T> {
             _ => {}
         }
     }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        let mut wake = None;
+        if let Some(replay) = self.replay.as_mut()
+            && !replay.stepped
+            && self.setup_done
+        {
+            let start = replay.start;
+            let mut due = Vec::new();
+            while let Some(recorded) = replay.replay.next_due(start.elapsed()) {
+                due.push(recorded.event.clone());
+            }
+            wake = replay.replay.next_time().map(|next| start + next);
+
+            for input in due {
+                self.play_recorded(event_loop, &input);
+            }
+        }
+
+        // long presses and requested frames come without any input, so
+        // wake up when one is due
+        if let (Some(ws), Some(ctx)) = (self.window_state.as_ref(), self.ctx.as_mut()) {
+            if ctx.tick(&mut self.app) {
+                ws.window.request_redraw();
+            }
+            if ctx.exit {
+                self.window_state = None;
+                event_loop.exit();
+                return;
+            }
+            // animating apps ask for frames, idle ones sleep until input
+            if ctx.check_redraw() {
+                ws.window.request_redraw();
+            }
+            let deadlines = [ctx.gestures.deadline(), ctx.redraw_deadline()];
+            for deadline in deadlines.into_iter().flatten() {
+                let at = Instant::now() + deadline.saturating_sub(ctx.time());
+                wake = Some(wake.map_or(at, |w: Instant| w.min(at)));
+            }
+        }
+
+        match wake {
+            Some(at) => event_loop.set_control_flow(ControlFlow::WaitUntil(at)),
+            None => event_loop.set_control_flow(ControlFlow::Wait),
+        }
+    }
+
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        if let (Some(recorder), Some(path)) = (self.recorder.take(), self.record_path.as_ref())
+            && let Err(e) = recorder.recording.save(path)
+        {
+            eprintln!("failed to save recording to {}: {e}", path.display());
+        }
+    }
 }
 
 pub struct App {
     title: String,
     width: u32,
     height: u32,
+    record_path: Option<PathBuf>,
+    replay: Option<(Recording, bool)>,
 }
 
 impl App {
@@ -346,12 +555,39 @@ impl App {
             title: title.to_string(),
             width,
             height,
+            record_path: None,
+            replay: None,
         }
     }
 
+    /// records every input event and saves it to `path` when the app exits
+    pub fn record(mut self, path: impl Into<PathBuf>) -> Self {
+        self.record_path = Some(path.into());
+        self
+    }
+
+    /// plays `recording` back with its original timing, ignoring live input.
+    /// the window opens at the recorded size
+    pub fn replay(mut self, recording: Recording) -> Self {
+        self.replay = Some((recording, false));
+        self
+    }
+
+    /// plays `recording` back one event per press of F10
+    pub fn replay_stepped(mut self, recording: Recording) -> Self {
+        self.replay = Some((recording, true));
+        self
+    }
+
     pub fn run<T: BentoApp>(self, app: T) {
         let event_loop = EventLoop::new().unwrap();
         let mut handler = WinitHandler::new(&self.title, self.width, self.height, app);
+        handler.record_path = self.record_path;
+        handler.replay = self.replay.map(|(recording, stepped)| WindowReplay {
+            replay: Replay::new(recording),
+            start: Instant::now(),
+            stepped,
+        });
         event_loop.run_app(&mut handler).unwrap();
     }
 }
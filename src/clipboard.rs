@@ -0,0 +1,242 @@
+use std::fmt;
+use std::io;
+
+use crate::RgbaImage;
+
+/// registry ids of the standard edit commands. they're only registered by
+/// `Commands::register_edit_commands`, apps check `Commands::triggered` for them
+pub const COPY_COMMAND: &str = "bento.copy";
+pub const CUT_COMMAND: &str = "bento.cut";
+pub const PASTE_COMMAND: &str = "bento.paste";
+
+#[derive(Debug)]
+pub enum ClipboardError {
+    /// the backend can't hold this kind of data
+    Unsupported,
+    /// no clipboard to talk to, e.g. no display server
+    Unavailable(String),
+    Io(io::Error),
+}
+
+impl fmt::Display for ClipboardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClipboardError::Unsupported => write!(f, "clipboard format not supported"),
+            ClipboardError::Unavailable(why) => write!(f, "clipboard unavailable: {why}"),
+            ClipboardError::Io(e) => write!(f, "clipboard io error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ClipboardError {}
+
+impl From<io::Error> for ClipboardError {
+    fn from(e: io::Error) -> Self {
+        ClipboardError::Io(e)
+    }
+}
+
+/// where clipboard data actually lives. text is required, images and html
+/// are optional and unsupported unless a backend overrides them. getters
+/// return `Ok(None)` when the clipboard holds nothing of that kind
+pub trait ClipboardBackend {
+    fn get_text(&mut self) -> Result<Option<String>, ClipboardError>;
+    fn set_text(&mut self, text: &str) -> Result<(), ClipboardError>;
+
+    fn get_image(&mut self) -> Result<Option<RgbaImage>, ClipboardError> {
+        Err(ClipboardError::Unsupported)
+    }
+
+    fn set_image(&mut self, image: &RgbaImage) -> Result<(), ClipboardError> {
+        Err(ClipboardError::Unsupported)
+    }
+
+    fn get_html(&mut self) -> Result<Option<String>, ClipboardError> {
+        Err(ClipboardError::Unsupported)
+    }
+
+    /// `alt_text` is what plain text consumers get
+    fn set_html(&mut self, html: &str, alt_text: &str) -> Result<(), ClipboardError> {
+        Err(ClipboardError::Unsupported)
+    }
+}
+
+/// a clipboard that lives in the process, for tests and headless runs.
+/// setting one kind of data clears the others, like a real clipboard
+#[derive(Clone, Debug, Default)]
+pub struct MemoryClipboard {
+    pub text: Option<String>,
+    pub image: Option<RgbaImage>,
+    pub html: Option<String>,
+}
+
+impl ClipboardBackend for MemoryClipboard {
+    fn get_text(&mut self) -> Result<Option<String>, ClipboardError> {
+        Ok(self.text.clone())
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<(), ClipboardError> {
+        *self = Self {
+            text: Some(text.to_string()),
+            ..Self::default()
+        };
+        Ok(())
+    }
+
+    fn get_image(&mut self) -> Result<Option<RgbaImage>, ClipboardError> {
+        Ok(self.image.clone())
+    }
+
+    fn set_image(&mut self, image: &RgbaImage) -> Result<(), ClipboardError> {
+        *self = Self {
+            image: Some(image.clone()),
+            ..Self::default()
+        };
+        Ok(())
+    }
+
+    fn get_html(&mut self) -> Result<Option<String>, ClipboardError> {
+        Ok(self.html.clone())
+    }
+
+    fn set_html(&mut self, html: &str, alt_text: &str) -> Result<(), ClipboardError> {
+        *self = Self {
+            text: Some(alt_text.to_string()),
+            html: Some(html.to_string()),
+            ..Self::default()
+        };
+        Ok(())
+    }
+}
+
+/// the os clipboard through the platform's native clipboard api. it's
+/// opened on first use, so creating one never fails
+#[derive(Default)]
+pub struct SystemClipboard {
+    native: Option<arboard::Clipboard>,
+}
+
+impl SystemClipboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn native(&mut self) -> Result<&mut arboard::Clipboard, ClipboardError> {
+        if self.native.is_none() {
+            self.native = Some(arboard::Clipboard::new().map_err(clipboard_error)?);
+        }
+        Ok(self.native.as_mut().unwrap())
+    }
+}
+
+impl fmt::Debug for SystemClipboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SystemClipboard")
+            .field("open", &self.native.is_some())
+            .finish()
+    }
+}
+
+fn clipboard_error(e: arboard::Error) -> ClipboardError {
+    match e {
+        arboard::Error::ConversionFailure => ClipboardError::Unsupported,
+        e => ClipboardError::Unavailable(e.to_string()),
+    }
+}
+
+/// an empty clipboard or one holding another kind of data is `None`
+fn content<T>(result: Result<T, arboard::Error>) -> Result<Option<T>, ClipboardError> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(arboard::Error::ContentNotAvailable) => Ok(None),
+        Err(e) => Err(clipboard_error(e)),
+    }
+}
+
+impl ClipboardBackend for SystemClipboard {
+    fn get_text(&mut self) -> Result<Option<String>, ClipboardError> {
+        content(self.native()?.get_text())
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<(), ClipboardError> {
+        self.native()?.set_text(text).map_err(clipboard_error)
+    }
+
+    fn get_image(&mut self) -> Result<Option<RgbaImage>, ClipboardError> {
+        let image = content(self.native()?.get_image())?;
+        Ok(image.map(|image| RgbaImage {
+            width: image.width as u32,
+            height: image.height as u32,
+            pixels: image.bytes.into_owned(),
+        }))
+    }
+
+    fn set_image(&mut self, image: &RgbaImage) -> Result<(), ClipboardError> {
+        let image = arboard::ImageData {
+            width: image.width as usize,
+            height: image.height as usize,
+            bytes: image.pixels.as_slice().into(),
+        };
+        self.native()?.set_image(image).map_err(clipboard_error)
+    }
+
+    fn get_html(&mut self) -> Result<Option<String>, ClipboardError> {
+        content(self.native()?.get().html())
+    }
+
+    fn set_html(&mut self, html: &str, alt_text: &str) -> Result<(), ClipboardError> {
+        self.native()?
+            .set_html(html, Some(alt_text))
+            .map_err(clipboard_error)
+    }
+}
+
+/// copy and paste for the app, on `Ctx::clipboard`. windows use the os
+/// clipboard, `Ctx::without_gpu` and `Headless` an in-memory one
+pub struct Clipboard {
+    backend: Box<dyn ClipboardBackend>,
+}
+
+impl Clipboard {
+    pub fn new(backend: impl ClipboardBackend + 'static) -> Self {
+        Self {
+            backend: Box::new(backend),
+        }
+    }
+
+    pub fn system() -> Self {
+        Self::new(SystemClipboard::new())
+    }
+
+    pub fn memory() -> Self {
+        Self::new(MemoryClipboard::default())
+    }
+
+    pub fn set_backend(&mut self, backend: impl ClipboardBackend + 'static) {
+        self.backend = Box::new(backend);
+    }
+
+    pub fn get_text(&mut self) -> Result<Option<String>, ClipboardError> {
+        self.backend.get_text()
+    }
+
+    pub fn set_text(&mut self, text: &str) -> Result<(), ClipboardError> {
+        self.backend.set_text(text)
+    }
+
+    pub fn get_image(&mut self) -> Result<Option<RgbaImage>, ClipboardError> {
+        self.backend.get_image()
+    }
+
+    pub fn set_image(&mut self, image: &RgbaImage) -> Result<(), ClipboardError> {
+        self.backend.set_image(image)
+    }
+
+    pub fn get_html(&mut self) -> Result<Option<String>, ClipboardError> {
+        self.backend.get_html()
+    }
+
+    pub fn set_html(&mut self, html: &str, alt_text: &str) -> Result<(), ClipboardError> {
+        self.backend.set_html(html, alt_text)
+    }
+}
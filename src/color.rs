@@ -32,6 +32,16 @@ impl Color {
         }
     }
 
+    /// scales r, g and b by `factor`, keeping alpha
+    pub fn darken(self, factor: f32) -> Self {
+        Self::new(self.r * factor, self.g * factor, self.b * factor, self.a)
+    }
+
+    /// same color at `a` times its alpha
+    pub fn faded(self, a: f32) -> Self {
+        Self { a: self.a * a, ..self }
+    }
+
     pub const RED: Self = Self::rgb(1.0, 0.0, 0.0);
     pub const GREEN: Self = Self::rgb(0.0, 1.0, 0.0);
     pub const BLUE: Self = Self::rgb(0.0, 0.0, 1.0);
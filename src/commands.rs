@@ -0,0 +1,345 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path as FsPath;
+
+use winit::keyboard::{Key, KeyCode};
+
+use crate::clipboard::{COPY_COMMAND, CUT_COMMAND, PASTE_COMMAND};
+use crate::{Modifiers, Shortcut};
+
+/// one or more shortcuts pressed in sequence, like "Ctrl+K Ctrl+S"
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyBinding(pub Vec<Shortcut>);
+
+impl KeyBinding {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let strokes = text
+            .split_whitespace()
+            .map(Shortcut::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        if strokes.is_empty() {
+            return Err(format!("invalid key binding '{text}': empty"));
+        }
+        Ok(Self(strokes))
+    }
+
+    fn starts_with(&self, prefix: &[Shortcut]) -> bool {
+        self.0.len() >= prefix.len() && self.0[..prefix.len()] == *prefix
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, stroke) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{stroke}")?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Command {
+    pub id: String,
+    /// human readable name for menus and palettes
+    pub title: String,
+    pub default_bindings: Vec<KeyBinding>,
+    pub bindings: Vec<KeyBinding>,
+}
+
+/// bindings that can't all work: identical, or one is the start of another
+/// so the longer chord can never be reached
+#[derive(Clone, Debug, PartialEq)]
+pub struct BindingConflict {
+    pub binding: String,
+    pub commands: Vec<String>,
+}
+
+/// a key press as the registry sees it
+struct Stroke {
+    code: KeyCode,
+    logical: Option<Key>,
+    modifiers: Modifiers,
+}
+
+impl Stroke {
+    fn is(&self, shortcut: &Shortcut) -> bool {
+        shortcut.matches_press(self.code, self.logical.as_ref(), self.modifiers)
+    }
+}
+
+/// named commands with rebindable keyboard shortcuts, on `Ctx::commands`
+#[derive(Default)]
+pub struct Commands {
+    commands: Vec<Command>,
+    /// strokes of a chord that's still being typed
+    pending: Vec<Stroke>,
+    triggered: Vec<String>,
+    queued: Vec<String>,
+}
+
+fn parse_bindings(bindings: &[&str]) -> Vec<KeyBinding> {
+    bindings
+        .iter()
+        .map(|b| KeyBinding::parse(b).unwrap_or_else(|e| panic!("{e}")))
+        .collect()
+}
+
+impl Commands {
+    /// adds a command, panicking if the id is taken or a binding is malformed.
+    /// when two commands share a binding the one registered last fires, so
+    /// app commands win over bento's built-in ones
+    pub fn register(&mut self, id: &str, title: &str, bindings: &[&str]) {
+        if self.get(id).is_some() {
+            panic!("Command with id '{}' already exists!", id);
+        }
+        let bindings = parse_bindings(bindings);
+        self.commands.push(Command {
+            id: id.to_string(),
+            title: title.to_string(),
+            default_bindings: bindings.clone(),
+            bindings,
+        });
+    }
+
+    /// registers copy, cut and paste on Ctrl+C/X/V (Cmd on macOS). bento
+    /// doesn't handle them itself, so apps that do opt in with this
+    pub fn register_edit_commands(&mut self) {
+        self.register(COPY_COMMAND, "Copy", &["CmdOrCtrl+C"]);
+        self.register(CUT_COMMAND, "Cut", &["CmdOrCtrl+X"]);
+        self.register(PASTE_COMMAND, "Paste", &["CmdOrCtrl+V"]);
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Command> {
+        self.commands.iter().find(|c| c.id == id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Command> {
+        self.commands.iter()
+    }
+
+    /// replaces a command's bindings, panics on an unknown id or bad binding
+    pub fn rebind(&mut self, id: &str, bindings: &[&str]) {
+        let bindings = parse_bindings(bindings);
+        self.command_mut(id).bindings = bindings;
+        self.pending.clear();
+    }
+
+    pub fn reset(&mut self, id: &str) {
+        let command = self.command_mut(id);
+        command.bindings = command.default_bindings.clone();
+        self.pending.clear();
+    }
+
+    pub fn reset_all(&mut self) {
+        for command in &mut self.commands {
+            command.bindings = command.default_bindings.clone();
+        }
+        self.pending.clear();
+    }
+
+    fn command_mut(&mut self, id: &str) -> &mut Command {
+        self.commands
+            .iter_mut()
+            .find(|c| c.id == id)
+            .unwrap_or_else(|| panic!("no command with id '{id}'"))
+    }
+
+    /// the first binding as text, e.g. "Ctrl+Shift+S", for menus and tooltips
+    pub fn binding_text(&self, id: &str) -> Option<String> {
+        self.get(id)?.bindings.first().map(|b| b.to_string())
+    }
+
+    pub fn conflicts(&self) -> Vec<BindingConflict> {
+        let mut by_binding: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        let all: Vec<_> = self
+            .commands
+            .iter()
+            .flat_map(|c| c.bindings.iter().map(move |b| (c, b)))
+            .collect();
+
+        for (i, (a, binding)) in all.iter().enumerate() {
+            for (b, other) in &all[i + 1..] {
+                let shorter = if binding.0.len() <= other.0.len() {
+                    binding
+                } else {
+                    other
+                };
+                if binding.starts_with(&other.0) || other.starts_with(&binding.0) {
+                    let ids = by_binding.entry(shorter.to_string()).or_default();
+                    for id in [&a.id, &b.id] {
+                        if !ids.contains(id) {
+                            ids.push(id.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        by_binding
+            .into_iter()
+            .map(|(binding, commands)| BindingConflict { binding, commands })
+            .collect()
+    }
+
+    /// runs a command as if its shortcut was pressed, e.g. from a menu. it
+    /// fires during the next `update`
+    pub fn run(&mut self, id: &str) {
+        if self.get(id).is_none() {
+            panic!("no command with id '{id}'");
+        }
+        self.queued.push(id.to_string());
+    }
+
+    /// whether the command fired for this `update`
+    pub fn triggered(&self, id: &str) -> bool {
+        self.triggered.iter().any(|t| t == id)
+    }
+
+    /// whether the first strokes of a chord were pressed and more are expected
+    pub fn chord_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// the pending strokes as text, e.g. "Ctrl+K", for a status bar
+    pub fn pending_text(&self) -> String {
+        let mut text = String::new();
+        for stroke in &self.pending {
+            let matched = self
+                .commands
+                .iter()
+                .flat_map(|c| &c.bindings)
+                .flat_map(|b| &b.0)
+                .find(|s| stroke.is(s));
+            if let Some(s) = matched {
+                if !text.is_empty() {
+                    text.push(' ');
+                }
+                text.push_str(&s.to_string());
+            }
+        }
+        text
+    }
+
+    pub(crate) fn has_queued(&self) -> bool {
+        !self.queued.is_empty()
+    }
+
+    /// moves commands run from code into this update's triggered list
+    pub(crate) fn begin_update(&mut self) {
+        self.triggered.append(&mut self.queued);
+    }
+
+    pub(crate) fn end_update(&mut self) {
+        self.triggered.clear();
+    }
+
+    /// feeds a key press into chord matching
+    pub(crate) fn key_pressed(&mut self, code: KeyCode, logical: Option<Key>, modifiers: Modifiers) {
+        if is_modifier(code) || self.commands.is_empty() {
+            return;
+        }
+        self.pending.push(Stroke {
+            code,
+            logical,
+            modifiers,
+        });
+
+        if self.match_pending() {
+            return;
+        }
+        // a failed chord still lets its last stroke start a new one
+        if self.pending.len() > 1 {
+            let last = self.pending.pop().unwrap();
+            self.pending.clear();
+            self.pending.push(last);
+            if self.match_pending() {
+                return;
+            }
+        }
+        self.pending.clear();
+    }
+
+    /// true if the pending strokes completed a binding or could still.
+    /// newest commands are checked first
+    fn match_pending(&mut self) -> bool {
+        let mut prefix = false;
+        for command in self.commands.iter().rev() {
+            for binding in &command.bindings {
+                if binding.0.len() < self.pending.len() {
+                    continue;
+                }
+                let matches = binding
+                    .0
+                    .iter()
+                    .zip(&self.pending)
+                    .all(|(shortcut, stroke)| stroke.is(shortcut));
+                if !matches {
+                    continue;
+                }
+                if binding.0.len() == self.pending.len() {
+                    self.triggered.push(command.id.clone());
+                    self.pending.clear();
+                    return true;
+                }
+                prefix = true;
+            }
+        }
+        prefix
+    }
+
+    /// saves bindings that differ from the defaults as json
+    pub fn save(&self, path: impl AsRef<FsPath>) -> io::Result<()> {
+        let overrides: BTreeMap<&str, Vec<String>> = self
+            .commands
+            .iter()
+            .filter(|c| c.bindings != c.default_bindings)
+            .map(|c| (c.id.as_str(), c.bindings.iter().map(|b| b.to_string()).collect()))
+            .collect();
+        let file = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(file, &overrides).map_err(io::Error::other)
+    }
+
+    /// applies bindings saved with `save`. unknown commands are skipped so
+    /// old files keep working, malformed bindings are an error and leave
+    /// every binding as it was
+    pub fn load(&mut self, path: impl AsRef<FsPath>) -> io::Result<()> {
+        let file = BufReader::new(File::open(path)?);
+        let overrides: BTreeMap<String, Vec<String>> = serde_json::from_reader(file)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let parsed = overrides
+            .into_iter()
+            .map(|(id, bindings)| {
+                let bindings: Result<Vec<_>, _> = bindings.iter().map(|b| KeyBinding::parse(b)).collect();
+                Ok((id, bindings?))
+            })
+            .collect::<Result<Vec<_>, String>>()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        for (id, bindings) in parsed {
+            if let Some(command) = self.commands.iter_mut().find(|c| c.id == id) {
+                command.bindings = bindings;
+            }
+        }
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+fn is_modifier(code: KeyCode) -> bool {
+    matches!(
+        code,
+        KeyCode::ShiftLeft
+            | KeyCode::ShiftRight
+            | KeyCode::ControlLeft
+            | KeyCode::ControlRight
+            | KeyCode::AltLeft
+            | KeyCode::AltRight
+            | KeyCode::SuperLeft
+            | KeyCode::SuperRight
+    )
+}
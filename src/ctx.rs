@@ -1,4 +1,12 @@
-use crate::{Color, FontId, Fonts, InputState, MouseState, ShapeRenderer, TextRenderer, ui::Ui};
+use std::time::{Duration, Instant};
+
+use crate::animation::Animations;
+use crate::transition::Transitions;
+use crate::cursor::CursorState;
+use crate::dnd::DragDrop;
+use crate::event::Callbacks;
+use crate::palette::PALETTE_COMMAND;
+use crate::{Canvas, Clipboard, Color, CommandPalette, Commands, Gestures, Touches, InputEvent, ThemeMode, FontId, Fonts, InputState, MouseState, ShapeRenderer, TextRenderer, Transform, ui::Ui};
 
 /// everything the user needs during setup and update
 pub struct Ctx {
@@ -7,11 +15,46 @@ pub struct Ctx {
     pub mouse: MouseState,
     pub input: InputState,
     pub exit: bool,
+    /// input received since the last frame was built, oldest first. the
+    /// polling flags only describe the newest event, this keeps every one in
+    /// order. `update` also runs once per event, see `new_events` for just
+    /// the ones since the previous `update`
+    pub events: Vec<InputEvent>,
+    pub commands: Commands,
+    pub palette: CommandPalette,
+    pub clipboard: Clipboard,
+    pub gestures: Gestures,
+    pub touches: Touches,
+    /// switch between the built-in themes when the os theme changes, on
+    /// by default
+    pub follow_system_theme: bool,
 
     pub window_width: f32,
     pub window_height: f32,
 
     dirty: bool,
+    /// how many of `events` an earlier `update` already got
+    pub(crate) events_seen: usize,
+    pub(crate) panning_canvas: Option<String>,
+    pub(crate) callbacks: Callbacks,
+    pub(crate) focused: Option<String>,
+    /// whether the focus ring shows, only after keyboard or programmatic focus
+    pub(crate) focus_visible: bool,
+    /// a focused button activated from the keyboard during this event
+    pub(crate) activated: Option<String>,
+    pub(crate) dnd: DragDrop,
+    pub(crate) cursor: CursorState,
+    pub(crate) animations: Animations,
+    pub(crate) transitions: Transitions,
+    pub(crate) system_theme: Option<ThemeMode>,
+    start: Instant,
+    /// set for contexts without a window, whose time only moves when told to
+    manual_time: Option<Duration>,
+    frame_time: Duration,
+    delta_time: Duration,
+    frame_count: u64,
+    /// when the app asked for the next frame
+    redraw_at: Option<Duration>,
 }
 
 impl Ctx {
@@ -20,17 +63,139 @@ impl Ctx {
         text_renderer: TextRenderer,
         shape_renderer: ShapeRenderer,
     ) -> Self {
-        Self {
+        let mut ctx = Self {
             ui: Ui::new(text_renderer, shape_renderer, fonts),
             mouse: MouseState::default(),
             input: InputState::default(),
             exit: false,
+            events: Vec::new(),
+            commands: Commands::default(),
+            palette: CommandPalette::default(),
+            clipboard: Clipboard::system(),
+            gestures: Gestures::default(),
+            touches: Touches::default(),
+            follow_system_theme: true,
 
             window_width: 0.0,
             window_height: 0.0,
 
             dirty: false,
+            events_seen: 0,
+            panning_canvas: None,
+            callbacks: Callbacks::default(),
+            focused: None,
+            focus_visible: false,
+            activated: None,
+            dnd: DragDrop::default(),
+            cursor: CursorState::default(),
+            animations: Animations::default(),
+            transitions: Transitions::default(),
+            system_theme: None,
+            start: Instant::now(),
+            manual_time: None,
+            frame_time: Duration::ZERO,
+            delta_time: Duration::ZERO,
+            frame_count: 0,
+            redraw_at: None,
+        };
+        ctx.commands
+            .register(PALETTE_COMMAND, "Command Palette", &["CmdOrCtrl+Shift+P"]);
+        ctx
+    }
+
+    /// a context with the default fonts whose renderers record draw calls
+    /// but never touch a gpu, for tests and tools
+    pub fn without_gpu(width: f32, height: f32) -> Self {
+        let mut ctx = Self::new(
+            crate::app::default_fonts(),
+            TextRenderer::without_gpu(),
+            ShapeRenderer::without_gpu(width, height),
+        );
+        ctx.ui.text_renderer.resize(width, height, 1.0);
+        ctx.resize(width, height);
+        ctx.manual_time = Some(Duration::ZERO);
+        ctx.clipboard = Clipboard::memory();
+        ctx
+    }
+
+    /// time since the context was created. without a window the clock
+    /// stands still until `Harness::advance` moves it, so tests are repeatable
+    pub fn time(&self) -> Duration {
+        self.manual_time.unwrap_or_else(|| self.start.elapsed())
+    }
+
+    /// `time` when the current frame started, the same for the whole frame
+    pub fn frame_time(&self) -> Duration {
+        self.frame_time
+    }
+
+    /// time between the starts of the last two frames, zero for the first
+    pub fn delta_time(&self) -> Duration {
+        self.delta_time
+    }
+
+    /// the part of `events` that came in since the last `update`
+    pub fn new_events(&self) -> &[InputEvent] {
+        &self.events[self.events_seen.min(self.events.len())..]
+    }
+
+    /// frames built so far
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// draws another frame as soon as possible. call it every frame while
+    /// animating, an app that stops asking goes back to idling
+    pub fn request_animation_frame(&mut self) {
+        self.request_redraw_after(Duration::ZERO);
+    }
+
+    /// draws a frame once `after` has passed, e.g. for a blinking caret.
+    /// the earliest request wins and each frame starts with none
+    pub fn request_redraw_after(&mut self, after: Duration) {
+        let at = self.time() + after;
+        self.redraw_at = Some(self.redraw_at.map_or(at, |t| t.min(at)));
+    }
+
+    /// when the next requested frame is due
+    pub(crate) fn redraw_deadline(&self) -> Option<Duration> {
+        self.redraw_at
+    }
+
+    /// marks the ui dirty once a requested frame is due, returns whether it is
+    pub(crate) fn check_redraw(&mut self) -> bool {
+        let due = self.redraw_at.is_some_and(|at| at <= self.time());
+        if due {
+            self.mark_dirty();
         }
+        due
+    }
+
+    pub(crate) fn begin_frame(&mut self) {
+        let now = self.time();
+        self.delta_time = if self.frame_count > 0 {
+            now.saturating_sub(self.frame_time)
+        } else {
+            Duration::ZERO
+        };
+        self.frame_time = now;
+        self.frame_count += 1;
+        self.redraw_at = None;
+    }
+
+    /// starts the next frame's event list
+    pub(crate) fn end_frame(&mut self) {
+        self.events.clear();
+        self.events_seen = 0;
+    }
+
+    pub(crate) fn use_manual_time(&mut self) {
+        self.manual_time = Some(self.time());
+    }
+
+    pub(crate) fn advance_time(&mut self, by: Duration) {
+        let now = self.time();
+        self.manual_time = Some(now + by);
     }
 
     pub fn exit(&mut self) {
@@ -56,21 +221,92 @@ impl Ctx {
     pub fn is_hovered(&self, id: &str) -> bool {
         for rect in &self.ui.rects {
             if rect.id == id {
-                return rect.visible && self.mouse.is_over(rect.x, rect.y, rect.w, rect.h);
+                let transform = rect.transform.around(rect.x + rect.w * 0.5, rect.y + rect.h * 0.5);
+                return rect.visible
+                    && self.mouse.is_over_transformed(rect.x, rect.y, rect.w, rect.h, &transform);
             }
         }
 
         for button in &self.ui.buttons {
             if button.id == id {
-                return button.visible && self.mouse.is_over(button.x, button.y, button.w, button.h);
+                let transform = button
+                    .transform
+                    .around(button.x + button.w * 0.5, button.y + button.h * 0.5);
+                return button.visible
+                    && self
+                        .mouse
+                        .is_over_transformed(button.x, button.y, button.w, button.h, &transform);
+            }
+        }
+
+        for canvas in &self.ui.canvases {
+            if canvas.id == id {
+                return canvas.visible && self.mouse.is_over(canvas.x, canvas.y, canvas.w, canvas.h);
             }
         }
 
         false
     }
 
+    /// the cursor position in the world coordinates of a canvas
+    pub fn canvas_mouse(&self, id: &str) -> Option<(f32, f32)> {
+        self.ui
+            .get::<Canvas>(id)
+            .map(|canvas| canvas.screen_to_world(self.mouse.x, self.mouse.y))
+    }
+
+    /// middle-drag panning and scroll zooming for canvases, run before the
+    /// app sees each pointer event
+    pub(crate) fn update_canvases(&mut self) {
+        if self.mouse.middle_just_pressed {
+            self.panning_canvas = self
+                .ui
+                .canvases
+                .iter()
+                .rev()
+                .find(|c| c.visible && c.pannable && self.mouse.is_over(c.x, c.y, c.w, c.h))
+                .map(|c| c.id.clone());
+        }
+        if !self.mouse.middle_pressed {
+            self.panning_canvas = None;
+        }
+
+        let (dx, dy) = (self.mouse.dx, self.mouse.dy);
+        if let Some(id) = &self.panning_canvas
+            && (dx != 0.0 || dy != 0.0)
+            && let Some(canvas) = self.ui.canvases.iter_mut().find(|c| &c.id == id)
+        {
+            canvas.pan(dx, dy);
+            self.ui.dirty = true;
+        }
+
+        if self.mouse.scroll_y != 0.0 {
+            let mouse = self.mouse;
+            if let Some(canvas) = self
+                .ui
+                .canvases
+                .iter_mut()
+                .rev()
+                .find(|c| c.visible && c.zoomable && mouse.is_over(c.x, c.y, c.w, c.h))
+            {
+                let factor = (1.0 + canvas.zoom_speed).powf(mouse.scroll_y);
+                canvas.zoom_at(mouse.x, mouse.y, factor);
+                self.ui.dirty = true;
+            }
+        }
+    }
+
+    /// whether the command's shortcut was pressed, or it was run from code,
+    /// for this update
+    pub fn command(&self, id: &str) -> bool {
+        self.commands.triggered(id)
+    }
+
+    /// left clicked, or activated with Enter or Space while focused
     pub fn is_clicked(&self, id: &str) -> bool {
-        self.mouse.left_just_pressed && self.is_hovered(id)
+        let clicked = (self.mouse.left_just_pressed && self.is_hovered(id))
+            || self.activated.as_deref() == Some(id);
+        clicked && self.ui.is_enabled(id)
     }
 
     pub fn is_right_clicked(&self, id: &str) -> bool {
@@ -79,8 +315,18 @@ impl Ctx {
 
     pub fn render_all(&mut self) {
         self.render_rects();
+        self.render_canvases();
         self.render_texts();
         self.render_buttons();
+        self.render_focus_ring();
+        self.render_drop_highlight();
+    }
+
+    /// draws bento's own overlays, after the app so they end up on top
+    pub(crate) fn render_overlay(&mut self) {
+        self.ui.begin_overlay();
+        self.render_drag_preview();
+        self.render_palette();
     }
 
     pub fn render_rects(&mut self) {
@@ -88,23 +334,77 @@ impl Ctx {
             if !rect.visible {
                 continue;
             }
+            let transform = rect.transform.around(rect.x + rect.w * 0.5, rect.y + rect.h * 0.5);
+            self.ui.shape_renderer.push_transform(transform);
+            self.ui.shape_renderer.push_opacity(rect.opacity);
+            let (color, outline) = (rect.color.to_array(), rect.outline_color.to_array());
+            if rect.radius > 0.0 {
+                self.ui.shape_renderer.rounded_rect(
+                    rect.x,
+                    rect.y,
+                    rect.w,
+                    rect.h,
+                    rect.radius,
+                    color,
+                    outline,
+                    rect.outline_thickness,
+                );
+            } else {
+                self.ui.shape_renderer.rect(
+                    rect.x,
+                    rect.y,
+                    rect.w,
+                    rect.h,
+                    color,
+                    outline,
+                    rect.outline_thickness,
+                );
+            }
+            self.ui.shape_renderer.pop_opacity();
+            self.ui.shape_renderer.pop_transform();
+        }
+    }
+
+    pub fn render_canvases(&mut self) {
+        for canvas in &mut self.ui.canvases {
+            canvas.group = None;
+            if !canvas.visible {
+                continue;
+            }
+            canvas.group = self.ui.shape_renderer.push_group(canvas.opacity);
             self.ui.shape_renderer.rect(
-                rect.x,
-                rect.y,
-                rect.w,
-                rect.h,
-                rect.color.to_array(),
-                rect.outline_color.to_array(),
-                rect.outline_thickness,
+                canvas.x,
+                canvas.y,
+                canvas.w,
+                canvas.h,
+                canvas.color.to_array(),
+                canvas.outline_color.to_array(),
+                canvas.outline_thickness,
             );
+            self.ui.shape_renderer.pop_group();
         }
     }
 
     pub fn render_texts(&mut self) {
-        for text in &self.ui.texts {
+        let transforms: Vec<_> = self
+            .ui
+            .texts
+            .iter()
+            .map(|text| {
+                if text.transform.is_identity() {
+                    return Transform::IDENTITY;
+                }
+                let (w, h) = self.ui.fonts.measure(&text.text, text.font_id);
+                text.transform.around(text.x + w * 0.5, text.y + h * 0.5)
+            })
+            .collect();
+
+        for (text, transform) in self.ui.texts.iter().zip(transforms) {
             if !text.visible {
                 continue;
             }
+            self.ui.text_renderer.push_transform(transform);
+            self.ui.text_renderer.push_opacity(text.opacity);
             self.ui.text_renderer.draw(
                 &mut self.ui.fonts.font_system,
                 text.font_family.clone(),
@@ -114,10 +414,13 @@ impl Ctx {
                 text.y,
                 text.color,
             );
+            self.ui.text_renderer.pop_opacity();
+            self.ui.text_renderer.pop_transform();
         }
     }
 
     pub fn render_buttons(&mut self) {
+        let now = self.frame_time();
         let button_data: Vec<_> = self
             .ui
             .buttons
@@ -125,27 +428,19 @@ impl Ctx {
             .filter(|button| button.visible)
             .map(|button| {
                 let entry = self.ui.fonts.get(button.font_id);
-                let hovered = self.is_hovered(&button.id);
-
-                let (bg_color, text_color, outline_color) = if hovered {
-                    (
-                        button.bg_color_hover,
-                        button.text_color_hover,
-                        button.outline_color_hover,
-                    )
-                } else {
-                    (button.bg_color, button.text_color, button.outline_color)
-                };
-
                 (
+                    button.id.clone(),
                     button.x,
                     button.y,
                     button.w,
                     button.h,
-                    bg_color,
-                    text_color,
-                    outline_color,
-                    button.outline_thickness,
+                    button.radius,
+                    button
+                        .transform
+                        .around(button.x + button.w * 0.5, button.y + button.h * 0.5),
+                    self.button_look(button),
+                    button.transition,
+                    button.opacity,
                     button.padding,
                     button.text.clone(),
                     entry.family.clone(),
@@ -154,30 +449,25 @@ impl Ctx {
             })
             .collect();
 
-        for (
-            x,
-            y,
-            w,
-            h,
-            bg_color,
-            text_color,
-            outline_color,
-            outline_thickness,
-            padding,
-            text,
-            family,
-            size,
-        ) in button_data
+        let ids: Vec<_> = button_data.iter().map(|b| b.0.clone()).collect();
+        self.transitions.retain(&ids);
+
+        for (id, x, y, w, h, radius, transform, target, transition, opacity, padding, text, family, size) in
+            button_data
         {
-            self.ui.shape_renderer.rect(
-                x,
-                y,
-                w,
-                h,
-                bg_color.to_array(),
-                outline_color.to_array(),
-                outline_thickness,
-            );
+            let (look, blending) = self.transitions.blend(&id, target, transition, now);
+            if blending {
+                self.request_animation_frame();
+            }
+
+            self.ui.push_transform(transform);
+            self.ui.push_opacity(opacity);
+            let (bg, outline) = (look.bg.to_array(), look.outline.to_array());
+            if radius > 0.0 {
+                self.ui.shape_renderer.rounded_rect(x, y, w, h, radius, bg, outline, look.outline_thickness);
+            } else {
+                self.ui.shape_renderer.rect(x, y, w, h, bg, outline, look.outline_thickness);
+            }
 
             let text_x = x + padding;
             let text_y = y + padding;
@@ -189,8 +479,10 @@ impl Ctx {
                 &text,
                 text_x,
                 text_y,
-                text_color,
+                look.text,
             );
+            self.ui.pop_opacity();
+            self.ui.pop_transform();
         }
     }
 }
@@ -0,0 +1,67 @@
+use winit::window::{CursorGrabMode, CursorIcon};
+
+use crate::Ctx;
+
+/// what the app asked for, the window applies it after every event
+pub(crate) struct CursorState {
+    icon: Option<CursorIcon>,
+    visible: bool,
+    grab: CursorGrabMode,
+}
+
+impl Default for CursorState {
+    fn default() -> Self {
+        Self {
+            icon: None,
+            visible: true,
+            grab: CursorGrabMode::None,
+        }
+    }
+}
+
+impl Ctx {
+    /// shows `icon` everywhere until `clear_cursor`, over what elements ask for
+    pub fn set_cursor(&mut self, icon: CursorIcon) {
+        self.cursor.icon = Some(icon);
+    }
+
+    pub fn clear_cursor(&mut self) {
+        self.cursor.icon = None;
+    }
+
+    /// the pointer for this frame: the override, then a closed hand while
+    /// dragging or panning, then the topmost hovered element's cursor and
+    /// an open hand over draggable elements
+    pub fn cursor(&self) -> CursorIcon {
+        if let Some(icon) = self.cursor.icon {
+            return icon;
+        }
+        if self.is_dragging_payload() || self.panning_canvas.is_some() {
+            return CursorIcon::Grabbing;
+        }
+        match self.hovered_cursor() {
+            Some(icon) => icon,
+            None if self.is_over_draggable() => CursorIcon::Grab,
+            None => CursorIcon::Default,
+        }
+    }
+
+    /// hides the pointer, e.g. while dragging a value with relative motion
+    pub fn set_cursor_visible(&mut self, visible: bool) {
+        self.cursor.visible = visible;
+    }
+
+    pub fn is_cursor_visible(&self) -> bool {
+        self.cursor.visible
+    }
+
+    /// keeps the pointer inside the window (`Confined`) or in place
+    /// (`Locked`). platforms without locking fall back to confining
+    pub fn set_cursor_grab(&mut self, grab: CursorGrabMode) {
+        self.cursor.grab = grab;
+    }
+
+    pub fn cursor_grab(&self) -> CursorGrabMode {
+        self.cursor.grab
+    }
+}
@@ -0,0 +1,302 @@
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use winit::keyboard::KeyCode;
+
+use crate::event::Trigger;
+use crate::{Ctx, InputEvent};
+
+/// an element being dragged with its payload
+struct DragSession {
+    source: String,
+    payload: TypeId,
+    /// where the element was grabbed, relative to its top left corner
+    grab: (f32, f32),
+    size: (f32, f32),
+}
+
+/// draggable elements, drop targets and the drag in progress, see
+/// `Ctx::set_draggable` and `Ctx::accept_drops`
+#[derive(Default)]
+pub(crate) struct DragDrop {
+    payloads: HashMap<String, Box<dyn Any>>,
+    /// payload types each drop target accepts
+    targets: HashMap<String, Vec<TypeId>>,
+    session: Option<DragSession>,
+    /// valid target under the cursor
+    hovered: Option<String>,
+    /// source and target of a drop during this event
+    dropped: Option<(String, String)>,
+
+    /// elements that take files dragged in from the os
+    file_targets: HashSet<String>,
+    /// files hovering over the window
+    files: Vec<PathBuf>,
+    /// file target under the cursor while files hover
+    file_target: Option<String>,
+    /// file dropped during this event and the target it landed on
+    dropped_file: Option<(PathBuf, Option<String>)>,
+}
+
+impl Ctx {
+    /// lets the element be dragged onto targets accepting `T`, carrying a
+    /// copy of `payload`. calling it again replaces the payload
+    pub fn set_draggable<T: Clone + 'static>(&mut self, id: &str, payload: T) {
+        self.dnd.payloads.insert(id.to_string(), Box::new(payload));
+    }
+
+    /// makes the element a drop target for payloads of type `T`
+    pub fn accept_drops<T: 'static>(&mut self, id: &str) {
+        let types = self.dnd.targets.entry(id.to_string()).or_default();
+        if !types.contains(&TypeId::of::<T>()) {
+            types.push(TypeId::of::<T>());
+        }
+    }
+
+    /// stops the element from being dragged or dropped on
+    pub fn clear_drag(&mut self, id: &str) {
+        self.dnd.payloads.remove(id);
+        self.dnd.targets.remove(id);
+        self.dnd.file_targets.remove(id);
+        if self.dnd.session.as_ref().is_some_and(|s| s.source == id) {
+            self.cancel_drag();
+        }
+    }
+
+    /// runs `f` when a payload or a file is dropped on the element
+    pub fn on_drop(&mut self, id: &str, f: impl FnMut(&mut Ctx) + 'static) {
+        self.add_callback(id, Trigger::Drop, Box::new(f));
+    }
+
+    /// the element being dragged, if any
+    pub fn drag_source(&self) -> Option<&str> {
+        self.dnd.session.as_ref().map(|s| s.source.as_str())
+    }
+
+    /// the payload being dragged, if there is one of type `T`
+    pub fn drag_payload<T: Clone + 'static>(&self) -> Option<T> {
+        self.payload(self.drag_source()?)
+    }
+
+    /// whether a drag is over this target and it would take the payload
+    pub fn is_drop_hovered(&self, id: &str) -> bool {
+        self.dnd.hovered.as_deref() == Some(id)
+    }
+
+    /// the payload dropped on `target` during this event, if it's a `T`
+    pub fn dropped<T: Clone + 'static>(&self, target: &str) -> Option<T> {
+        let (source, dropped_on) = self.dnd.dropped.as_ref()?;
+        if dropped_on != target {
+            return None;
+        }
+        self.payload(source)
+    }
+
+    /// the element whose payload was dropped during this event
+    pub fn drop_source(&self) -> Option<&str> {
+        self.dnd.dropped.as_ref().map(|(source, _)| source.as_str())
+    }
+
+    /// makes the element a drop target for files dragged in from the os.
+    /// the target is found at the last known cursor position, which may be
+    /// stale, see `dropped_file`
+    pub fn accept_files(&mut self, id: &str) {
+        self.dnd.file_targets.insert(id.to_string());
+    }
+
+    /// files being dragged over the window, empty when there are none
+    pub fn hovered_files(&self) -> &[PathBuf] {
+        &self.dnd.files
+    }
+
+    /// whether files hover over this file target
+    pub fn is_file_hovered(&self, id: &str) -> bool {
+        self.dnd.file_target.as_deref() == Some(id)
+    }
+
+    /// the file dropped on the window during this event, with the cursor
+    /// position. the os drops several files as one event each. the position
+    /// is where the cursor was last seen moving, and many platforms don't
+    /// report cursor moves while files are dragged in from the os, so it
+    /// can be wherever the cursor was before the drag came in
+    pub fn dropped_file(&self) -> Option<(&Path, f32, f32)> {
+        let (path, _) = self.dnd.dropped_file.as_ref()?;
+        Some((path.as_path(), self.mouse.x, self.mouse.y))
+    }
+
+    /// the file dropped on this file target during this event
+    pub fn dropped_file_on(&self, id: &str) -> Option<&Path> {
+        match &self.dnd.dropped_file {
+            Some((path, Some(target))) if target == id => Some(path.as_path()),
+            _ => None,
+        }
+    }
+
+    pub fn cancel_drag(&mut self) {
+        if self.dnd.session.take().is_some() {
+            self.dnd.hovered = None;
+            self.mark_dirty();
+        }
+    }
+
+    fn payload<T: Clone + 'static>(&self, source: &str) -> Option<T> {
+        self.dnd.payloads.get(source)?.downcast_ref::<T>().cloned()
+    }
+
+    /// the topmost target under the cursor that accepts what's dragged
+    fn drop_target(&self) -> Option<String> {
+        let session = self.dnd.session.as_ref()?;
+        self.hovered_elements().into_iter().find(|id| {
+            *id != session.source
+                && self
+                    .dnd
+                    .targets
+                    .get(id)
+                    .is_some_and(|types| types.contains(&session.payload))
+        })
+    }
+
+    /// the topmost file target under the cursor
+    fn file_target(&self) -> Option<String> {
+        self.hovered_elements()
+            .into_iter()
+            .find(|id| self.dnd.file_targets.contains(id))
+    }
+
+    /// tracks files dragged over the window from the os
+    pub(crate) fn update_file_drop(&mut self, event: &InputEvent) {
+        match event {
+            InputEvent::FileHovered(path) => {
+                self.dnd.files.push(path.clone());
+                self.dnd.file_target = self.file_target();
+                self.mark_dirty();
+            }
+            InputEvent::FileHoverCancelled => {
+                self.dnd.files.clear();
+                self.dnd.file_target = None;
+                self.mark_dirty();
+            }
+            InputEvent::FileDropped(path) => {
+                // some platforms skip the hover, so find the target again
+                let target = self.file_target();
+                self.dnd.files.retain(|p| p != path);
+                if self.dnd.files.is_empty() {
+                    self.dnd.file_target = None;
+                }
+                self.dnd.dropped_file = Some((path.clone(), target.clone()));
+                self.mark_dirty();
+                if let Some(target) = target {
+                    self.fire_callbacks(Trigger::Drop, &target);
+                }
+            }
+            InputEvent::CursorMoved { .. } if !self.dnd.files.is_empty() => {
+                self.dnd.file_target = self.file_target();
+            }
+            _ => {}
+        }
+    }
+
+    /// starts a drag once the gesture layer sees one on a draggable element,
+    /// then tracks the target under the cursor until the button is released
+    pub(crate) fn update_drag(&mut self, event: &InputEvent) {
+        if self.gestures.drag_started
+            && let Some(source) = self.gestures.target.clone()
+            && let Some(payload) = self.dnd.payloads.get(&source)
+            && let Some(((x, y, w, h), _)) = self.element_bounds(&source)
+        {
+            let (dx, dy) = self.gestures.drag_total;
+            self.dnd.session = Some(DragSession {
+                payload: (**payload).type_id(),
+                grab: (self.mouse.x - dx - x, self.mouse.y - dy - y),
+                size: (w, h),
+                source,
+            });
+        }
+        if self.dnd.session.is_none() {
+            return;
+        }
+
+        match event {
+            InputEvent::CursorMoved { .. } => {
+                self.dnd.hovered = self.drop_target();
+                self.mark_dirty();
+            }
+            InputEvent::Key {
+                key: KeyCode::Escape,
+                pressed: true,
+                ..
+            }
+            | InputEvent::Focused(false) => self.cancel_drag(),
+            _ if self.gestures.drag_ended => {
+                let session = self.dnd.session.take().unwrap();
+                self.mark_dirty();
+                if let Some(target) = self.dnd.hovered.take() {
+                    self.events.push(InputEvent::ElementDropped {
+                        source: session.source.clone(),
+                        target: target.clone(),
+                    });
+                    self.dnd.dropped = Some((session.source, target.clone()));
+                    self.fire_callbacks(Trigger::Drop, &target);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn is_dragging_payload(&self) -> bool {
+        self.dnd.session.is_some()
+    }
+
+    /// whether the topmost element under the cursor can be dragged
+    pub(crate) fn is_over_draggable(&self) -> bool {
+        self.hovered_element()
+            .is_some_and(|id| self.dnd.payloads.contains_key(&id))
+    }
+
+    pub(crate) fn end_drag(&mut self) {
+        self.dnd.dropped = None;
+        self.dnd.dropped_file = None;
+    }
+
+    /// outlines the target a drop would land on
+    pub(crate) fn render_drop_highlight(&mut self) {
+        let Some(id) = self.dnd.hovered.as_ref().or(self.dnd.file_target.as_ref()) else {
+            return;
+        };
+        let Some(((x, y, w, h), transform)) = self.element_bounds(id) else {
+            return;
+        };
+        self.ui.shape_renderer.push_transform(transform);
+        self.ui.shape_renderer.rect(
+            x,
+            y,
+            w,
+            h,
+            [0.0; 4],
+            self.ui.drop_highlight_color.to_array(),
+            self.ui.focus_ring_thickness,
+        );
+        self.ui.shape_renderer.pop_transform();
+    }
+
+    /// a translucent box the size of the source, following the cursor
+    pub(crate) fn render_drag_preview(&mut self) {
+        let Some(session) = &self.dnd.session else {
+            return;
+        };
+        let (w, h) = session.size;
+        let x = self.mouse.x - session.grab.0;
+        let y = self.mouse.y - session.grab.1;
+        self.ui.shape_renderer.rounded_rect(
+            x,
+            y,
+            w,
+            h,
+            4.0,
+            self.ui.drag_preview_color.to_array(),
+            [0.0; 4],
+            0.0,
+        );
+    }
+}
@@ -0,0 +1,410 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use winit::event::{MouseButton, TouchPhase};
+use winit::keyboard::{Key, KeyCode};
+
+use crate::input::default_logical;
+use crate::palette::PALETTE_COMMAND;
+use crate::{BentoApp, Ctx, Modifiers, Property, ThemeMode};
+
+/// input as bento sees it, in logical pixels. the window turns winit events
+/// into these, and tests and replays can feed them in directly
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum InputEvent {
+    CursorMoved { x: f32, y: f32 },
+    MouseButton { button: MouseButton, pressed: bool },
+    Scroll { x: f32, y: f32 },
+    /// a finger with a stable `id` from touch down to lift
+    Touch { id: u64, phase: TouchPhase, x: f32, y: f32 },
+    /// `logical` is what the key produces on the current layout, inferred
+    /// from a us layout when missing
+    Key {
+        key: KeyCode,
+        #[serde(default)]
+        logical: Option<Key>,
+        pressed: bool,
+        #[serde(default)]
+        repeat: bool,
+    },
+    Modifiers(Modifiers),
+    Text(String),
+    Resized { width: f32, height: f32 },
+    ScaleFactorChanged { scale_factor: f64 },
+    /// the window gained or lost keyboard focus
+    Focused(bool),
+    /// a file from the os is dragged over the window, one event per file
+    FileHovered(PathBuf),
+    FileHoverCancelled,
+    FileDropped(PathBuf),
+    /// an element gained or lost focus. bento produces these itself, they
+    /// only show up in `Ctx::events`
+    ElementFocused(String),
+    ElementBlurred(String),
+    /// a drag from `source` was dropped on `target`, also produced by bento
+    ElementDropped { source: String, target: String },
+    /// a tween started with `Ctx::animate` reached its target
+    AnimationFinished { id: String, property: Property },
+    /// the os switched between light and dark mode
+    ThemeChanged(ThemeMode),
+}
+
+type Callback = Box<dyn FnMut(&mut Ctx)>;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Trigger {
+    Click,
+    HoverEnter,
+    HoverLeave,
+    Focus,
+    Blur,
+    Drop,
+}
+
+/// per-element callbacks, run in registration order before `update`
+#[derive(Default)]
+pub(crate) struct Callbacks {
+    entries: Vec<(String, Trigger, Callback)>,
+    hovered: HashSet<String>,
+    /// ids cleared from inside a callback, applied once the callbacks return
+    cleared: Vec<String>,
+}
+
+impl Ctx {
+    /// runs `f` whenever the element is left clicked
+    pub fn on_click(&mut self, id: &str, f: impl FnMut(&mut Ctx) + 'static) {
+        self.add_callback(id, Trigger::Click, Box::new(f));
+    }
+
+    /// runs `f` when the cursor moves onto the element
+    pub fn on_hover_enter(&mut self, id: &str, f: impl FnMut(&mut Ctx) + 'static) {
+        self.add_callback(id, Trigger::HoverEnter, Box::new(f));
+    }
+
+    /// runs `f` when the cursor moves off the element
+    pub fn on_hover_leave(&mut self, id: &str, f: impl FnMut(&mut Ctx) + 'static) {
+        self.add_callback(id, Trigger::HoverLeave, Box::new(f));
+    }
+
+    pub fn on_focus(&mut self, id: &str, f: impl FnMut(&mut Ctx) + 'static) {
+        self.add_callback(id, Trigger::Focus, Box::new(f));
+    }
+
+    pub fn on_blur(&mut self, id: &str, f: impl FnMut(&mut Ctx) + 'static) {
+        self.add_callback(id, Trigger::Blur, Box::new(f));
+    }
+
+    /// drops every callback registered for `id`
+    pub fn clear_callbacks(&mut self, id: &str) {
+        self.callbacks.entries.retain(|(target, _, _)| target != id);
+        self.callbacks.hovered.remove(id);
+        self.callbacks.cleared.push(id.to_string());
+    }
+
+    pub(crate) fn add_callback(&mut self, id: &str, trigger: Trigger, f: Callback) {
+        if matches!(trigger, Trigger::HoverEnter | Trigger::HoverLeave) && self.is_hovered(id) {
+            self.callbacks.hovered.insert(id.to_string());
+        }
+        self.callbacks.entries.push((id.to_string(), trigger, f));
+    }
+
+    fn run_callbacks(&mut self, event: &InputEvent) {
+        if self.callbacks.entries.is_empty() {
+            return;
+        }
+
+        let click = matches!(
+            event,
+            InputEvent::MouseButton {
+                button: MouseButton::Left,
+                pressed: true
+            }
+        ) || self.activated.is_some();
+        let pointer = matches!(
+            event,
+            InputEvent::CursorMoved { .. } | InputEvent::Scroll { .. } | InputEvent::Resized { .. }
+        );
+        if !click && !pointer {
+            return;
+        }
+
+        // decide everything that fires before running any of it, so callbacks
+        // that move or hide elements don't change what this event means
+        let mut fired = Vec::new();
+        let mut entered = HashSet::new();
+        let mut left = HashSet::new();
+        for (i, (id, trigger, _)) in self.callbacks.entries.iter().enumerate() {
+            let hovered = self.is_hovered(id);
+            let was_hovered = self.callbacks.hovered.contains(id);
+            let fire = match trigger {
+                Trigger::Click => {
+                    click
+                        && (hovered || self.activated.as_deref() == Some(id.as_str()))
+                        && self.ui.is_enabled(id)
+                }
+                Trigger::HoverEnter => pointer && hovered && !was_hovered,
+                Trigger::HoverLeave => pointer && !hovered && was_hovered,
+                Trigger::Focus | Trigger::Blur | Trigger::Drop => false,
+            };
+            if fire {
+                fired.push(i);
+            }
+            if pointer && hovered && !was_hovered {
+                entered.insert(id.clone());
+            }
+            if pointer && !hovered && was_hovered {
+                left.insert(id.clone());
+            }
+        }
+        for id in &left {
+            self.callbacks.hovered.remove(id);
+        }
+        self.callbacks.hovered.extend(entered);
+
+        self.run_entries(fired);
+    }
+
+    /// runs the `trigger` callbacks of `id` right away
+    pub(crate) fn fire_callbacks(&mut self, trigger: Trigger, id: &str) {
+        let fired = self
+            .callbacks
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, (target, t, _))| *t == trigger && target == id)
+            .map(|(i, _)| i)
+            .collect();
+        self.run_entries(fired);
+    }
+
+    fn run_entries(&mut self, fired: Vec<usize>) {
+        if fired.is_empty() {
+            return;
+        }
+
+        // callbacks get the whole ctx, so they run with the list taken out and
+        // anything they register is appended afterwards
+        self.callbacks.cleared.clear();
+        let mut entries = std::mem::take(&mut self.callbacks.entries);
+        for i in fired {
+            (entries[i].2)(self);
+        }
+        let cleared = std::mem::take(&mut self.callbacks.cleared);
+        entries.retain(|(id, _, _)| !cleared.contains(id));
+        entries.append(&mut self.callbacks.entries);
+        self.callbacks.entries = entries;
+    }
+}
+
+impl Ctx {
+    /// applies `event` to the input state, lets the app react to it and then
+    /// resets the per-event flags, the same way for windows and tests
+    pub(crate) fn dispatch<T: BentoApp>(&mut self, app: &mut T, event: &InputEvent) {
+        let emulated = self.update_touch(event);
+        self.dispatch_one(app, event);
+        for event in &emulated {
+            self.dispatch_one(app, event);
+        }
+    }
+
+    fn dispatch_one<T: BentoApp>(&mut self, app: &mut T, event: &InputEvent) {
+        self.begin_event(event);
+        if self.update_palette(event) {
+            self.end_event(event);
+            return;
+        }
+        self.update_gestures(event);
+        self.update_drag(event);
+        self.update_file_drop(event);
+        if let InputEvent::Key {
+            key,
+            pressed: true,
+            repeat: false,
+            ..
+        } = event
+        {
+            let logical = self.input.logical_keys.get(key).cloned();
+            self.commands.key_pressed(*key, logical, self.input.modifiers);
+        }
+        self.update_focus(event);
+        if matches!(
+            event,
+            InputEvent::CursorMoved { .. } | InputEvent::MouseButton { .. } | InputEvent::Scroll { .. }
+        ) {
+            self.update_canvases();
+        }
+        self.run_callbacks(event);
+        if !matches!(
+            event,
+            InputEvent::Resized { .. } | InputEvent::ScaleFactorChanged { .. }
+        ) {
+            self.run_update(app);
+        }
+        self.end_event(event);
+    }
+
+    /// calls the app's `update`, `new_events` holds what came in since the
+    /// last one
+    pub(crate) fn run_update<T: BentoApp>(&mut self, app: &mut T) {
+        self.commands.begin_update();
+        if self.commands.triggered(PALETTE_COMMAND) {
+            self.palette.toggle();
+            self.mark_dirty();
+        }
+        app.update(self);
+        self.commands.end_update();
+        self.events_seen = self.events.len();
+        // commands run from code fire on the next update, so ask for a frame
+        if self.commands.has_queued() {
+            self.mark_dirty();
+        }
+    }
+
+    fn begin_event(&mut self, event: &InputEvent) {
+        self.events.push(event.clone());
+        match event {
+            InputEvent::CursorMoved { x, y } => {
+                self.mouse.dx = x - self.mouse.x;
+                self.mouse.dy = y - self.mouse.y;
+                self.mouse.x = *x;
+                self.mouse.y = *y;
+                self.mark_dirty();
+            }
+            InputEvent::MouseButton { button, pressed } => {
+                let pressed = *pressed;
+                match button {
+                    MouseButton::Left => {
+                        self.mouse.left_just_pressed = pressed && !self.mouse.left_pressed;
+                        self.mouse.left_just_released = !pressed && self.mouse.left_pressed;
+                        self.mouse.left_pressed = pressed;
+                        // buttons look different while pressed
+                        self.mark_dirty();
+                    }
+                    MouseButton::Right => {
+                        self.mouse.right_just_pressed = pressed && !self.mouse.right_pressed;
+                        self.mouse.right_just_released = !pressed && self.mouse.right_pressed;
+                        self.mouse.right_pressed = pressed;
+                    }
+                    MouseButton::Middle => {
+                        self.mouse.middle_just_pressed = pressed && !self.mouse.middle_pressed;
+                        self.mouse.middle_just_released = !pressed && self.mouse.middle_pressed;
+                        self.mouse.middle_pressed = pressed;
+                    }
+                    _ => {}
+                }
+            }
+            InputEvent::Scroll { x, y } => {
+                self.mouse.scroll_x = *x;
+                self.mouse.scroll_y = *y;
+            }
+            InputEvent::Key {
+                key,
+                logical,
+                pressed,
+                repeat,
+            } => {
+                self.input.modifiers.set_key(*key, *pressed);
+                if *pressed {
+                    let logical = logical
+                        .clone()
+                        .unwrap_or_else(|| default_logical(*key, self.input.modifiers.shift));
+                    self.input.logical_keys.insert(*key, logical);
+                    if *repeat {
+                        self.input.keys_repeated.insert(*key);
+                    } else {
+                        self.input.keys_just_pressed.insert(*key);
+                    }
+                    self.input.keys_pressed.insert(*key);
+                } else {
+                    self.input.keys_just_released.insert(*key);
+                    self.input.keys_pressed.remove(key);
+                }
+            }
+            InputEvent::Modifiers(modifiers) => {
+                self.input.modifiers = *modifiers;
+            }
+            InputEvent::Text(text) => {
+                self.input.text.push_str(text);
+            }
+            InputEvent::Resized { width, height } => {
+                self.ui.shape_renderer.resize(*width, *height, self.ui.text_renderer.scale_factor());
+                self.ui.text_renderer.resize(*width, *height, self.ui.text_renderer.scale_factor());
+                self.resize(*width, *height);
+                self.mark_dirty();
+            }
+            InputEvent::ScaleFactorChanged { scale_factor } => {
+                let (w, h) = (self.window_width, self.window_height);
+                self.ui.shape_renderer.resize(w, h, *scale_factor);
+                self.ui.text_renderer.resize(w, h, *scale_factor);
+                self.mark_dirty();
+            }
+            InputEvent::Touch { .. }
+            | InputEvent::ElementFocused(_)
+            | InputEvent::ElementBlurred(_)
+            | InputEvent::ElementDropped { .. }
+            | InputEvent::AnimationFinished { .. }
+            | InputEvent::FileHovered(_)
+            | InputEvent::FileHoverCancelled
+            | InputEvent::FileDropped(_) => {}
+            InputEvent::ThemeChanged(mode) => self.system_theme_changed(*mode),
+            InputEvent::Focused(focused) => {
+                // keys released while unfocused never reach us
+                if !focused {
+                    self.input.keys_pressed.clear();
+                    self.input.logical_keys.clear();
+                    self.input.modifiers = Modifiers::NONE;
+                }
+            }
+        }
+    }
+
+    fn end_event(&mut self, event: &InputEvent) {
+        self.end_gestures();
+        self.end_drag();
+        self.end_touch();
+        match event {
+            InputEvent::CursorMoved { .. } => {
+                self.mouse.dx = 0.0;
+                self.mouse.dy = 0.0;
+            }
+            InputEvent::MouseButton { .. } => {
+                self.mouse.left_just_pressed = false;
+                self.mouse.left_just_released = false;
+                self.mouse.right_just_pressed = false;
+                self.mouse.right_just_released = false;
+                self.mouse.middle_just_pressed = false;
+                self.mouse.middle_just_released = false;
+            }
+            InputEvent::Scroll { .. } => {
+                self.mouse.scroll_x = 0.0;
+                self.mouse.scroll_y = 0.0;
+            }
+            InputEvent::Key { key, pressed, .. } => {
+                self.activated = None;
+                self.input.keys_just_pressed.clear();
+                self.input.keys_just_released.clear();
+                self.input.keys_repeated.clear();
+                if !pressed {
+                    self.input.logical_keys.remove(key);
+                }
+            }
+            InputEvent::Text(_) => {
+                self.input.text.clear();
+            }
+            InputEvent::Touch { .. }
+            | InputEvent::Resized { .. }
+            | InputEvent::ScaleFactorChanged { .. }
+            | InputEvent::Modifiers(_)
+            | InputEvent::Focused(_)
+            | InputEvent::ElementFocused(_)
+            | InputEvent::ElementBlurred(_)
+            | InputEvent::ElementDropped { .. }
+            | InputEvent::AnimationFinished { .. }
+            | InputEvent::ThemeChanged(_)
+            | InputEvent::FileHovered(_)
+            | InputEvent::FileHoverCancelled
+            | InputEvent::FileDropped(_) => {}
+        }
+    }
+}
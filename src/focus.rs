@@ -0,0 +1,263 @@
+use winit::event::MouseButton;
+use winit::keyboard::KeyCode;
+use winit::window::CursorIcon;
+
+use crate::event::Trigger;
+use crate::{Ctx, InputEvent, Transform};
+
+/// an element that can take part in focus, in draw order
+struct FocusTarget {
+    id: String,
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+    transform: Transform,
+    visible: bool,
+    focusable: bool,
+    tab_index: i32,
+    cursor: Option<CursorIcon>,
+}
+
+impl Ctx {
+    /// focuses a visible focusable element, returns whether it worked.
+    /// shows the focus ring like keyboard navigation does
+    pub fn focus(&mut self, id: &str) -> bool {
+        let ok = self
+            .focus_targets()
+            .iter()
+            .any(|t| t.id == id && t.visible && t.focusable);
+        if ok {
+            self.set_focus(Some(id.to_string()), true);
+        }
+        ok
+    }
+
+    pub fn blur(&mut self) {
+        self.set_focus(None, false);
+    }
+
+    pub fn focused(&self) -> Option<&str> {
+        self.focused.as_deref()
+    }
+
+    pub fn is_focused(&self, id: &str) -> bool {
+        self.focused.as_deref() == Some(id)
+    }
+
+    /// moves focus to the next element in Tab order, wrapping around
+    pub fn focus_next(&mut self) {
+        self.step_focus(true);
+    }
+
+    pub fn focus_prev(&mut self) {
+        self.step_focus(false);
+    }
+
+    fn step_focus(&mut self, forward: bool) {
+        let order = self.tab_order();
+        if order.is_empty() {
+            return;
+        }
+        let current = self
+            .focused
+            .as_ref()
+            .and_then(|id| order.iter().position(|o| o == id));
+        let next = match (current, forward) {
+            (Some(i), true) => (i + 1) % order.len(),
+            (Some(i), false) => (i + order.len() - 1) % order.len(),
+            (None, true) => 0,
+            (None, false) => order.len() - 1,
+        };
+        self.set_focus(Some(order[next].clone()), true);
+    }
+
+    /// ids reachable with Tab: positive tab indices ascending, then the rest
+    /// top to bottom, left to right
+    fn tab_order(&self) -> Vec<String> {
+        let mut targets: Vec<_> = self
+            .focus_targets()
+            .into_iter()
+            .filter(|t| t.visible && t.focusable && t.tab_index >= 0)
+            .collect();
+        targets.sort_by(|a, b| {
+            let group = |t: &FocusTarget| if t.tab_index > 0 { t.tab_index } else { i32::MAX };
+            group(a)
+                .cmp(&group(b))
+                .then(a.y.total_cmp(&b.y))
+                .then(a.x.total_cmp(&b.x))
+        });
+        targets.into_iter().map(|t| t.id).collect()
+    }
+
+    fn focus_targets(&self) -> Vec<FocusTarget> {
+        let ui = &self.ui;
+        let rects = ui.rects.iter().map(|r| FocusTarget {
+            id: r.id.clone(),
+            x: r.x,
+            y: r.y,
+            w: r.w,
+            h: r.h,
+            transform: r.transform.around(r.x + r.w * 0.5, r.y + r.h * 0.5),
+            visible: r.visible,
+            focusable: r.focusable,
+            tab_index: r.tab_index,
+            cursor: r.cursor,
+        });
+        let canvases = ui.canvases.iter().map(|c| FocusTarget {
+            id: c.id.clone(),
+            x: c.x,
+            y: c.y,
+            w: c.w,
+            h: c.h,
+            transform: Transform::IDENTITY,
+            visible: c.visible,
+            focusable: c.focusable,
+            tab_index: c.tab_index,
+            cursor: c.cursor,
+        });
+        let buttons = ui.buttons.iter().map(|b| FocusTarget {
+            id: b.id.clone(),
+            x: b.x,
+            y: b.y,
+            w: b.w,
+            h: b.h,
+            transform: b.transform.around(b.x + b.w * 0.5, b.y + b.h * 0.5),
+            visible: b.visible,
+            focusable: b.focusable && b.enabled,
+            tab_index: b.tab_index,
+            cursor: if b.enabled { b.cursor } else { Some(CursorIcon::NotAllowed) },
+        });
+        rects.chain(canvases).chain(buttons).collect()
+    }
+
+    /// the topmost visible element under the cursor
+    fn hovered_target(&self) -> Option<FocusTarget> {
+        // topmost first, so reverse draw order
+        self.focus_targets()
+            .into_iter()
+            .rev()
+            .find(|t| t.visible && self.is_hovered(&t.id))
+    }
+
+    pub(crate) fn hovered_element(&self) -> Option<String> {
+        self.hovered_target().map(|t| t.id)
+    }
+
+    /// every visible element under the cursor, topmost first
+    pub(crate) fn hovered_elements(&self) -> Vec<String> {
+        self.focus_targets()
+            .into_iter()
+            .rev()
+            .filter(|t| t.visible && self.is_hovered(&t.id))
+            .map(|t| t.id)
+            .collect()
+    }
+
+    /// the pointer asked for by the topmost hovered element that sets one
+    pub(crate) fn hovered_cursor(&self) -> Option<CursorIcon> {
+        self.focus_targets()
+            .into_iter()
+            .rev()
+            .filter(|t| t.visible && self.is_hovered(&t.id))
+            .find_map(|t| t.cursor)
+    }
+
+    /// position and size of a visible element, and the transform it's drawn with
+    pub(crate) fn element_bounds(&self, id: &str) -> Option<((f32, f32, f32, f32), Transform)> {
+        self.focus_targets()
+            .into_iter()
+            .find(|t| t.id == id && t.visible)
+            .map(|t| ((t.x, t.y, t.w, t.h), t.transform))
+    }
+
+    fn set_focus(&mut self, id: Option<String>, visible: bool) {
+        self.focus_visible = visible && id.is_some();
+        self.mark_dirty();
+        if id == self.focused {
+            return;
+        }
+
+        let old = std::mem::replace(&mut self.focused, id.clone());
+        if let Some(old) = old {
+            self.events.push(InputEvent::ElementBlurred(old.clone()));
+            self.fire_callbacks(Trigger::Blur, &old);
+        }
+        if let Some(new) = id {
+            self.events.push(InputEvent::ElementFocused(new.clone()));
+            self.fire_callbacks(Trigger::Focus, &new);
+        }
+    }
+
+    /// clicks focus what's under the cursor, Tab moves focus and Enter or
+    /// Space activate a focused button
+    pub(crate) fn update_focus(&mut self, event: &InputEvent) {
+        match event {
+            InputEvent::MouseButton {
+                button: MouseButton::Left,
+                pressed: true,
+            } => {
+                match self.hovered_target() {
+                    Some(t) if t.focusable => self.set_focus(Some(t.id), false),
+                    _ => self.set_focus(None, false),
+                }
+            }
+            InputEvent::Key {
+                key,
+                pressed: true,
+                repeat,
+                ..
+            } => {
+                let mods = self.input.modifiers;
+                match key {
+                    KeyCode::Tab if !mods.ctrl && !mods.alt && !mods.logo => {
+                        self.step_focus(!mods.shift);
+                    }
+                    KeyCode::Enter | KeyCode::NumpadEnter | KeyCode::Space if !repeat => {
+                        if let Some(id) = &self.focused
+                            && self
+                                .ui
+                                .buttons
+                                .iter()
+                                .any(|b| &b.id == id && b.visible && b.enabled)
+                        {
+                            self.activated = Some(id.clone());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// outlines the focused element when focus came from the keyboard
+    pub(crate) fn render_focus_ring(&mut self) {
+        if !self.focus_visible {
+            return;
+        }
+        let Some(id) = &self.focused else {
+            return;
+        };
+        let Some(t) = self
+            .focus_targets()
+            .into_iter()
+            .find(|t| &t.id == id && t.visible)
+        else {
+            return;
+        };
+
+        let gap = self.ui.focus_ring_thickness;
+        self.ui.shape_renderer.push_transform(t.transform);
+        self.ui.shape_renderer.rect(
+            t.x - gap,
+            t.y - gap,
+            t.w + gap * 2.0,
+            t.h + gap * 2.0,
+            [0.0; 4],
+            self.ui.focus_ring_color.to_array(),
+            self.ui.focus_ring_thickness,
+        );
+        self.ui.shape_renderer.pop_transform();
+    }
+}
@@ -11,20 +11,24 @@ pub struct FontEntry {
 
 pub struct Fonts {
     pub(crate) font_system: FontSystem,
+    /// padding around button text, writes are forwarded to the current
+    /// theme's `Theme::padding` the next time it's used
+    #[deprecated(note = "use `Theme::padding`")]
+    pub default_padding: f32,
     entries: Vec<FontEntry>,
     measure_cache: HashMap<(usize, String), (f32, f32)>,
     name_to_id: HashMap<String, FontId>,
-    pub default_padding: f32,
 }
 
 impl Fonts {
+    #[allow(clippy::new_without_default, deprecated)]
     pub fn new() -> Self {
         Self {
             font_system: FontSystem::new(),
+            default_padding: 8.0,
             entries: Vec::new(),
             measure_cache: HashMap::new(),
             name_to_id: HashMap::new(),
-            default_padding: 8.0,
         }
     }
 
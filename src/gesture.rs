@@ -0,0 +1,214 @@
+use std::time::Duration;
+
+use winit::event::MouseButton;
+
+use crate::{BentoApp, Ctx, InputEvent};
+
+/// thresholds for recognizing gestures
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GestureSettings {
+    /// longest gap between clicks that still counts as a double click
+    pub multi_click_interval: Duration,
+    /// how far apart, in logical pixels, clicks of a double click may be
+    pub multi_click_distance: f32,
+    pub long_press: Duration,
+    /// how far the cursor has to move while pressed before it's a drag
+    pub drag_threshold: f32,
+}
+
+impl Default for GestureSettings {
+    fn default() -> Self {
+        Self {
+            multi_click_interval: Duration::from_millis(500),
+            multi_click_distance: 4.0,
+            long_press: Duration::from_millis(500),
+            drag_threshold: 4.0,
+        }
+    }
+}
+
+/// the left button press a gesture started with
+#[derive(Clone, Debug)]
+struct Press {
+    x: f32,
+    y: f32,
+    time: Duration,
+    target: Option<String>,
+    long_fired: bool,
+}
+
+/// double clicks, long presses and drags of the left button, built on top
+/// of the raw press and release edges in `MouseState`. lives on
+/// `Ctx::gestures`
+#[derive(Default)]
+pub struct Gestures {
+    pub settings: GestureSettings,
+    /// clicks in the current series, 2 on the press of a double click
+    pub click_count: u32,
+    /// the topmost element under the press that started the gesture
+    pub target: Option<String>,
+    /// the drag moved during this event by this much
+    pub drag_delta: (f32, f32),
+    /// distance from where the drag started
+    pub drag_total: (f32, f32),
+    pub dragging: bool,
+
+    pub just_clicked: bool,
+    pub long_pressed: bool,
+    pub drag_started: bool,
+    pub drag_ended: bool,
+
+    press: Option<Press>,
+    last_click: Option<(Duration, f32, f32, Option<String>)>,
+}
+
+impl Gestures {
+    /// when a long press would fire if nothing else happens, for waking up
+    pub(crate) fn deadline(&self) -> Option<Duration> {
+        let press = self.press.as_ref()?;
+        (!press.long_fired && !self.dragging).then_some(press.time + self.settings.long_press)
+    }
+
+    fn clear_flags(&mut self) {
+        self.just_clicked = false;
+        self.long_pressed = false;
+        self.drag_started = false;
+        self.drag_ended = false;
+        self.drag_delta = (0.0, 0.0);
+    }
+}
+
+impl Ctx {
+    pub fn is_double_clicked(&self, id: &str) -> bool {
+        self.is_multi_clicked(id, 2)
+    }
+
+    pub fn is_triple_clicked(&self, id: &str) -> bool {
+        self.is_multi_clicked(id, 3)
+    }
+
+    /// pressed for the `count`th time in a row on this event
+    pub fn is_multi_clicked(&self, id: &str, count: u32) -> bool {
+        self.gestures.just_clicked
+            && self.gestures.click_count == count
+            && self.gestures.target.as_deref() == Some(id)
+    }
+
+    /// held without dragging for `GestureSettings::long_press`, fires once
+    pub fn is_long_pressed(&self, id: &str) -> bool {
+        self.gestures.long_pressed && self.gestures.target.as_deref() == Some(id)
+    }
+
+    pub fn is_drag_started(&self, id: &str) -> bool {
+        self.gestures.drag_started && self.gestures.target.as_deref() == Some(id)
+    }
+
+    pub fn is_dragging(&self, id: &str) -> bool {
+        self.gestures.dragging && self.gestures.target.as_deref() == Some(id)
+    }
+
+    pub fn is_drag_ended(&self, id: &str) -> bool {
+        self.gestures.drag_ended && self.gestures.target.as_deref() == Some(id)
+    }
+
+    /// how far the drag on `id` moved during this event
+    pub fn drag_delta(&self, id: &str) -> Option<(f32, f32)> {
+        self.is_dragging(id).then_some(self.gestures.drag_delta)
+    }
+
+    /// how far the drag on `id` is from where it started
+    pub fn drag_total(&self, id: &str) -> Option<(f32, f32)> {
+        (self.is_dragging(id) || self.is_drag_ended(id)).then_some(self.gestures.drag_total)
+    }
+
+    pub(crate) fn update_gestures(&mut self, event: &InputEvent) {
+        let now = self.time();
+        let (x, y) = (self.mouse.x, self.mouse.y);
+        let settings = self.gestures.settings;
+
+        match event {
+            InputEvent::MouseButton {
+                button: MouseButton::Left,
+                pressed: true,
+            } => {
+                let target = self.hovered_element();
+                let g = &mut self.gestures;
+                let continues = g.last_click.as_ref().is_some_and(|(time, lx, ly, last)| {
+                    now.saturating_sub(*time) <= settings.multi_click_interval
+                        && (x - lx).hypot(y - ly) <= settings.multi_click_distance
+                        && *last == target
+                });
+                g.click_count = if continues { g.click_count + 1 } else { 1 };
+                g.last_click = Some((now, x, y, target.clone()));
+                g.just_clicked = true;
+                g.target = target.clone();
+                g.press = Some(Press {
+                    x,
+                    y,
+                    time: now,
+                    target,
+                    long_fired: false,
+                });
+            }
+            // losing window focus means we'll never hear about the release
+            InputEvent::MouseButton {
+                button: MouseButton::Left,
+                pressed: false,
+            }
+            | InputEvent::Focused(false) => {
+                let g = &mut self.gestures;
+                if g.dragging {
+                    g.dragging = false;
+                    g.drag_ended = true;
+                }
+                g.press = None;
+            }
+            InputEvent::CursorMoved { .. } if self.gestures.press.is_some() => {
+                let g = &mut self.gestures;
+                let press = g.press.as_ref().unwrap();
+                let total = (x - press.x, y - press.y);
+                if !g.dragging && total.0.hypot(total.1) > settings.drag_threshold {
+                    g.dragging = true;
+                    g.drag_started = true;
+                    g.target = press.target.clone();
+                    g.drag_delta = total;
+                } else if g.dragging {
+                    g.drag_delta = (total.0 - g.drag_total.0, total.1 - g.drag_total.1);
+                }
+                g.drag_total = total;
+            }
+            _ => {}
+        }
+        self.check_long_press();
+    }
+
+    fn check_long_press(&mut self) {
+        let now = self.time();
+        let g = &mut self.gestures;
+        if let Some(deadline) = g.deadline()
+            && now >= deadline
+            && let Some(press) = g.press.as_mut()
+        {
+            press.long_fired = true;
+            g.long_pressed = true;
+            g.target = press.target.clone();
+        }
+    }
+
+    pub(crate) fn end_gestures(&mut self) {
+        self.gestures.clear_flags();
+    }
+
+    /// lets time based gestures fire without input, calling the app's
+    /// `update` if one did. returns whether it did
+    pub(crate) fn tick<T: BentoApp>(&mut self, app: &mut T) -> bool {
+        self.check_long_press();
+        if !self.gestures.long_pressed {
+            return false;
+        }
+        self.mark_dirty();
+        self.run_update(app);
+        self.end_gestures();
+        true
+    }
+}
@@ -0,0 +1,354 @@
+use std::time::Duration;
+
+use winit::event::{MouseButton, TouchPhase};
+use winit::keyboard::{Key, KeyCode};
+
+use crate::app::build_frame;
+use crate::snapshot::{check_snapshot, SnapshotError, SnapshotOptions};
+use crate::{BentoApp, Ctx, Headless, HeadlessError, InputEvent, Recording, Replay, RgbaImage};
+
+enum Backend {
+    Cpu(Ctx),
+    Gpu(Headless),
+}
+
+/// drives a `BentoApp` without a window: inject input, step frames and
+/// inspect the ui from tests
+pub struct Harness<T: BentoApp> {
+    pub app: T,
+    backend: Backend,
+}
+
+impl<T: BentoApp> Harness<T> {
+    /// no gpu at all, draw calls are recorded and thrown away
+    pub fn new(app: T, width: f32, height: f32) -> Self {
+        let mut harness = Self {
+            app,
+            backend: Backend::Cpu(Ctx::without_gpu(width, height)),
+        };
+        harness.start();
+        harness
+    }
+
+    /// renders through an offscreen gpu context so frames can be captured
+    pub fn with_gpu(app: T, width: u32, height: u32, scale_factor: f64) -> Result<Self, HeadlessError> {
+        let headless = Headless::new(width, height, scale_factor)?;
+        let mut harness = Self {
+            app,
+            backend: Backend::Gpu(headless),
+        };
+        harness.start();
+        Ok(harness)
+    }
+
+    fn start(&mut self) {
+        let (app, ctx) = self.parts();
+        app.once(ctx);
+        app.update(ctx);
+    }
+
+    fn parts(&mut self) -> (&mut T, &mut Ctx) {
+        match &mut self.backend {
+            Backend::Cpu(ctx) => (&mut self.app, ctx),
+            Backend::Gpu(headless) => (&mut self.app, &mut headless.ctx),
+        }
+    }
+
+    pub fn ctx(&self) -> &Ctx {
+        match &self.backend {
+            Backend::Cpu(ctx) => ctx,
+            Backend::Gpu(headless) => &headless.ctx,
+        }
+    }
+
+    pub fn ctx_mut(&mut self) -> &mut Ctx {
+        self.parts().1
+    }
+
+    /// feeds one event through the same path window input takes
+    pub fn send(&mut self, event: InputEvent) -> &mut Self {
+        // the render target follows first, like a window's surface does
+        if let Backend::Gpu(headless) = &mut self.backend {
+            match event {
+                InputEvent::Resized { width, height } => {
+                    headless.resize(width as u32, height as u32);
+                }
+                InputEvent::ScaleFactorChanged { scale_factor } => {
+                    headless.set_scale_factor(scale_factor);
+                }
+                _ => {}
+            }
+        }
+        let (app, ctx) = self.parts();
+        ctx.dispatch(app, &event);
+        self
+    }
+
+    pub fn move_mouse(&mut self, x: f32, y: f32) -> &mut Self {
+        self.send(InputEvent::CursorMoved { x, y })
+    }
+
+    pub fn press(&mut self, button: MouseButton) -> &mut Self {
+        self.send(InputEvent::MouseButton { button, pressed: true })
+    }
+
+    pub fn release(&mut self, button: MouseButton) -> &mut Self {
+        self.send(InputEvent::MouseButton { button, pressed: false })
+    }
+
+    /// moves to (x, y) and presses and releases the left button
+    pub fn click(&mut self, x: f32, y: f32) -> &mut Self {
+        self.move_mouse(x, y)
+            .press(MouseButton::Left)
+            .release(MouseButton::Left)
+    }
+
+    pub fn right_click(&mut self, x: f32, y: f32) -> &mut Self {
+        self.move_mouse(x, y)
+            .press(MouseButton::Right)
+            .release(MouseButton::Right)
+    }
+
+    /// clicks the center of a visible element
+    pub fn click_element(&mut self, id: &str) -> &mut Self {
+        let (x, y, w, h) = self
+            .bounds(id)
+            .unwrap_or_else(|| panic!("no element with id '{id}' to click"));
+        self.click(x + w * 0.5, y + h * 0.5)
+    }
+
+    pub fn touch(&mut self, id: u64, phase: TouchPhase, x: f32, y: f32) -> &mut Self {
+        self.send(InputEvent::Touch { id, phase, x, y })
+    }
+
+    /// a finger touching (x, y) and lifting again
+    pub fn tap(&mut self, x: f32, y: f32) -> &mut Self {
+        self.touch(0, TouchPhase::Started, x, y)
+            .touch(0, TouchPhase::Ended, x, y)
+    }
+
+    /// two fingers around (x, y) moving from `from` to `to` apart in
+    /// `steps` moves, then lifting
+    pub fn pinch(&mut self, x: f32, y: f32, from: f32, to: f32, steps: u32) -> &mut Self {
+        self.touch(0, TouchPhase::Started, x - from * 0.5, y)
+            .touch(1, TouchPhase::Started, x + from * 0.5, y);
+        let mut distance = from;
+        for step in 1..=steps.max(1) {
+            distance = from + (to - from) * step as f32 / steps.max(1) as f32;
+            self.touch(0, TouchPhase::Moved, x - distance * 0.5, y)
+                .touch(1, TouchPhase::Moved, x + distance * 0.5, y);
+        }
+        self.touch(0, TouchPhase::Ended, x - distance * 0.5, y)
+            .touch(1, TouchPhase::Ended, x + distance * 0.5, y)
+    }
+
+    /// drags files in from the os and drops them at (x, y)
+    pub fn drop_files(&mut self, x: f32, y: f32, paths: &[impl AsRef<std::path::Path>]) -> &mut Self {
+        self.move_mouse(x, y);
+        for path in paths {
+            self.send(InputEvent::FileHovered(path.as_ref().to_path_buf()));
+        }
+        for path in paths {
+            self.send(InputEvent::FileDropped(path.as_ref().to_path_buf()));
+        }
+        self
+    }
+
+    pub fn scroll(&mut self, x: f32, y: f32) -> &mut Self {
+        self.send(InputEvent::Scroll { x, y })
+    }
+
+    /// presses a key as it would be on a us layout
+    pub fn key_down(&mut self, key: KeyCode) -> &mut Self {
+        self.send(InputEvent::Key {
+            key,
+            logical: None,
+            pressed: true,
+            repeat: false,
+        })
+    }
+
+    pub fn key_up(&mut self, key: KeyCode) -> &mut Self {
+        self.send(InputEvent::Key {
+            key,
+            logical: None,
+            pressed: false,
+            repeat: false,
+        })
+    }
+
+    /// a key repeat for a key that's already held
+    pub fn key_repeat(&mut self, key: KeyCode) -> &mut Self {
+        self.send(InputEvent::Key {
+            key,
+            logical: None,
+            pressed: true,
+            repeat: true,
+        })
+    }
+
+    /// presses a key that produces `logical`, for simulating other layouts
+    pub fn key_down_logical(&mut self, key: KeyCode, logical: Key) -> &mut Self {
+        self.send(InputEvent::Key {
+            key,
+            logical: Some(logical),
+            pressed: true,
+            repeat: false,
+        })
+    }
+
+    pub fn tap_key(&mut self, key: KeyCode) -> &mut Self {
+        self.key_down(key).key_up(key)
+    }
+
+    /// holds the keys in order and releases them in reverse, e.g.
+    /// `&[KeyCode::ControlLeft, KeyCode::KeyS]`
+    pub fn chord(&mut self, keys: &[KeyCode]) -> &mut Self {
+        for key in keys {
+            self.key_down(*key);
+        }
+        for key in keys.iter().rev() {
+            self.key_up(*key);
+        }
+        self
+    }
+
+    pub fn type_text(&mut self, text: &str) -> &mut Self {
+        self.send(InputEvent::Text(text.to_string()))
+    }
+
+    /// sends every event of a recording in order, moving the clock by the
+    /// recorded gaps so timed gestures like long presses come out the same.
+    /// resizes to the recorded size first
+    pub fn replay(&mut self, recording: &Recording) -> &mut Self {
+        let ctx = self.ctx();
+        if (ctx.window_width, ctx.window_height) != (recording.width, recording.height) {
+            self.resize(recording.width, recording.height);
+        }
+        let mut last = recording.events.first().map_or(0.0, |r| r.time);
+        for recorded in &recording.events {
+            self.advance(Duration::from_secs_f64((recorded.time - last).max(0.0)));
+            last = recorded.time;
+            self.send(recorded.event.clone());
+        }
+        self
+    }
+
+    /// sends the next event of `replay`, returning it so a debugger or test
+    /// can inspect the ui between events
+    pub fn step(&mut self, replay: &mut Replay) -> Option<InputEvent> {
+        let previous = replay.position().checked_sub(1);
+        let previous = previous.map(|i| replay.recording.events[i].time);
+        let recorded = replay.step()?.clone();
+        if let Some(previous) = previous {
+            self.advance(Duration::from_secs_f64((recorded.time - previous).max(0.0)));
+        }
+        self.send(recorded.event.clone());
+        Some(recorded.event)
+    }
+
+    /// moves the clock forward, firing long presses and building a frame if
+    /// the app asked for one by then
+    pub fn advance(&mut self, by: Duration) -> &mut Self {
+        let (app, ctx) = self.parts();
+        ctx.advance_time(by);
+        ctx.tick(app);
+        if ctx.check_redraw() {
+            self.frame();
+        }
+        self
+    }
+
+    /// advances by `frame` at a time for `duration`, like a running
+    /// animation at a fixed frame rate
+    pub fn run_for(&mut self, duration: Duration, frame: Duration) -> &mut Self {
+        let mut elapsed = Duration::ZERO;
+        while elapsed < duration && !frame.is_zero() {
+            self.advance(frame);
+            elapsed += frame;
+        }
+        self
+    }
+
+    pub fn resize(&mut self, width: f32, height: f32) -> &mut Self {
+        self.send(InputEvent::Resized { width, height })
+    }
+
+    /// builds a frame like a redraw does, without drawing it
+    pub fn frame(&mut self) -> &mut Self {
+        let (app, ctx) = self.parts();
+        ctx.ui.dirty = false;
+        build_frame(ctx, app);
+        self
+    }
+
+    /// renders a frame and reads it back, `None` without a gpu
+    pub fn render(&mut self) -> Option<RgbaImage> {
+        match &mut self.backend {
+            Backend::Cpu(_) => None,
+            Backend::Gpu(headless) => Some(headless.render(&mut self.app)),
+        }
+    }
+
+    /// renders a frame and compares it against the reference png, see
+    /// `check_snapshot`. panics when the harness has no gpu
+    pub fn check_snapshot(
+        &mut self,
+        reference: impl AsRef<std::path::Path>,
+        options: &SnapshotOptions,
+    ) -> Result<(), SnapshotError> {
+        let image = self
+            .render()
+            .expect("snapshots need a harness created with `Harness::with_gpu`");
+        check_snapshot(reference, &image, options)
+    }
+
+    /// `check_snapshot` with default options, panicking on mismatch
+    #[track_caller]
+    pub fn assert_snapshot(&mut self, reference: impl AsRef<std::path::Path>) {
+        if let Err(e) = self.check_snapshot(reference, &SnapshotOptions::default()) {
+            panic!("snapshot mismatch: {e}");
+        }
+    }
+
+    pub fn is_hovered(&self, id: &str) -> bool {
+        self.ctx().is_hovered(id)
+    }
+
+    pub fn is_visible(&self, id: &str) -> bool {
+        self.ctx().ui.is_visible(id)
+    }
+
+    pub fn get<E: 'static>(&self, id: &str) -> Option<&E> {
+        self.ctx().ui.get::<E>(id)
+    }
+
+    pub fn exited(&self) -> bool {
+        self.ctx().exit
+    }
+
+    /// strings drawn by the last `frame` or `render`, in draw order
+    pub fn drawn_text(&self) -> Vec<String> {
+        self.ctx()
+            .ui
+            .text_renderer
+            .drawn_text()
+            .map(|(text, _, _)| text.to_string())
+            .collect()
+    }
+
+    /// screen bounds of a visible rect, button or canvas
+    pub fn bounds(&self, id: &str) -> Option<(f32, f32, f32, f32)> {
+        let ui = &self.ctx().ui;
+        if let Some(r) = ui.rects.iter().find(|r| r.id == id && r.visible) {
+            return Some((r.x, r.y, r.w, r.h));
+        }
+        if let Some(b) = ui.buttons.iter().find(|b| b.id == id && b.visible) {
+            return Some((b.x, b.y, b.w, b.h));
+        }
+        if let Some(c) = ui.canvases.iter().find(|c| c.id == id && c.visible) {
+            return Some((c.x, c.y, c.w, c.h));
+        }
+        None
+    }
+}
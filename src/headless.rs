@@ -0,0 +1,338 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path as FsPath;
+
+use crate::app::{build_frame, default_fonts, encode_frame};
+use crate::{BentoApp, Clipboard, Ctx, ShapeRenderer, TextRenderer};
+
+/// texture format of offscreen frames, so readbacks are plain srgb rgba8
+const HEADLESS_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+#[derive(Debug)]
+pub enum HeadlessError {
+    NoAdapter,
+    RequestDevice(wgpu::RequestDeviceError),
+    InvalidSize,
+}
+
+impl fmt::Display for HeadlessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeadlessError::NoAdapter => write!(f, "no gpu or software adapter available"),
+            HeadlessError::RequestDevice(e) => write!(f, "failed to create device: {e}"),
+            HeadlessError::InvalidSize => write!(f, "offscreen size must be non-zero"),
+        }
+    }
+}
+
+impl std::error::Error for HeadlessError {}
+
+/// an rgba8 frame read back from the gpu, rows top to bottom, no padding
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 4],
+        }
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+
+    pub fn set_pixel(&mut self, x: u32, y: u32, rgba: [u8; 4]) {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        self.pixels[i..i + 4].copy_from_slice(&rgba);
+    }
+
+    pub fn save_png(&self, path: impl AsRef<FsPath>) -> io::Result<()> {
+        self.write_png(BufWriter::new(File::create(path)?))
+    }
+
+    pub fn write_png(&self, writer: impl Write) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        writer
+            .write_image_data(&self.pixels)
+            .map_err(io::Error::other)?;
+        Ok(())
+    }
+
+    pub fn load_png(path: impl AsRef<FsPath>) -> io::Result<Self> {
+        Self::read_png(BufReader::new(File::open(path)?))
+    }
+
+    pub fn read_png(reader: impl Read) -> io::Result<Self> {
+        let decoder = png::Decoder::new(reader);
+        let mut reader = decoder.read_info().map_err(io::Error::other)?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).map_err(io::Error::other)?;
+        buf.truncate(info.buffer_size());
+
+        let pixels = match (info.color_type, info.bit_depth) {
+            (png::ColorType::Rgba, png::BitDepth::Eight) => buf,
+            (png::ColorType::Rgb, png::BitDepth::Eight) => buf
+                .chunks_exact(3)
+                .flat_map(|p| [p[0], p[1], p[2], 255])
+                .collect(),
+            (color, depth) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unsupported png format {color:?} {depth:?}"),
+                ));
+            }
+        };
+
+        Ok(Self {
+            width: info.width,
+            height: info.height,
+            pixels,
+        })
+    }
+}
+
+/// renders a `Ctx` into an offscreen texture instead of a window, for
+/// screenshots and golden-image tests. falls back to a software adapter when
+/// there's no gpu
+pub struct Headless {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    texture: wgpu::Texture,
+    msaa_texture: wgpu::Texture,
+    width: f32,
+    height: f32,
+    scale_factor: f64,
+    pub ctx: Ctx,
+}
+
+impl Headless {
+    /// `width` and `height` are logical, the texture is scaled by `scale_factor`
+    pub fn new(width: u32, height: u32, scale_factor: f64) -> Result<Self, HeadlessError> {
+        pollster::block_on(Self::new_async(width, height, scale_factor))
+    }
+
+    pub async fn new_async(width: u32, height: u32, scale_factor: f64) -> Result<Self, HeadlessError> {
+        if width == 0 || height == 0 || scale_factor <= 0.0 {
+            return Err(HeadlessError::InvalidSize);
+        }
+
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let mut adapter = None;
+        for force_fallback_adapter in [false, true] {
+            adapter = instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::LowPower,
+                    compatible_surface: None,
+                    force_fallback_adapter,
+                })
+                .await
+                .ok();
+            if adapter.is_some() {
+                break;
+            }
+        }
+        let adapter = adapter.ok_or(HeadlessError::NoAdapter)?;
+
+        let (device, queue) = adapter
+            .request_device(&wgpu::DeviceDescriptor::default())
+            .await
+            .map_err(HeadlessError::RequestDevice)?;
+
+        let (w, h) = (width as f32, height as f32);
+        let mut text_renderer = TextRenderer::new(&device, &queue, HEADLESS_FORMAT);
+        let mut shape_renderer = ShapeRenderer::new(&device, HEADLESS_FORMAT, w, h);
+        shape_renderer.resize(w, h, scale_factor);
+        text_renderer.resize(w, h, scale_factor);
+
+        let mut ctx = Ctx::new(default_fonts(), text_renderer, shape_renderer);
+        ctx.resize(w, h);
+        ctx.use_manual_time();
+        ctx.clipboard = Clipboard::memory();
+
+        let (texture, msaa_texture) = Self::create_targets(&device, w, h, scale_factor);
+
+        Ok(Self {
+            device,
+            queue,
+            texture,
+            msaa_texture,
+            width: w,
+            height: h,
+            scale_factor,
+            ctx,
+        })
+    }
+
+    fn create_targets(
+        device: &wgpu::Device,
+        width: f32,
+        height: f32,
+        scale_factor: f64,
+    ) -> (wgpu::Texture, wgpu::Texture) {
+        let size = wgpu::Extent3d {
+            width: ((width as f64 * scale_factor).round() as u32).max(1),
+            height: ((height as f64 * scale_factor).round() as u32).max(1),
+            depth_or_array_layers: 1,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: HEADLESS_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let msaa_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen MSAA Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 4,
+            dimension: wgpu::TextureDimension::D2,
+            format: HEADLESS_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        (texture, msaa_texture)
+    }
+
+    pub fn logical_size(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    pub fn physical_size(&self) -> (u32, u32) {
+        (self.texture.width(), self.texture.height())
+    }
+
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.set_size(width, height, self.scale_factor);
+    }
+
+    pub fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.set_size(self.width as u32, self.height as u32, scale_factor);
+    }
+
+    fn set_size(&mut self, width: u32, height: u32, scale_factor: f64) {
+        if width == 0 || height == 0 || scale_factor <= 0.0 {
+            return;
+        }
+        let (w, h) = (width as f32, height as f32);
+        self.width = w;
+        self.height = h;
+        self.scale_factor = scale_factor;
+        (self.texture, self.msaa_texture) = Self::create_targets(&self.device, w, h, scale_factor);
+        self.ctx.ui.shape_renderer.resize(w, h, scale_factor);
+        self.ctx.ui.text_renderer.resize(w, h, scale_factor);
+        self.ctx.resize(w, h);
+    }
+
+    /// runs the app's `once` and first `update`, like `App::run` does when
+    /// the window opens
+    pub fn start<T: BentoApp>(&mut self, app: &mut T) {
+        app.once(&mut self.ctx);
+        app.update(&mut self.ctx);
+    }
+
+    /// draws a full frame, the same way a window redraw does, and reads it back
+    pub fn render<T: BentoApp>(&mut self, app: &mut T) -> RgbaImage {
+        self.ctx.ui.dirty = false;
+        build_frame(&mut self.ctx, app);
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Offscreen Encoder"),
+            });
+        let view = self
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let msaa_view = self
+            .msaa_texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        encode_frame(
+            &mut self.ctx,
+            &self.device,
+            &self.queue,
+            &mut encoder,
+            &msaa_view,
+            &view,
+            self.width,
+            self.height,
+            self.scale_factor,
+        );
+
+        self.read_back(encoder)
+    }
+
+    fn read_back(&self, mut encoder: wgpu::CommandEncoder) -> RgbaImage {
+        let (width, height) = self.physical_size();
+        let unpadded = width * 4;
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded = unpadded.div_ceil(align) * align;
+
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Offscreen Readback"),
+            size: padded as u64 * height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded),
+                    rows_per_image: Some(height),
+                },
+            },
+            self.texture.size(),
+        );
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| {});
+        self.device.poll(wgpu::PollType::Wait).unwrap();
+
+        let mut image = RgbaImage::new(width, height);
+        {
+            let data = slice.get_mapped_range();
+            for (row, chunk) in data.chunks_exact(padded as usize).enumerate() {
+                let start = row * unpadded as usize;
+                image.pixels[start..start + unpadded as usize]
+                    .copy_from_slice(&chunk[..unpadded as usize]);
+            }
+        }
+        buffer.unmap();
+        image
+    }
+}
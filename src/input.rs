@@ -1,11 +1,75 @@
-use std::collections::HashSet;
-use winit::keyboard::KeyCode;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use winit::keyboard::{Key, KeyCode, NamedKey};
+
+/// which modifier keys are held
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    /// the windows, command or super key
+    pub logo: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Self = Self {
+        shift: false,
+        ctrl: false,
+        alt: false,
+        logo: false,
+    };
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::NONE
+    }
+
+    /// ctrl on most platforms, command on macos
+    pub fn command(&self) -> bool {
+        if cfg!(target_os = "macos") {
+            self.logo
+        } else {
+            self.ctrl
+        }
+    }
+
+    pub(crate) fn from_winit(state: winit::keyboard::ModifiersState) -> Self {
+        Self {
+            shift: state.shift_key(),
+            ctrl: state.control_key(),
+            alt: state.alt_key(),
+            logo: state.super_key(),
+        }
+    }
+
+    /// tracks modifier keys for input that doesn't come with a separate
+    /// modifiers event, like tests and replays
+    pub(crate) fn set_key(&mut self, key: KeyCode, pressed: bool) {
+        match key {
+            KeyCode::ShiftLeft | KeyCode::ShiftRight => self.shift = pressed,
+            KeyCode::ControlLeft | KeyCode::ControlRight => self.ctrl = pressed,
+            KeyCode::AltLeft | KeyCode::AltRight => self.alt = pressed,
+            KeyCode::SuperLeft | KeyCode::SuperRight => self.logo = pressed,
+            _ => {}
+        }
+    }
+}
 
 #[derive(Default)]
 pub struct InputState {
     pub keys_pressed: HashSet<KeyCode>,
+    /// keys that went down this event, not counting key repeat
     pub keys_just_pressed: HashSet<KeyCode>,
     pub keys_just_released: HashSet<KeyCode>,
+    /// keys the os repeated this event because they're held down
+    pub keys_repeated: HashSet<KeyCode>,
+    /// the layout-dependent key each held physical key produced
+    pub logical_keys: HashMap<KeyCode, Key>,
+    pub modifiers: Modifiers,
+    /// text typed during the current event
+    pub text: String,
 }
 
 impl InputState {
@@ -20,4 +84,329 @@ impl InputState {
     pub fn just_released(&self, key: KeyCode) -> bool {
         self.keys_just_released.contains(&key)
     }
+
+    pub fn just_repeated(&self, key: KeyCode) -> bool {
+        self.keys_repeated.contains(&key)
+    }
+
+    /// pressed this event, either fresh or by key repeat, for things like
+    /// moving a text cursor
+    pub fn just_pressed_or_repeated(&self, key: KeyCode) -> bool {
+        self.just_pressed(key) || self.just_repeated(key)
+    }
+
+    /// whether a held key produces `key` on the current layout, e.g.
+    /// `Key::Character("z".into())` is the key labelled z on azerty too
+    pub fn is_logical_pressed(&self, key: &Key) -> bool {
+        self.logical_keys.values().any(|k| logical_eq(k, key))
+    }
+
+    pub fn logical_just_pressed(&self, key: &Key) -> bool {
+        self.keys_just_pressed
+            .iter()
+            .filter_map(|code| self.logical_keys.get(code))
+            .any(|k| logical_eq(k, key))
+    }
+
+    /// whether `shortcut` was just pressed with exactly its modifiers held,
+    /// written like "Ctrl+Shift+S", "Alt+Enter" or "CmdOrCtrl+Z". letters
+    /// match the key the layout produces, falling back to the physical key
+    /// on layouts without latin letters. symbols the layout types with
+    /// shift, like "Ctrl++" on us keyboards, don't care about shift. panics
+    /// on a malformed shortcut
+    pub fn shortcut(&self, shortcut: &str) -> bool {
+        let parsed = Shortcut::parse(shortcut).unwrap_or_else(|e| panic!("{e}"));
+        self.keys_just_pressed
+            .iter()
+            .any(|code| parsed.matches_press(*code, self.logical_keys.get(code), self.modifiers))
+    }
+}
+
+fn logical_eq(a: &Key, b: &Key) -> bool {
+    match (a, b) {
+        (Key::Character(a), Key::Character(b)) => a.to_lowercase() == b.to_lowercase(),
+        _ => a == b,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum ShortcutKey {
+    Char(char),
+    Named(NamedKey),
+}
+
+/// a parsed key combination like "Ctrl+Shift+S"
+#[derive(Clone, Debug, PartialEq)]
+pub struct Shortcut {
+    pub modifiers: Modifiers,
+    key: ShortcutKey,
+}
+
+impl Shortcut {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let invalid = |why: &str| format!("invalid shortcut '{text}': {why}");
+
+        // a trailing "++" means the key itself is plus
+        let (mods, key) = match text.strip_suffix("++") {
+            Some(rest) => (rest, "+"),
+            None => match text.rsplit_once('+') {
+                Some((mods, key)) => (mods, key),
+                None => ("", text),
+            },
+        };
+
+        let mut modifiers = Modifiers::NONE;
+        for name in mods.split('+').filter(|m| !m.is_empty()) {
+            match name.trim().to_ascii_lowercase().as_str() {
+                "shift" => modifiers.shift = true,
+                "ctrl" | "control" => modifiers.ctrl = true,
+                "alt" | "option" => modifiers.alt = true,
+                "super" | "cmd" | "command" | "meta" | "logo" | "win" => modifiers.logo = true,
+                "cmdorctrl" | "mod" => {
+                    if cfg!(target_os = "macos") {
+                        modifiers.logo = true;
+                    } else {
+                        modifiers.ctrl = true;
+                    }
+                }
+                _ => return Err(invalid(&format!("unknown modifier '{name}'"))),
+            }
+        }
+
+        let key = key.trim();
+        let mut chars = key.chars();
+        let key = match (chars.next(), chars.next()) {
+            (Some(c), None) => ShortcutKey::Char(c.to_ascii_lowercase()),
+            (Some(_), Some(_)) => ShortcutKey::Named(
+                named_key(key).ok_or_else(|| invalid(&format!("unknown key '{key}'")))?,
+            ),
+            (None, _) => return Err(invalid("missing key")),
+        };
+
+        Ok(Self { modifiers, key })
+    }
+
+    /// whether a key press with `modifiers` held is this shortcut
+    pub(crate) fn matches_press(&self, code: KeyCode, logical: Option<&Key>, modifiers: Modifiers) -> bool {
+        if !self.matches(code, logical) {
+            return false;
+        }
+        // a symbol the layout produced may have needed shift to type
+        let typed = match (&self.key, logical) {
+            (ShortcutKey::Char(c), Some(Key::Character(s))) => {
+                !c.is_ascii_alphabetic() && s.starts_with(*c)
+            }
+            _ => false,
+        };
+        let shift = if typed { self.modifiers.shift } else { modifiers.shift };
+        self.modifiers == Modifiers { shift, ..modifiers }
+    }
+
+    fn matches(&self, code: KeyCode, logical: Option<&Key>) -> bool {
+        match &self.key {
+            ShortcutKey::Named(named) => match logical {
+                Some(Key::Named(k)) => k == named,
+                _ => default_logical(code, false) == Key::Named(*named),
+            },
+            ShortcutKey::Char(c) => {
+                let produced = match logical {
+                    Some(Key::Character(s)) => s.chars().next().map(|c| c.to_ascii_lowercase()),
+                    _ => None,
+                };
+                match produced {
+                    Some(p) if p.is_ascii_alphabetic() => p == *c,
+                    // shifted digits and non-latin layouts go by position
+                    Some(p) if p == *c => true,
+                    _ => us_char(code) == Some(*c),
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Shortcut {
+    /// the canonical spelling, e.g. "Ctrl+Shift+S", for menus and tooltips
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let m = self.modifiers;
+        let logo = if cfg!(target_os = "macos") { "Cmd" } else { "Super" };
+        for (held, name) in [(m.ctrl, "Ctrl"), (m.alt, "Alt"), (m.shift, "Shift"), (m.logo, logo)] {
+            if held {
+                write!(f, "{name}+")?;
+            }
+        }
+        match &self.key {
+            ShortcutKey::Char(' ') => write!(f, "Space"),
+            ShortcutKey::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            ShortcutKey::Named(named) => write!(f, "{}", named_key_name(*named)),
+        }
+    }
+}
+
+fn named_key_name(key: NamedKey) -> &'static str {
+    match key {
+        NamedKey::Enter => "Enter",
+        NamedKey::Escape => "Esc",
+        NamedKey::Tab => "Tab",
+        NamedKey::Space => "Space",
+        NamedKey::Backspace => "Backspace",
+        NamedKey::Delete => "Delete",
+        NamedKey::Insert => "Insert",
+        NamedKey::Home => "Home",
+        NamedKey::End => "End",
+        NamedKey::PageUp => "PageUp",
+        NamedKey::PageDown => "PageDown",
+        NamedKey::ArrowUp => "Up",
+        NamedKey::ArrowDown => "Down",
+        NamedKey::ArrowLeft => "Left",
+        NamedKey::ArrowRight => "Right",
+        NamedKey::F1 => "F1",
+        NamedKey::F2 => "F2",
+        NamedKey::F3 => "F3",
+        NamedKey::F4 => "F4",
+        NamedKey::F5 => "F5",
+        NamedKey::F6 => "F6",
+        NamedKey::F7 => "F7",
+        NamedKey::F8 => "F8",
+        NamedKey::F9 => "F9",
+        NamedKey::F10 => "F10",
+        NamedKey::F11 => "F11",
+        NamedKey::F12 => "F12",
+        _ => "?",
+    }
+}
+
+fn named_key(name: &str) -> Option<NamedKey> {
+    let key = match name.to_ascii_lowercase().as_str() {
+        "enter" | "return" => NamedKey::Enter,
+        "esc" | "escape" => NamedKey::Escape,
+        "tab" => NamedKey::Tab,
+        "space" => NamedKey::Space,
+        "backspace" => NamedKey::Backspace,
+        "del" | "delete" => NamedKey::Delete,
+        "ins" | "insert" => NamedKey::Insert,
+        "home" => NamedKey::Home,
+        "end" => NamedKey::End,
+        "pageup" => NamedKey::PageUp,
+        "pagedown" => NamedKey::PageDown,
+        "up" => NamedKey::ArrowUp,
+        "down" => NamedKey::ArrowDown,
+        "left" => NamedKey::ArrowLeft,
+        "right" => NamedKey::ArrowRight,
+        "f1" => NamedKey::F1,
+        "f2" => NamedKey::F2,
+        "f3" => NamedKey::F3,
+        "f4" => NamedKey::F4,
+        "f5" => NamedKey::F5,
+        "f6" => NamedKey::F6,
+        "f7" => NamedKey::F7,
+        "f8" => NamedKey::F8,
+        "f9" => NamedKey::F9,
+        "f10" => NamedKey::F10,
+        "f11" => NamedKey::F11,
+        "f12" => NamedKey::F12,
+        _ => return None,
+    };
+    Some(key)
+}
+
+/// the character a physical key types on a us layout without shift
+fn us_char(code: KeyCode) -> Option<char> {
+    let c = match code {
+        KeyCode::KeyA => 'a',
+        KeyCode::KeyB => 'b',
+        KeyCode::KeyC => 'c',
+        KeyCode::KeyD => 'd',
+        KeyCode::KeyE => 'e',
+        KeyCode::KeyF => 'f',
+        KeyCode::KeyG => 'g',
+        KeyCode::KeyH => 'h',
+        KeyCode::KeyI => 'i',
+        KeyCode::KeyJ => 'j',
+        KeyCode::KeyK => 'k',
+        KeyCode::KeyL => 'l',
+        KeyCode::KeyM => 'm',
+        KeyCode::KeyN => 'n',
+        KeyCode::KeyO => 'o',
+        KeyCode::KeyP => 'p',
+        KeyCode::KeyQ => 'q',
+        KeyCode::KeyR => 'r',
+        KeyCode::KeyS => 's',
+        KeyCode::KeyT => 't',
+        KeyCode::KeyU => 'u',
+        KeyCode::KeyV => 'v',
+        KeyCode::KeyW => 'w',
+        KeyCode::KeyX => 'x',
+        KeyCode::KeyY => 'y',
+        KeyCode::KeyZ => 'z',
+        KeyCode::Digit0 | KeyCode::Numpad0 => '0',
+        KeyCode::Digit1 | KeyCode::Numpad1 => '1',
+        KeyCode::Digit2 | KeyCode::Numpad2 => '2',
+        KeyCode::Digit3 | KeyCode::Numpad3 => '3',
+        KeyCode::Digit4 | KeyCode::Numpad4 => '4',
+        KeyCode::Digit5 | KeyCode::Numpad5 => '5',
+        KeyCode::Digit6 | KeyCode::Numpad6 => '6',
+        KeyCode::Digit7 | KeyCode::Numpad7 => '7',
+        KeyCode::Digit8 | KeyCode::Numpad8 => '8',
+        KeyCode::Digit9 | KeyCode::Numpad9 => '9',
+        KeyCode::Minus | KeyCode::NumpadSubtract => '-',
+        KeyCode::Equal => '=',
+        KeyCode::NumpadAdd => '+',
+        KeyCode::NumpadMultiply => '*',
+        KeyCode::Slash | KeyCode::NumpadDivide => '/',
+        KeyCode::Backslash => '\\',
+        KeyCode::BracketLeft => '[',
+        KeyCode::BracketRight => ']',
+        KeyCode::Semicolon => ';',
+        KeyCode::Quote => '\'',
+        KeyCode::Comma => ',',
+        KeyCode::Period | KeyCode::NumpadDecimal => '.',
+        KeyCode::Backquote => '`',
+        KeyCode::Space => ' ',
+        _ => return None,
+    };
+    Some(c)
+}
+
+/// what a physical key produces on a us layout, used when input arrives
+/// without a logical key
+pub(crate) fn default_logical(code: KeyCode, shift: bool) -> Key {
+    if let Some(c) = us_char(code).filter(|c| *c != ' ') {
+        let c = if shift { c.to_ascii_uppercase() } else { c };
+        return Key::Character(c.to_string().into());
+    }
+    let named = match code {
+        KeyCode::Enter | KeyCode::NumpadEnter => NamedKey::Enter,
+        KeyCode::Escape => NamedKey::Escape,
+        KeyCode::Tab => NamedKey::Tab,
+        KeyCode::Space => NamedKey::Space,
+        KeyCode::Backspace => NamedKey::Backspace,
+        KeyCode::Delete => NamedKey::Delete,
+        KeyCode::Insert => NamedKey::Insert,
+        KeyCode::Home => NamedKey::Home,
+        KeyCode::End => NamedKey::End,
+        KeyCode::PageUp => NamedKey::PageUp,
+        KeyCode::PageDown => NamedKey::PageDown,
+        KeyCode::ArrowUp => NamedKey::ArrowUp,
+        KeyCode::ArrowDown => NamedKey::ArrowDown,
+        KeyCode::ArrowLeft => NamedKey::ArrowLeft,
+        KeyCode::ArrowRight => NamedKey::ArrowRight,
+        KeyCode::ShiftLeft | KeyCode::ShiftRight => NamedKey::Shift,
+        KeyCode::ControlLeft | KeyCode::ControlRight => NamedKey::Control,
+        KeyCode::AltLeft | KeyCode::AltRight => NamedKey::Alt,
+        KeyCode::SuperLeft | KeyCode::SuperRight => NamedKey::Super,
+        KeyCode::F1 => NamedKey::F1,
+        KeyCode::F2 => NamedKey::F2,
+        KeyCode::F3 => NamedKey::F3,
+        KeyCode::F4 => NamedKey::F4,
+        KeyCode::F5 => NamedKey::F5,
+        KeyCode::F6 => NamedKey::F6,
+        KeyCode::F7 => NamedKey::F7,
+        KeyCode::F8 => NamedKey::F8,
+        KeyCode::F9 => NamedKey::F9,
+        KeyCode::F10 => NamedKey::F10,
+        KeyCode::F11 => NamedKey::F11,
+        KeyCode::F12 => NamedKey::F12,
+        _ => return Key::Unidentified(winit::keyboard::NativeKey::Unidentified),
+    };
+    Key::Named(named)
 }
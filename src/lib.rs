@@ -5,26 +5,68 @@
 
 mod render {
     pub mod gpu;
+    pub mod path;
     pub mod shape_renderer;
+    pub mod svg;
     pub mod text_renderer;
 }
 
 pub use render::gpu::{FrameFinisher, GpuContext, RenderFrame};
-pub use render::shape_renderer::ShapeRenderer;
+pub use render::path::{FillRule, LineCap, LineJoin, Path, PathCmd, StrokeStyle};
+pub use render::shape_renderer::{Layer, ShapeRenderer};
+pub use render::svg::{Svg, SvgError, SvgWarning};
 pub use render::text_renderer::TextRenderer;
 
+mod animation;
 mod app;
+mod clipboard;
+mod commands;
 mod ctx;
+mod cursor;
+mod dnd;
+mod event;
+mod focus;
 mod fonts;
+mod gesture;
+mod harness;
+mod headless;
 mod mouse;
+mod palette;
+mod replay;
+mod snapshot;
+mod style;
 mod input;
 mod color;
+mod theme;
+mod touch;
+mod transition;
+mod transform;
 mod ui;
 
+pub use animation::{AnimValue, Easing, Property};
 pub use app::{App, BentoApp};
+pub use clipboard::{
+    Clipboard, ClipboardBackend, ClipboardError, MemoryClipboard, SystemClipboard, COPY_COMMAND,
+    CUT_COMMAND, PASTE_COMMAND,
+};
+pub use commands::{BindingConflict, Command, Commands, KeyBinding};
 pub use ctx::{Ctx};
-pub use ui::{Height, Width, Rect, Text, Button};
+pub use event::InputEvent;
+pub use ui::{Height, Width, Rect, Text, Button, Canvas};
 pub use fonts::{FontId, Fonts};
+pub use gesture::{GestureSettings, Gestures};
+pub use harness::Harness;
+pub use headless::{Headless, HeadlessError, RgbaImage};
 pub use mouse::MouseState;
-pub use input::InputState;
+pub use palette::{fuzzy_score, CommandPalette, PaletteItem, PALETTE_COMMAND};
+pub use replay::{RecordedEvent, Recorder, Recording, Replay};
+pub use snapshot::{
+    assert_snapshot, check_snapshot, diff_images, ImageDiff, SnapshotError, SnapshotOptions,
+    BLESS_ENV,
+};
+pub use input::{InputState, Modifiers, Shortcut};
 pub use color::Color;
+pub use style::Style;
+pub use theme::{FontRole, FontSpec, Theme, ThemeMode, ThemeToken};
+pub use touch::{Pinch, Pointer, PointerId, SwipeDirection, TouchSettings, Touches};
+pub use transform::Transform;
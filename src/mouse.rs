@@ -1,3 +1,5 @@
+use crate::Transform;
+
 #[derive(Clone, Copy, Debug, Default)]
 pub struct MouseState {
     pub x: f32,
@@ -25,4 +27,19 @@ impl MouseState {
     pub fn is_over(&self, x: f32, y: f32, w: f32, h: f32) -> bool {
         self.x >= x && self.x <= x + w && self.y >= y && self.y <= y + h
     }
+
+    /// the cursor position in the space that `transform` maps to the screen
+    pub fn local(&self, transform: &Transform) -> Option<(f32, f32)> {
+        transform.invert().map(|inv| inv.apply(self.x, self.y))
+    }
+
+    pub fn is_over_transformed(&self, x: f32, y: f32, w: f32, h: f32, transform: &Transform) -> bool {
+        if transform.is_identity() {
+            return self.is_over(x, y, w, h);
+        }
+        match self.local(transform) {
+            Some((lx, ly)) => lx >= x && lx <= x + w && ly >= y && ly <= y + h,
+            None => false,
+        }
+    }
 }
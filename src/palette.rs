@@ -0,0 +1,393 @@
+use winit::event::MouseButton;
+use winit::keyboard::KeyCode;
+
+use crate::ui::Ui;
+use crate::{Color, Commands, Ctx, FontId, FontRole, Fonts, InputEvent, Theme};
+
+/// the registry id of the command that opens the palette
+pub const PALETTE_COMMAND: &str = "bento.command_palette";
+
+/// a searchable list of every registered command, opened with Ctrl+Shift+P
+/// and drawn over the rest of the ui. lives on `Ctx::palette`
+pub struct CommandPalette {
+    pub query: String,
+    /// index into the filtered list
+    pub selected: usize,
+    /// ids of commands run from the palette, most recent first
+    pub recent: Vec<String>,
+    pub max_recent: usize,
+    pub max_rows: usize,
+    pub width: f32,
+    /// `None` uses the theme's body font
+    pub font_id: Option<FontId>,
+    pub background: Color,
+    pub input_background: Color,
+    pub selected_background: Color,
+    pub text_color: Color,
+    pub hint_color: Color,
+    open: bool,
+    scroll: usize,
+}
+
+/// a filtered palette entry
+pub struct PaletteItem {
+    pub id: String,
+    pub title: String,
+    pub binding: Option<String>,
+}
+
+/// where the palette sits on screen this frame
+struct PaletteLayout {
+    x: f32,
+    y: f32,
+    w: f32,
+    input_h: f32,
+    row_h: f32,
+    rows: usize,
+    padding: f32,
+    font: FontId,
+}
+
+impl PaletteLayout {
+    fn height(&self) -> f32 {
+        self.input_h + self.row_h * self.rows.max(1) as f32 + self.padding
+    }
+
+    fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x && x <= self.x + self.w && y >= self.y && y <= self.y + self.height()
+    }
+
+    /// visible row under (x, y)
+    fn row_at(&self, x: f32, y: f32) -> Option<usize> {
+        let top = self.y + self.input_h;
+        if x < self.x || x > self.x + self.w || y < top {
+            return None;
+        }
+        let row = ((y - top) / self.row_h) as usize;
+        (row < self.rows).then_some(row)
+    }
+}
+
+impl Default for CommandPalette {
+    fn default() -> Self {
+        let theme = Theme::dark();
+        Self {
+            query: String::new(),
+            selected: 0,
+            recent: Vec::new(),
+            max_recent: 8,
+            max_rows: 8,
+            width: 480.0,
+            font_id: None,
+            background: theme.surface,
+            input_background: theme.surface_raised,
+            selected_background: theme.selection,
+            text_color: theme.text,
+            hint_color: theme.text_muted,
+            open: false,
+            scroll: 0,
+        }
+    }
+}
+
+impl CommandPalette {
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+        self.query.clear();
+        self.selected = 0;
+        self.scroll = 0;
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    pub fn toggle(&mut self) {
+        if self.open {
+            self.close();
+        } else {
+            self.open();
+        }
+    }
+
+    /// commands matching the query, best first. an empty query lists recent
+    /// commands first, then the rest in registration order
+    pub fn items(&self, commands: &Commands) -> Vec<PaletteItem> {
+        let recent_rank = |id: &str| {
+            self.recent
+                .iter()
+                .position(|r| r == id)
+                .unwrap_or(usize::MAX)
+        };
+
+        let mut scored: Vec<_> = commands
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.id != PALETTE_COMMAND)
+            .filter_map(|(order, c)| {
+                let score = if self.query.is_empty() {
+                    0
+                } else {
+                    fuzzy_score(&self.query, &c.title).max(fuzzy_score(&self.query, &c.id))?
+                };
+                Some((score, recent_rank(&c.id), order, c))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+
+        scored
+            .into_iter()
+            .map(|(_, _, _, c)| PaletteItem {
+                id: c.id.clone(),
+                title: c.title.clone(),
+                binding: c.bindings.first().map(|b| b.to_string()),
+            })
+            .collect()
+    }
+
+    fn remember(&mut self, id: &str) {
+        self.recent.retain(|r| r != id);
+        self.recent.insert(0, id.to_string());
+        self.recent.truncate(self.max_recent);
+    }
+
+    /// arrow keys wrap around the ends, paging stops at them
+    fn move_selection(&mut self, delta: isize, count: usize, wrap: bool) {
+        if count == 0 {
+            self.selected = 0;
+            return;
+        }
+        let selected = self.selected as isize + delta;
+        self.selected = if wrap {
+            selected.rem_euclid(count as isize) as usize
+        } else {
+            selected.clamp(0, count as isize - 1) as usize
+        };
+        self.scroll_to_selected();
+    }
+
+    fn scroll_to_selected(&mut self) {
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if self.selected >= self.scroll + self.max_rows {
+            self.scroll = self.selected + 1 - self.max_rows;
+        }
+    }
+
+    fn layout(&self, ui: &mut Ui, window_width: f32, count: usize) -> PaletteLayout {
+        let font = self.font_id.unwrap_or_else(|| ui.font(FontRole::Body));
+        let padding = ui.padding();
+        let (_, text_h) = ui.fonts.measure("Ag", font);
+        let row_h = text_h + padding * 2.0;
+        let w = self.width.min(window_width - 32.0).max(0.0);
+        PaletteLayout {
+            x: (window_width - w) * 0.5,
+            y: 48.0,
+            w,
+            input_h: row_h + padding * 2.0,
+            row_h,
+            rows: count.saturating_sub(self.scroll).min(self.max_rows),
+            padding,
+            font,
+        }
+    }
+}
+
+/// subsequence match of `query` in `text`, ignoring case. consecutive
+/// letters and word starts score higher, `None` when it doesn't match
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut last_match: Option<usize> = None;
+
+    for q in query.chars().filter(|c| !c.is_whitespace()) {
+        let q = q.to_lowercase().next()?;
+        let found = (pos..text.len()).find(|&i| text[i].to_lowercase().next() == Some(q))?;
+
+        score += 1;
+        if last_match.is_some_and(|last| last + 1 == found) {
+            score += 5;
+        }
+        let word_start = found == 0
+            || matches!(text[found - 1], ' ' | '_' | '-' | '.' | ':' | '/')
+            || (text[found - 1].is_lowercase() && text[found].is_uppercase());
+        if word_start {
+            score += 8;
+        }
+        score -= (found - pos).min(3) as i32;
+
+        last_match = Some(found);
+        pos = found + 1;
+    }
+    Some(score)
+}
+
+impl Ctx {
+    /// runs the filtered command at `index` and closes the palette
+    fn run_palette_item(&mut self, index: usize) {
+        let items = self.palette.items(&self.commands);
+        if let Some(item) = items.get(index) {
+            self.commands.run(&item.id);
+            self.palette.remember(&item.id);
+        }
+        self.palette.close();
+    }
+
+    /// keyboard and pointer handling while the palette is open. returns
+    /// true when the palette used the event and the app shouldn't see it
+    pub(crate) fn update_palette(&mut self, event: &InputEvent) -> bool {
+        if !self.palette.is_open() {
+            return false;
+        }
+        let count = self.palette.items(&self.commands).len();
+        let layout = self
+            .palette
+            .layout(&mut self.ui, self.window_width, count);
+        let mods = self.input.modifiers;
+        self.mark_dirty();
+
+        match event {
+            // shortcuts with modifiers still reach commands, e.g. to close it
+            InputEvent::Key { .. } if mods.ctrl || mods.alt || mods.logo => false,
+            InputEvent::Key {
+                key, pressed: true, ..
+            } => {
+                let page = self.palette.max_rows as isize;
+                match key {
+                    KeyCode::Escape => self.palette.close(),
+                    KeyCode::ArrowUp => self.palette.move_selection(-1, count, true),
+                    KeyCode::ArrowDown => self.palette.move_selection(1, count, true),
+                    KeyCode::PageUp => self.palette.move_selection(-page, count, false),
+                    KeyCode::PageDown => self.palette.move_selection(page, count, false),
+                    KeyCode::Enter | KeyCode::NumpadEnter => {
+                        self.run_palette_item(self.palette.selected)
+                    }
+                    KeyCode::Backspace => {
+                        self.palette.query.pop();
+                        self.palette.selected = 0;
+                        self.palette.scroll = 0;
+                    }
+                    _ => {}
+                }
+                true
+            }
+            InputEvent::Key { .. } => true,
+            InputEvent::Text(text) => {
+                self.palette.query.push_str(text);
+                self.palette.selected = 0;
+                self.palette.scroll = 0;
+                true
+            }
+            InputEvent::CursorMoved { x, y } => {
+                if let Some(row) = layout.row_at(*x, *y) {
+                    self.palette.selected = self.palette.scroll + row;
+                }
+                layout.contains(*x, *y)
+            }
+            InputEvent::MouseButton {
+                button: MouseButton::Left,
+                pressed: true,
+            } => {
+                let (x, y) = (self.mouse.x, self.mouse.y);
+                match layout.row_at(x, y) {
+                    Some(row) => self.run_palette_item(self.palette.scroll + row),
+                    None if !layout.contains(x, y) => self.palette.close(),
+                    None => {}
+                }
+                true
+            }
+            InputEvent::MouseButton { .. } => layout.contains(self.mouse.x, self.mouse.y),
+            InputEvent::Scroll { y, .. } if layout.contains(self.mouse.x, self.mouse.y) => {
+                let max_scroll = count.saturating_sub(self.palette.max_rows);
+                let scroll = self.palette.scroll as isize - y.signum() as isize;
+                self.palette.scroll = scroll.clamp(0, max_scroll as isize) as usize;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub(crate) fn render_palette(&mut self) {
+        if !self.palette.is_open() {
+            return;
+        }
+        let items = self.palette.items(&self.commands);
+        self.palette.selected = self.palette.selected.min(items.len().saturating_sub(1));
+        self.palette.scroll_to_selected();
+        let layout = self
+            .palette
+            .layout(&mut self.ui, self.window_width, items.len());
+        let palette = &self.palette;
+        let ui = &mut self.ui;
+        let (x, y, w, pad) = (layout.x, layout.y, layout.w, layout.padding);
+        let (radius, row_radius) = (ui.theme.radius_large, ui.theme.radius);
+
+        ui.rounded_rect(
+            x,
+            y,
+            w,
+            layout.height(),
+            radius,
+            palette.background.to_array(),
+            [0.0; 4],
+            0.0,
+        );
+        ui.push_clip(x, y, w, layout.height());
+
+        ui.rounded_rect(
+            x + pad,
+            y + pad,
+            w - pad * 2.0,
+            layout.row_h,
+            row_radius,
+            palette.input_background.to_array(),
+            [0.0; 4],
+            0.0,
+        );
+        let (query, query_color) = if palette.query.is_empty() {
+            ("Type a command", palette.hint_color)
+        } else {
+            (palette.query.as_str(), palette.text_color)
+        };
+        ui.draw_text(query, layout.font, x + pad * 2.0, y + pad * 2.0, query_color);
+
+        let top = y + layout.input_h;
+        if items.is_empty() {
+            let hint = palette.hint_color;
+            ui.draw_text("No matching commands", layout.font, x + pad * 2.0, top + pad, hint);
+        }
+        let visible = items.iter().enumerate().skip(palette.scroll).take(layout.rows);
+        for (row, (index, item)) in visible.enumerate() {
+            let row_y = top + row as f32 * layout.row_h;
+            if index == palette.selected {
+                ui.rounded_rect(
+                    x + pad,
+                    row_y,
+                    w - pad * 2.0,
+                    layout.row_h,
+                    row_radius,
+                    palette.selected_background.to_array(),
+                    [0.0; 4],
+                    0.0,
+                );
+            }
+            ui.draw_text(&item.title, layout.font, x + pad * 2.0, row_y + pad, palette.text_color);
+            if let Some(binding) = &item.binding {
+                let (bw, _) = ui.fonts.measure(binding, layout.font);
+                let color = if index == palette.selected {
+                    palette.text_color
+                } else {
+                    palette.hint_color
+                };
+                ui.draw_text(binding, layout.font, x + w - pad * 2.0 - bw, row_y + pad, color);
+            }
+        }
+
+        ui.pop_clip();
+    }
+}
@@ -0,0 +1,521 @@
+use lyon::geom::{Angle, Arc, ArcFlags, SvgArc, point, vector};
+use lyon::path::Path as LyonPath;
+use lyon::tessellation;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PathCmd {
+    MoveTo([f32; 2]),
+    LineTo([f32; 2]),
+    QuadTo([f32; 2], [f32; 2]),
+    CubicTo([f32; 2], [f32; 2], [f32; 2]),
+    Close,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum FillRule {
+    #[default]
+    NonZero,
+    EvenOdd,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum LineJoin {
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum LineCap {
+    #[default]
+    Butt,
+    Round,
+    Square,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrokeStyle {
+    pub width: f32,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    pub miter_limit: f32,
+    /// alternating on/off lengths, empty means a solid line. an odd number
+    /// of lengths is repeated once, like svg does, so [5] is 5 on 5 off.
+    /// patterns too fine to see at the current scale are drawn solid
+    pub dashes: Vec<f32>,
+    pub dash_offset: f32,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl StrokeStyle {
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+            miter_limit: 4.0,
+            dashes: Vec::new(),
+            dash_offset: 0.0,
+        }
+    }
+
+    pub fn join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    pub fn cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    pub fn miter_limit(mut self, limit: f32) -> Self {
+        self.miter_limit = limit;
+        self
+    }
+
+    pub fn dashes(mut self, dashes: &[f32], offset: f32) -> Self {
+        self.dashes = dashes.to_vec();
+        self.dash_offset = offset;
+        self
+    }
+
+    pub(crate) fn to_options(&self, tolerance: f32) -> tessellation::StrokeOptions {
+        let join = match self.join {
+            LineJoin::Miter => tessellation::LineJoin::Miter,
+            LineJoin::Round => tessellation::LineJoin::Round,
+            LineJoin::Bevel => tessellation::LineJoin::Bevel,
+        };
+        let cap = match self.cap {
+            LineCap::Butt => tessellation::LineCap::Butt,
+            LineCap::Round => tessellation::LineCap::Round,
+            LineCap::Square => tessellation::LineCap::Square,
+        };
+        tessellation::StrokeOptions::tolerance(tolerance)
+            .with_line_width(self.width)
+            .with_line_join(join)
+            .with_line_cap(cap)
+            .with_miter_limit(self.miter_limit.max(1.0))
+    }
+}
+
+/// a vector path in logical pixels, built up with `move_to`/`line_to`/... and
+/// drawn through `ShapeRenderer::fill_path` and `ShapeRenderer::stroke_path`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Path {
+    pub(crate) cmds: Vec<PathCmd>,
+    start: [f32; 2],
+    current: [f32; 2],
+    open: bool,
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cmds.is_empty()
+    }
+
+    pub fn cmds(&self) -> &[PathCmd] {
+        &self.cmds
+    }
+
+    pub fn current_point(&self) -> [f32; 2] {
+        self.current
+    }
+
+    fn ensure_open(&mut self) {
+        if !self.open {
+            let p = self.current;
+            self.move_to(p[0], p[1]);
+        }
+    }
+
+    pub fn move_to(&mut self, x: f32, y: f32) -> &mut Self {
+        self.cmds.push(PathCmd::MoveTo([x, y]));
+        self.start = [x, y];
+        self.current = [x, y];
+        self.open = true;
+        self
+    }
+
+    pub fn line_to(&mut self, x: f32, y: f32) -> &mut Self {
+        self.ensure_open();
+        self.cmds.push(PathCmd::LineTo([x, y]));
+        self.current = [x, y];
+        self
+    }
+
+    pub fn quad_to(&mut self, cx: f32, cy: f32, x: f32, y: f32) -> &mut Self {
+        self.ensure_open();
+        self.cmds.push(PathCmd::QuadTo([cx, cy], [x, y]));
+        self.current = [x, y];
+        self
+    }
+
+    pub fn cubic_to(&mut self, c1x: f32, c1y: f32, c2x: f32, c2y: f32, x: f32, y: f32) -> &mut Self {
+        self.ensure_open();
+        self.cmds.push(PathCmd::CubicTo([c1x, c1y], [c2x, c2y], [x, y]));
+        self.current = [x, y];
+        self
+    }
+
+    /// svg-style elliptical arc from the current point to (x, y)
+    #[allow(clippy::too_many_arguments)]
+    pub fn arc_to(
+        &mut self,
+        rx: f32,
+        ry: f32,
+        x_rotation_deg: f32,
+        large_arc: bool,
+        sweep: bool,
+        x: f32,
+        y: f32,
+    ) -> &mut Self {
+        self.ensure_open();
+        let arc = SvgArc {
+            from: point(self.current[0], self.current[1]),
+            to: point(x, y),
+            radii: vector(rx, ry),
+            x_rotation: Angle::degrees(x_rotation_deg),
+            flags: ArcFlags { large_arc, sweep },
+        };
+        if arc.is_straight_line() {
+            return self.line_to(x, y);
+        }
+        arc.for_each_cubic_bezier(&mut |seg| {
+            self.cmds.push(PathCmd::CubicTo(
+                seg.ctrl1.to_array(),
+                seg.ctrl2.to_array(),
+                seg.to.to_array(),
+            ));
+        });
+        self.current = [x, y];
+        self
+    }
+
+    /// circular arc around (cx, cy), angles in radians, clockwise on screen
+    /// for positive sweeps. connects to the current point with a line if a
+    /// subpath is already open
+    pub fn arc(&mut self, cx: f32, cy: f32, radius: f32, start_angle: f32, end_angle: f32) -> &mut Self {
+        let arc = Arc {
+            center: point(cx, cy),
+            radii: vector(radius, radius),
+            start_angle: Angle::radians(start_angle),
+            sweep_angle: Angle::radians(end_angle - start_angle),
+            x_rotation: Angle::zero(),
+        };
+        let from = arc.from();
+        if self.open {
+            self.line_to(from.x, from.y);
+        } else {
+            self.move_to(from.x, from.y);
+        }
+        arc.for_each_cubic_bezier(&mut |seg| {
+            self.cmds.push(PathCmd::CubicTo(
+                seg.ctrl1.to_array(),
+                seg.ctrl2.to_array(),
+                seg.to.to_array(),
+            ));
+        });
+        self.current = arc.to().to_array();
+        self
+    }
+
+    pub fn close(&mut self) -> &mut Self {
+        if self.open {
+            self.cmds.push(PathCmd::Close);
+            self.current = self.start;
+            self.open = false;
+        }
+        self
+    }
+
+    pub fn rect(&mut self, x: f32, y: f32, w: f32, h: f32) -> &mut Self {
+        self.move_to(x, y)
+            .line_to(x + w, y)
+            .line_to(x + w, y + h)
+            .line_to(x, y + h)
+            .close()
+    }
+
+    pub fn circle(&mut self, cx: f32, cy: f32, radius: f32) -> &mut Self {
+        self.open = false;
+        self.arc(cx, cy, radius, 0.0, std::f32::consts::TAU).close()
+    }
+
+    pub fn polyline(points: &[[f32; 2]]) -> Self {
+        let mut path = Self::new();
+        for (i, p) in points.iter().enumerate() {
+            if i == 0 {
+                path.move_to(p[0], p[1]);
+            } else {
+                path.line_to(p[0], p[1]);
+            }
+        }
+        path
+    }
+
+    pub fn polygon(points: &[[f32; 2]]) -> Self {
+        let mut path = Self::polyline(points);
+        path.close();
+        path
+    }
+
+    /// every point in the path mapped through `f`, used for transforms
+    pub fn map_points(&self, mut f: impl FnMut([f32; 2]) -> [f32; 2]) -> Self {
+        let cmds = self
+            .cmds
+            .iter()
+            .map(|cmd| match *cmd {
+                PathCmd::MoveTo(p) => PathCmd::MoveTo(f(p)),
+                PathCmd::LineTo(p) => PathCmd::LineTo(f(p)),
+                PathCmd::QuadTo(c, p) => PathCmd::QuadTo(f(c), f(p)),
+                PathCmd::CubicTo(c1, c2, p) => PathCmd::CubicTo(f(c1), f(c2), f(p)),
+                PathCmd::Close => PathCmd::Close,
+            })
+            .collect();
+        Self {
+            cmds,
+            start: f(self.start),
+            current: f(self.current),
+            open: self.open,
+        }
+    }
+
+    pub(crate) fn to_lyon(&self) -> LyonPath {
+        let mut builder = LyonPath::builder();
+        let mut open = false;
+        for cmd in &self.cmds {
+            match *cmd {
+                PathCmd::MoveTo(p) => {
+                    if open {
+                        builder.end(false);
+                    }
+                    builder.begin(point(p[0], p[1]));
+                    open = true;
+                }
+                PathCmd::LineTo(p) => {
+                    builder.line_to(point(p[0], p[1]));
+                }
+                PathCmd::QuadTo(c, p) => {
+                    builder.quadratic_bezier_to(point(c[0], c[1]), point(p[0], p[1]));
+                }
+                PathCmd::CubicTo(c1, c2, p) => {
+                    builder.cubic_bezier_to(
+                        point(c1[0], c1[1]),
+                        point(c2[0], c2[1]),
+                        point(p[0], p[1]),
+                    );
+                }
+                PathCmd::Close => {
+                    if open {
+                        builder.end(true);
+                        open = false;
+                    }
+                }
+            }
+        }
+        if open {
+            builder.end(false);
+        }
+        builder.build()
+    }
+
+    /// splits the flattened path into the "on" pieces of a dash pattern.
+    /// a pattern shorter than `tolerance` leaves the path solid, walking it
+    /// would take forever and couldn't be seen anyway
+    pub(crate) fn dashed(&self, dashes: &[f32], offset: f32, tolerance: f32) -> Self {
+        let dashes = if dashes.len() % 2 == 1 {
+            dashes.repeat(2)
+        } else {
+            dashes.to_vec()
+        };
+        let pattern_len: f32 = dashes.iter().sum();
+        let too_fine = pattern_len < tolerance.max(f32::EPSILON);
+        if dashes.is_empty() || too_fine || dashes.iter().any(|d| *d < 0.0) {
+            return self.clone();
+        }
+
+        let mut out = Path::new();
+        for polyline in self.flatten(tolerance) {
+            let mut dash_idx = 0;
+            let mut remaining = dashes[0];
+            let mut skip = offset.rem_euclid(pattern_len);
+            while skip > 0.0 {
+                if skip >= remaining {
+                    skip -= remaining;
+                    dash_idx = (dash_idx + 1) % dashes.len();
+                    remaining = dashes[dash_idx];
+                } else {
+                    remaining -= skip;
+                    skip = 0.0;
+                }
+            }
+
+            let mut pen_down = false;
+            for seg in polyline.windows(2) {
+                let (a, b) = (seg[0], seg[1]);
+                let seg_len = ((b[0] - a[0]).powi(2) + (b[1] - a[1]).powi(2)).sqrt();
+                let mut t = 0.0;
+                while t < seg_len {
+                    let step = remaining.min(seg_len - t);
+                    let lerp = |d: f32| {
+                        let k = d / seg_len;
+                        [a[0] + (b[0] - a[0]) * k, a[1] + (b[1] - a[1]) * k]
+                    };
+                    if dash_idx % 2 == 0 {
+                        let p0 = lerp(t);
+                        let p1 = lerp(t + step);
+                        if !pen_down {
+                            out.move_to(p0[0], p0[1]);
+                            pen_down = true;
+                        }
+                        out.line_to(p1[0], p1[1]);
+                    }
+                    if step > 0.0 && t + step <= t {
+                        // far from the origin the dash is below float precision
+                        break;
+                    }
+                    t += step;
+                    remaining -= step;
+                    if remaining <= 0.0 {
+                        dash_idx = (dash_idx + 1) % dashes.len();
+                        remaining = dashes[dash_idx];
+                        pen_down = false;
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// each subpath as a list of points, closed subpaths repeat their start
+    pub(crate) fn flatten(&self, tolerance: f32) -> Vec<Vec<[f32; 2]>> {
+        use lyon::path::PathEvent;
+        use lyon::path::iterator::PathIterator;
+
+        let mut polylines = Vec::new();
+        let mut current: Vec<[f32; 2]> = Vec::new();
+        for event in self.to_lyon().iter().flattened(tolerance) {
+            match event {
+                PathEvent::Begin { at } => {
+                    current = vec![at.to_array()];
+                }
+                PathEvent::Line { to, .. } => {
+                    current.push(to.to_array());
+                }
+                PathEvent::End { first, close, .. } => {
+                    if close {
+                        current.push(first.to_array());
+                    }
+                    polylines.push(std::mem::take(&mut current));
+                }
+                _ => {}
+            }
+        }
+        polylines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square() -> Path {
+        let mut path = Path::new();
+        path.rect(0.0, 0.0, 10.0, 10.0);
+        path
+    }
+
+    fn line(length: f32) -> Path {
+        Path::polyline(&[[0.0, 0.0], [length, 0.0]])
+    }
+
+    /// the start and end x of every dash along a horizontal line
+    fn spans(path: &Path) -> Vec<(f32, f32)> {
+        path.flatten(0.1)
+            .iter()
+            .map(|dash| (dash[0][0], dash[dash.len() - 1][0]))
+            .collect()
+    }
+
+    #[test]
+    fn closed_subpaths_flatten_back_to_their_start() {
+        let polylines = square().flatten(0.1);
+
+        assert_eq!(polylines.len(), 1);
+        assert_eq!(polylines[0].first(), polylines[0].last());
+        assert_eq!(polylines[0].len(), 5);
+    }
+
+    #[test]
+    fn curves_flatten_within_tolerance() {
+        let mut circle = Path::new();
+        circle.circle(0.0, 0.0, 10.0);
+        let coarse = circle.flatten(1.0);
+        let fine = circle.flatten(0.01);
+
+        assert!(fine[0].len() > coarse[0].len());
+        for [x, y] in &fine[0] {
+            assert!(((x * x + y * y).sqrt() - 10.0).abs() < 0.02);
+        }
+    }
+
+    #[test]
+    fn dashes_alternate_on_and_off() {
+        let dashed = line(10.0).dashed(&[2.0, 1.0], 0.0, 0.1);
+
+        assert_eq!(spans(&dashed), [(0.0, 2.0), (3.0, 5.0), (6.0, 8.0), (9.0, 10.0)]);
+    }
+
+    #[test]
+    fn odd_patterns_repeat_once() {
+        let dashed = line(10.0).dashed(&[3.0], 0.0, 0.1);
+
+        assert_eq!(spans(&dashed), [(0.0, 3.0), (6.0, 9.0)]);
+    }
+
+    #[test]
+    fn offsets_shift_the_pattern() {
+        let dashed = line(10.0).dashed(&[2.0, 2.0], 1.0, 0.1);
+        assert_eq!(spans(&dashed), [(0.0, 1.0), (3.0, 5.0), (7.0, 9.0)]);
+
+        // negative offsets wrap around the pattern
+        let dashed = line(10.0).dashed(&[2.0, 2.0], -1.0, 0.1);
+        assert_eq!(spans(&dashed), [(1.0, 3.0), (5.0, 7.0), (9.0, 10.0)]);
+    }
+
+    #[test]
+    fn dashes_turn_corners() {
+        let dashed = square().dashed(&[15.0, 5.0], 0.0, 0.1);
+        let dashes = dashed.flatten(0.1);
+
+        assert_eq!(dashes.len(), 2);
+        assert_eq!(dashes[0], [[0.0, 0.0], [10.0, 0.0], [10.0, 5.0]]);
+        assert_eq!(dashes[1], [[10.0, 10.0], [0.0, 10.0], [0.0, 5.0]]);
+    }
+
+    #[test]
+    fn patterns_finer_than_the_tolerance_stay_solid() {
+        let path = line(1.0e6);
+        let dashed = path.dashed(&[1.0e-4, 1.0e-4], 0.0, 0.1);
+
+        assert_eq!(spans(&dashed), [(0.0, 1.0e6)]);
+    }
+
+    #[test]
+    fn unusable_patterns_stay_solid() {
+        for dashes in [&[0.0, 0.0][..], &[2.0, -1.0]] {
+            assert_eq!(spans(&line(10.0).dashed(dashes, 0.0, 0.1)), [(0.0, 10.0)]);
+        }
+    }
+}
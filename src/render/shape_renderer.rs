@@ -1,5 +1,15 @@
 use wgpu;
 use std::mem;
+use lyon::tessellation::{
+    BuffersBuilder, FillOptions, FillTessellator, FillVertex, StrokeTessellator, StrokeVertex,
+    VertexBuffers,
+};
+use crate::render::path::{FillRule, Path, StrokeStyle};
+use crate::render::svg::{Svg, SvgItem, SvgPaint};
+use crate::Transform;
+
+/// max distance in logical pixels between a curve and its flattened segments
+const PATH_TOLERANCE: f32 = 0.1;
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
@@ -8,76 +18,108 @@ struct Vertex {
     color: [f32; 4],
 }
 
-pub struct ShapeRenderer {
+/// the gpu side of the renderer, missing when running without a gpu
+struct ShapeGpu {
     pipeline: wgpu::RenderPipeline,
     vertex_buffer: wgpu::Buffer,
+    composite_pipeline: wgpu::RenderPipeline,
+    format: wgpu::TextureFormat,
+    /// one per nesting depth, siblings take turns
+    group_targets: Vec<GroupTarget>,
+    group_target_size: (u32, u32),
+}
+
+/// the offscreen layer faded groups are drawn into before they're
+/// composited onto their parent
+struct GroupTarget {
+    msaa_view: wgpu::TextureView,
+    view: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+}
+
+pub struct ShapeRenderer {
+    gpu: Option<ShapeGpu>,
     vertices: Vec<Vertex>,
     screen_width: f32,
     screen_height: f32,
     vertex_capacity: usize,
     ndc_scale_x: f32,
     ndc_scale_y: f32,
+    fill_tess: FillTessellator,
+    stroke_tess: StrokeTessellator,
+    path_geometry: VertexBuffers<[f32; 2], u32>,
+    transform: Transform,
+    transform_stack: Vec<Transform>,
+    scale_factor: f64,
+    clip: Option<[f32; 4]>,
+    clip_stack: Vec<Option<[f32; 4]>>,
+    /// multiplies the alpha of everything drawn
+    opacity: f32,
+    opacity_stack: Vec<f32>,
+    batches: Vec<ShapeBatch>,
+    /// first vertex drawn on the overlay layer
+    overlay_start: Option<u32>,
+    /// the faded group drawn into right now, `None` for the screen
+    group: Option<usize>,
+    group_stack: Vec<Option<OpenGroup>>,
+    groups: Vec<Group>,
+}
+
+/// renderers draw in two layers: everything in `Base`, then everything in
+/// `Overlay`, so overlay shapes cover base text too
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layer {
+    Base,
+    Overlay,
+}
+
+/// a run of vertices sharing one scissor rect and target
+struct ShapeBatch {
+    start: u32,
+    clip: Option<[f32; 4]>,
+    /// the faded group these vertices draw into, `None` for the screen
+    group: Option<usize>,
+    /// set when the run is the quad drawing that group's layer back
+    composite: Option<usize>,
+}
+
+/// a group faded as a whole, see `push_group`
+struct Group {
+    /// vertices from `start` to `end` belong to the group or its children
+    start: u32,
+    end: u32,
+    /// how many faded groups it's nested in
+    depth: usize,
+}
+
+struct OpenGroup {
+    index: usize,
+    parent: Option<usize>,
+    opacity: f32,
+    clip: Option<[f32; 4]>,
+    /// opened by `resume_group`, the group is already composited
+    resumed: bool,
 }
 
 impl ShapeRenderer {
     pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, width: f32, height: f32) -> Self {
-        let vertex_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Shape Shader"),
             source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/shape.wgsl").into()),
         });
+        let pipeline = create_pipeline(device, format, &shader, wgpu::BlendState::ALPHA_BLENDING, "Shape Pipeline");
 
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Shape Pipeline"),
-            layout: None,
-            vertex: wgpu::VertexState {
-                module: &vertex_shader,
-                entry_point: Some("vs_main"),
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-                buffers: &[wgpu::VertexBufferLayout {
-                    array_stride: mem::size_of::<Vertex>() as wgpu::BufferAddress,
-                    step_mode: wgpu::VertexStepMode::Vertex,
-                    attributes: &[
-                        wgpu::VertexAttribute {
-                            offset: 0,
-                            shader_location: 0,
-                            format: wgpu::VertexFormat::Float32x2,
-                        },
-                        wgpu::VertexAttribute {
-                            offset: mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
-                            shader_location: 1,
-                            format: wgpu::VertexFormat::Float32x4,
-                        },
-                    ],
-                }],
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &vertex_shader,
-                entry_point: Some("fs_main"),
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: None,
-                unclipped_depth: false,
-                polygon_mode: wgpu::PolygonMode::Fill,
-                conservative: false,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 4,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
-            cache: None,
+        let composite_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Composite Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../../shaders/composite.wgsl").into()),
         });
+        let composite_pipeline = create_pipeline(
+            device,
+            format,
+            &composite_shader,
+            wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+            "Composite Pipeline",
+        );
 
         let vertex_capacity = 4096;
         let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
@@ -87,28 +129,236 @@ impl ShapeRenderer {
             mapped_at_creation: false,
         });
 
+        let gpu = ShapeGpu {
+            pipeline,
+            vertex_buffer,
+            composite_pipeline,
+            format,
+            group_targets: Vec::new(),
+            group_target_size: (0, 0),
+        };
+        Self::build(Some(gpu), width, height)
+    }
+
+    /// a renderer that tessellates and records vertices but never draws, for
+    /// tests and tools that run without a gpu
+    pub fn without_gpu(width: f32, height: f32) -> Self {
+        Self::build(None, width, height)
+    }
+
+    fn build(gpu: Option<ShapeGpu>, width: f32, height: f32) -> Self {
+        let vertex_capacity = 4096;
         let ndc_scale_x = 2.0 / width;
         let ndc_scale_y = 2.0 / height;
 
         Self {
-            pipeline,
-            vertex_buffer,
+            gpu,
             vertices: Vec::with_capacity(vertex_capacity),
             screen_width: width,
             screen_height: height,
             vertex_capacity,
             ndc_scale_x,
             ndc_scale_y,
+            fill_tess: FillTessellator::new(),
+            stroke_tess: StrokeTessellator::new(),
+            path_geometry: VertexBuffers::new(),
+            transform: Transform::IDENTITY,
+            transform_stack: Vec::new(),
+            scale_factor: 1.0,
+            clip: None,
+            clip_stack: Vec::new(),
+            opacity: 1.0,
+            opacity_stack: Vec::new(),
+            batches: Vec::new(),
+            overlay_start: None,
+            group: None,
+            group_stack: Vec::new(),
+            groups: Vec::new(),
         }
     }
 
     #[inline(always)]
     pub fn clear(&mut self) {
         self.vertices.clear();
+        self.transform = Transform::IDENTITY;
+        self.transform_stack.clear();
+        self.clip = None;
+        self.clip_stack.clear();
+        self.opacity = 1.0;
+        self.opacity_stack.clear();
+        self.batches.clear();
+        self.overlay_start = None;
+        self.group = None;
+        self.group_stack.clear();
+        self.groups.clear();
+    }
+
+    /// everything drawn from now until `clear` goes on the overlay layer
+    pub fn begin_overlay(&mut self) {
+        if self.overlay_start.is_none() {
+            self.overlay_start = Some(self.vertices.len() as u32);
+        }
+    }
+
+    pub fn layer(&self) -> Layer {
+        match self.overlay_start {
+            Some(_) => Layer::Overlay,
+            None => Layer::Base,
+        }
+    }
+
+    /// clips everything drawn until the matching `pop_clip` to the
+    /// intersection of (x, y, w, h) and the current clip. the rect is in
+    /// screen space and ignores the transform stack
+    pub fn push_clip(&mut self, x: f32, y: f32, w: f32, h: f32) {
+        self.clip_stack.push(self.clip);
+        let rect = match self.clip {
+            Some(current) => intersect_rects(current, [x, y, w, h]),
+            None => [x, y, w.max(0.0), h.max(0.0)],
+        };
+        self.set_clip(Some(rect));
+    }
+
+    pub fn pop_clip(&mut self) {
+        if let Some(clip) = self.clip_stack.pop() {
+            self.set_clip(clip);
+        }
+    }
+
+    pub fn clip(&self) -> Option<[f32; 4]> {
+        self.clip
+    }
+
+    fn set_clip(&mut self, clip: Option<[f32; 4]>) {
+        self.clip = clip;
+        self.start_batch(None);
+    }
+
+    fn start_batch(&mut self, composite: Option<usize>) {
+        let batch = ShapeBatch {
+            start: self.vertices.len() as u32,
+            clip: self.clip,
+            group: self.group,
+            composite,
+        };
+        match self.batches.last_mut() {
+            Some(last) if last.start == batch.start => *last = batch,
+            _ => self.batches.push(batch),
+        }
+    }
+
+    /// draws everything until the matching `pop_group` into an offscreen
+    /// layer that's faded as a whole when it's drawn back, so overlapping
+    /// shapes in the group don't show through each other. returns the
+    /// group's index, or `None` when `opacity` is 1 and there's nothing to fade
+    pub fn push_group(&mut self, opacity: f32) -> Option<usize> {
+        let opacity = opacity.clamp(0.0, 1.0);
+        if opacity >= 1.0 {
+            self.group_stack.push(None);
+            return None;
+        }
+        let index = self.groups.len();
+        let start = self.vertices.len() as u32;
+        self.groups.push(Group {
+            start,
+            end: start,
+            depth: self.group_stack.iter().flatten().count(),
+        });
+        self.group_stack.push(Some(OpenGroup {
+            index,
+            parent: self.group,
+            opacity,
+            clip: self.clip,
+            resumed: false,
+        }));
+        self.group = Some(index);
+        self.start_batch(None);
+        Some(index)
+    }
+
+    pub fn pop_group(&mut self) {
+        let Some(Some(open)) = self.group_stack.pop() else {
+            return;
+        };
+        self.groups[open.index].end = self.vertices.len() as u32;
+        self.group = open.parent;
+        if open.resumed {
+            self.start_batch(None);
+            return;
+        }
+
+        // the layer is screen sized, the quad only needs to cover the clip
+        let [x, y, w, h] = open.clip.unwrap_or([0.0, 0.0, self.screen_width, self.screen_height]);
+        let ndc = |x: f32, y: f32| [x * self.ndc_scale_x - 1.0, 1.0 - y * self.ndc_scale_y];
+        let (p1, p2, p3, p4) = (ndc(x, y), ndc(x + w, y), ndc(x, y + h), ndc(x + w, y + h));
+        let color = [1.0, 1.0, 1.0, open.opacity];
+        self.start_batch(Some(open.index));
+        self.vertices
+            .extend([p1, p2, p3, p2, p4, p3].map(|position| Vertex { position, color }));
+        self.start_batch(None);
+    }
+
+    /// draws into `group` again until the matching `pop_group`. the layer
+    /// is still composited where the group was first drawn
+    pub fn resume_group(&mut self, group: usize) {
+        if group >= self.groups.len() {
+            self.group_stack.push(None);
+            return;
+        }
+        self.group_stack.push(Some(OpenGroup {
+            index: group,
+            parent: self.group,
+            opacity: 1.0,
+            clip: self.clip,
+            resumed: true,
+        }));
+        self.group = Some(group);
+        self.start_batch(None);
+    }
+
+    /// fades everything drawn until the matching `pop_opacity`, nested
+    /// opacities multiply
+    pub fn push_opacity(&mut self, opacity: f32) {
+        self.opacity_stack.push(self.opacity);
+        self.opacity *= opacity.clamp(0.0, 1.0);
+    }
+
+    pub fn pop_opacity(&mut self) {
+        if let Some(opacity) = self.opacity_stack.pop() {
+            self.opacity = opacity;
+        }
+    }
+
+    pub fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    #[inline(always)]
+    fn faded(&self, mut color: [f32; 4]) -> [f32; 4] {
+        color[3] *= self.opacity;
+        color
+    }
+
+    /// everything drawn until the matching `pop_transform` goes through
+    /// `transform` first, then through the transforms pushed before it
+    pub fn push_transform(&mut self, transform: Transform) {
+        self.transform_stack.push(self.transform);
+        self.transform = transform.then(self.transform);
+    }
+
+    pub fn pop_transform(&mut self) {
+        if let Some(t) = self.transform_stack.pop() {
+            self.transform = t;
+        }
+    }
+
+    pub fn transform(&self) -> Transform {
+        self.transform
     }
 
     #[inline(always)]
     fn to_ndc(&self, x: f32, y: f32) -> [f32; 2] {
+        let (x, y) = self.transform.apply(x, y);
         [
             x * self.ndc_scale_x - 1.0,
             1.0 - y * self.ndc_scale_y,
@@ -117,6 +367,7 @@ impl ShapeRenderer {
 
     #[inline(always)]
     fn push_quad(&mut self, p1: [f32; 2], p2: [f32; 2], p3: [f32; 2], p4: [f32; 2], color: [f32; 4]) {
+        let color = self.faded(color);
         self.vertices.reserve(6);
         
         unsafe {
@@ -134,6 +385,7 @@ impl ShapeRenderer {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn rect(&mut self, x: f32, y: f32, w: f32, h: f32, color: [f32; 4], outline_color: [f32; 4], outline_thickness: f32) {
         let p1 = self.to_ndc(x, y);
         let p2 = self.to_ndc(x + w, y);
@@ -184,6 +436,7 @@ impl ShapeRenderer {
     }
 
     #[inline(always)]
+    #[allow(clippy::too_many_arguments)]
     pub fn draw_rect(&mut self, x: f32, y: f32, w: f32, h: f32, color: [f32; 4], outline_color: [f32; 4], outline_thickness: f32) {
         self.rect(x, y, w, h, color, outline_color, outline_thickness);
     }
@@ -192,15 +445,16 @@ impl ShapeRenderer {
         const SEGMENTS: usize = 32;
         
         self.vertices.reserve(SEGMENTS * 3);
+        let color = self.faded(color);
         
         let center = self.to_ndc(cx, cy);
         
         use std::sync::LazyLock;
         static CIRCLE_LUT: LazyLock<[(f32, f32); 33]> = LazyLock::new(|| {
             let mut lut = [(0.0, 0.0); 33];
-            for i in 0..=32 {
+            for (i, entry) in lut.iter_mut().enumerate() {
                 let angle = (i as f32 / 32.0) * 2.0 * std::f32::consts::PI;
-                lut[i] = (angle.cos(), angle.sin());
+                *entry = (angle.cos(), angle.sin());
             }
             lut
         });
@@ -239,9 +493,9 @@ impl ShapeRenderer {
         use std::sync::LazyLock;
         static CIRCLE_LUT: LazyLock<[(f32, f32); 33]> = LazyLock::new(|| {
             let mut lut = [(0.0, 0.0); 33];
-            for i in 0..=32 {
+            for (i, entry) in lut.iter_mut().enumerate() {
                 let angle = (i as f32 / 32.0) * 2.0 * std::f32::consts::PI;
-                lut[i] = (angle.cos(), angle.sin());
+                *entry = (angle.cos(), angle.sin());
             }
             lut
         });
@@ -264,6 +518,7 @@ impl ShapeRenderer {
         self.circle(cx, cy, radius, color, outline_color, outline_thickness);
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn rounded_rect(&mut self, x: f32, y: f32, w: f32, h: f32, radius: f32, color: [f32; 4], outline_color: [f32; 4], outline_thickness: f32) {
         let radius = radius.min(w * 0.5).min(h * 0.5);
         
@@ -287,6 +542,7 @@ impl ShapeRenderer {
         let start_angle = quarter as f32 * std::f32::consts::FRAC_PI_2;
         
         self.vertices.reserve(SEGMENTS * 3);
+        let color = self.faded(color);
         let center = self.to_ndc(cx, cy);
         
         for i in 0..SEGMENTS {
@@ -308,6 +564,7 @@ impl ShapeRenderer {
     }
 
     #[inline]
+    #[allow(clippy::too_many_arguments)]
     fn rounded_rect_outline_fast(&mut self, x: f32, y: f32, w: f32, h: f32, radius: f32, color: [f32; 4], thickness: f32) {
         let half = thickness * 0.5;
         
@@ -348,26 +605,147 @@ impl ShapeRenderer {
     }
 
     #[inline(always)]
+    #[allow(clippy::too_many_arguments)]
     pub fn draw_rounded_rect(&mut self, x: f32, y: f32, w: f32, h: f32, radius: f32, color: [f32; 4], outline_color: [f32; 4], outline_thickness: f32) {
         self.rounded_rect(x, y, w, h, radius, color, outline_color, outline_thickness);
     }
 
-    pub fn render<'pass>(
-        &'pass mut self,
-        device: &wgpu::Device,
-        queue: &wgpu::Queue,
-        pass: &mut wgpu::RenderPass<'pass>,
-    ) {
+    pub fn fill_path(&mut self, path: &Path, rule: FillRule, color: [f32; 4]) {
+        if path.is_empty() {
+            return;
+        }
+
+        let rule = match rule {
+            FillRule::NonZero => lyon::tessellation::FillRule::NonZero,
+            FillRule::EvenOdd => lyon::tessellation::FillRule::EvenOdd,
+        };
+        let options = FillOptions::tolerance(self.path_tolerance()).with_fill_rule(rule);
+
+        self.path_geometry.vertices.clear();
+        self.path_geometry.indices.clear();
+        let result = self.fill_tess.tessellate_path(
+            &path.to_lyon(),
+            &options,
+            &mut BuffersBuilder::new(&mut self.path_geometry, |v: FillVertex| {
+                v.position().to_array()
+            }),
+        );
+        if result.is_ok() {
+            self.push_path_geometry(color);
+        }
+    }
+
+    pub fn stroke_path(&mut self, path: &Path, style: &StrokeStyle, color: [f32; 4]) {
+        if path.is_empty() || style.width <= 0.0 {
+            return;
+        }
+
+        let tolerance = self.path_tolerance();
+        let dashed;
+        let path = if style.dashes.is_empty() {
+            path
+        } else {
+            dashed = path.dashed(&style.dashes, style.dash_offset, tolerance);
+            &dashed
+        };
+        let options = style.to_options(tolerance);
+
+        self.path_geometry.vertices.clear();
+        self.path_geometry.indices.clear();
+        let result = self.stroke_tess.tessellate_path(
+            &path.to_lyon(),
+            &options,
+            &mut BuffersBuilder::new(&mut self.path_geometry, |v: StrokeVertex| {
+                v.position().to_array()
+            }),
+        );
+        if result.is_ok() {
+            self.push_path_geometry(color);
+        }
+    }
+
+    #[inline(always)]
+    pub fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, width: f32, color: [f32; 4]) {
+        self.stroke_path(&Path::polyline(&[[x1, y1], [x2, y2]]), &StrokeStyle::new(width), color);
+    }
+
+    #[inline(always)]
+    pub fn polyline(&mut self, points: &[[f32; 2]], style: &StrokeStyle, color: [f32; 4]) {
+        self.stroke_path(&Path::polyline(points), style, color);
+    }
+
+    /// draws the svg scaled to fill (x, y, w, h). groups with an opacity
+    /// are faded as a whole through `push_group`. gradients and patterns
+    /// aren't supported, they're filled with the average of their stop
+    /// colors
+    pub fn svg(&mut self, svg: &Svg, x: f32, y: f32, w: f32, h: f32) {
+        self.svg_items(svg.placed(x, y, w, h, None));
+    }
+
+    /// like `svg` but every fill and stroke uses `tint`, for monochrome icons
+    pub fn svg_tinted(&mut self, svg: &Svg, x: f32, y: f32, w: f32, h: f32, tint: [f32; 4]) {
+        self.svg_items(svg.placed(x, y, w, h, Some(tint)));
+    }
+
+    fn svg_items(&mut self, items: impl Iterator<Item = SvgItem>) {
+        for item in items {
+            match item {
+                SvgItem::Shape(shape) => match &shape.paint {
+                    SvgPaint::Fill(rule) => self.fill_path(&shape.path, *rule, shape.color),
+                    SvgPaint::Stroke(style) => self.stroke_path(&shape.path, style, shape.color),
+                },
+                SvgItem::BeginGroup(opacity) => {
+                    self.push_group(opacity);
+                }
+                SvgItem::EndGroup => self.pop_group(),
+            }
+        }
+    }
+
+    /// paths are tessellated before the transform, so zooming in needs
+    /// finer segments to stay smooth
+    fn path_tolerance(&self) -> f32 {
+        PATH_TOLERANCE / self.transform.uniform_scale().max(1e-3)
+    }
+
+    fn push_path_geometry(&mut self, color: [f32; 4]) {
+        let color = self.faded(color);
+        let geometry = mem::take(&mut self.path_geometry);
+        self.vertices.reserve(geometry.indices.len());
+        for &i in &geometry.indices {
+            let [x, y] = geometry.vertices[i as usize];
+            let position = self.to_ndc(x, y);
+            self.vertices.push(Vertex { position, color });
+        }
+        self.path_geometry = geometry;
+    }
+
+    /// draws both layers. faded groups need passes of their own and are
+    /// left out, see `draw_range`
+    pub fn render(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, pass: &mut wgpu::RenderPass<'_>) {
+        self.prepare(device, queue);
+        self.render_layer(pass, Layer::Base);
+        self.render_layer(pass, Layer::Overlay);
+    }
+
+    /// uploads this frame's vertices and makes room for its faded groups,
+    /// call once before drawing
+    pub fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
         if self.vertices.is_empty() {
             return;
         }
+        let (target_w, target_h) = self.target_size();
+        let depth = self.groups.iter().map(|g| g.depth + 1).max().unwrap_or(0);
+        let Some(gpu) = self.gpu.as_mut() else {
+            return;
+        };
 
         let vertex_data = bytemuck::cast_slice(&self.vertices);
         let required_size = vertex_data.len() as u64;
         
-        if required_size > self.vertex_buffer.size() {
+        if required_size > gpu.vertex_buffer.size() {
             let new_size = (required_size * 3 / 2).max(required_size);
-            self.vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            gpu.vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
                 label: Some("Shape Vertex Buffer"),
                 size: new_size,
                 usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
@@ -376,17 +754,253 @@ impl ShapeRenderer {
             self.vertex_capacity = (new_size / mem::size_of::<Vertex>() as u64) as usize;
         }
         
-        queue.write_buffer(&self.vertex_buffer, 0, vertex_data);
+        queue.write_buffer(&gpu.vertex_buffer, 0, vertex_data);
 
-        pass.set_pipeline(&self.pipeline);
-        pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-        pass.draw(0..self.vertices.len() as u32, 0..1);
+        if gpu.group_target_size != (target_w, target_h) {
+            gpu.group_targets.clear();
+            gpu.group_target_size = (target_w, target_h);
+        }
+        while gpu.group_targets.len() < depth {
+            let target = create_group_target(device, gpu, target_w.max(1), target_h.max(1));
+            gpu.group_targets.push(target);
+        }
+    }
+
+    /// draws the screen's part of `layer`. faded groups in it are left out,
+    /// drawing them needs `draw_range` and a pass per group
+    pub fn render_layer(&self, pass: &mut wgpu::RenderPass<'_>, layer: Layer) {
+        let (lo, hi) = self.layer_range(layer);
+        self.draw_runs(pass, None, lo, hi, false);
+    }
+
+    /// the vertices of `layer`, faded groups in it included
+    pub fn layer_range(&self, layer: Layer) -> (u32, u32) {
+        let len = self.vertices.len() as u32;
+        let split = self.overlay_start.unwrap_or(len);
+        match layer {
+            Layer::Base => (0, split),
+            Layer::Overlay => (split, len),
+        }
+    }
+
+    /// the vertices of a faded group, its children included
+    pub fn group_range(&self, group: usize) -> (u32, u32) {
+        let group = &self.groups[group];
+        (group.start, group.end)
+    }
+
+    /// the groups drawn straight onto `target` (`None` for the screen)
+    /// between vertex `lo` and `hi`, each with the vertex right after the
+    /// quad that composites it
+    pub fn groups_in(&self, target: Option<usize>, lo: u32, hi: u32) -> Vec<(usize, u32)> {
+        self.batches
+            .iter()
+            .filter(|b| b.group == target && (lo..hi).contains(&b.start))
+            .filter_map(|b| Some((b.composite?, b.start + 6)))
+            .collect()
     }
 
-    pub fn resize(&mut self, width: f32, height: f32) {
+    /// the multisampled view to draw `group` into and the view it resolves
+    /// to, `None` without a gpu
+    pub fn group_views(&self, group: usize) -> Option<(&wgpu::TextureView, &wgpu::TextureView)> {
+        let gpu = self.gpu.as_ref()?;
+        let target = gpu.group_targets.get(self.groups[group].depth)?;
+        Some((&target.msaa_view, &target.view))
+    }
+
+    /// draws the vertices between `lo` and `hi` that go onto `target`
+    /// (`None` for the screen), composites included. a group's layer has to
+    /// be drawn, in a pass of its own, before the quad compositing it
+    pub fn draw_range(&self, pass: &mut wgpu::RenderPass<'_>, target: Option<usize>, lo: u32, hi: u32) {
+        self.draw_runs(pass, target, lo, hi, true);
+    }
+
+    fn draw_runs(&self, pass: &mut wgpu::RenderPass<'_>, target: Option<usize>, lo: u32, hi: u32, composites: bool) {
+        let Some(gpu) = self.gpu.as_ref() else {
+            return;
+        };
+        if lo >= hi {
+            return;
+        }
+        let len = self.vertices.len() as u32;
+        pass.set_vertex_buffer(0, gpu.vertex_buffer.slice(..));
+
+        let (target_w, target_h) = self.target_size();
+
+        // unclipped screen vertices before the first batch, then one draw
+        // per batch, each cut down to the range
+        let first = self.batches.first().map_or(len, |b| b.start);
+        let runs = std::iter::once((0, first, None, None, None)).chain(self.batches.iter().enumerate().map(
+            |(i, batch)| {
+                let end = self.batches.get(i + 1).map_or(len, |b| b.start);
+                (batch.start, end, batch.clip, batch.group, batch.composite)
+            },
+        ));
+        for (start, end, clip, group, composite) in runs {
+            let (start, end) = (start.max(lo), end.min(hi));
+            if end <= start || group != target {
+                continue;
+            }
+            match composite {
+                Some(composite) => {
+                    let layer = gpu.group_targets.get(self.groups[composite].depth);
+                    let Some(layer) = layer.filter(|_| composites) else {
+                        continue;
+                    };
+                    pass.set_pipeline(&gpu.composite_pipeline);
+                    pass.set_bind_group(0, &layer.bind_group, &[]);
+                }
+                None => pass.set_pipeline(&gpu.pipeline),
+            }
+            match clip {
+                Some(clip) => {
+                    let Some([x, y, w, h]) =
+                        physical_scissor(clip, self.scale_factor as f32, target_w, target_h)
+                    else {
+                        continue;
+                    };
+                    pass.set_scissor_rect(x, y, w, h);
+                }
+                None => pass.set_scissor_rect(0, 0, target_w, target_h),
+            }
+            pass.draw(start..end, 0..1);
+        }
+        pass.set_scissor_rect(0, 0, target_w, target_h);
+    }
+
+    /// physical size of the render target
+    fn target_size(&self) -> (u32, u32) {
+        (
+            (self.screen_width * self.scale_factor as f32).round().max(0.0) as u32,
+            (self.screen_height * self.scale_factor as f32).round().max(0.0) as u32,
+        )
+    }
+
+    /// number of vertices recorded this frame
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    pub fn resize(&mut self, width: f32, height: f32, scale_factor: f64) {
         self.screen_width = width;
         self.screen_height = height;
+        self.scale_factor = scale_factor;
         self.ndc_scale_x = 2.0 / width;
         self.ndc_scale_y = 2.0 / height;
     }
 }
+
+pub(crate) fn intersect_rects(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    let x0 = a[0].max(b[0]);
+    let y0 = a[1].max(b[1]);
+    let x1 = (a[0] + a[2]).min(b[0] + b[2]);
+    let y1 = (a[1] + a[3]).min(b[1] + b[3]);
+    [x0, y0, (x1 - x0).max(0.0), (y1 - y0).max(0.0)]
+}
+
+/// a logical clip rect as a scissor rect inside the render target, or
+/// `None` when nothing of it is visible
+fn physical_scissor(clip: [f32; 4], scale: f32, target_w: u32, target_h: u32) -> Option<[u32; 4]> {
+    let x0 = (clip[0] * scale).floor().clamp(0.0, target_w as f32) as u32;
+    let y0 = (clip[1] * scale).floor().clamp(0.0, target_h as f32) as u32;
+    let x1 = ((clip[0] + clip[2]) * scale).ceil().clamp(0.0, target_w as f32) as u32;
+    let y1 = ((clip[1] + clip[3]) * scale).ceil().clamp(0.0, target_h as f32) as u32;
+    if x1 <= x0 || y1 <= y0 {
+        return None;
+    }
+    Some([x0, y0, x1 - x0, y1 - y0])
+}
+
+fn create_pipeline(
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+    shader: &wgpu::ShaderModule,
+    blend: wgpu::BlendState,
+    label: &str,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(label),
+        layout: None,
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some("vs_main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            buffers: &[wgpu::VertexBufferLayout {
+                array_stride: mem::size_of::<Vertex>() as wgpu::BufferAddress,
+                step_mode: wgpu::VertexStepMode::Vertex,
+                attributes: &[
+                    wgpu::VertexAttribute {
+                        offset: 0,
+                        shader_location: 0,
+                        format: wgpu::VertexFormat::Float32x2,
+                    },
+                    wgpu::VertexAttribute {
+                        offset: mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+                        shader_location: 1,
+                        format: wgpu::VertexFormat::Float32x4,
+                    },
+                ],
+            }],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some("fs_main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(blend),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            unclipped_depth: false,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            conservative: false,
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: 4,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+        cache: None,
+    })
+}
+
+/// a layer the size of the render target, multisampled like the screen
+fn create_group_target(device: &wgpu::Device, gpu: &ShapeGpu, width: u32, height: u32) -> GroupTarget {
+    let size = wgpu::Extent3d { width, height, depth_or_array_layers: 1 };
+    let texture = |sample_count, usage| {
+        device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("Group Layer"),
+                size,
+                mip_level_count: 1,
+                sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format: gpu.format,
+                usage,
+                view_formats: &[],
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default())
+    };
+    let msaa_view = texture(4, wgpu::TextureUsages::RENDER_ATTACHMENT);
+    let view = texture(
+        1,
+        wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+    );
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Group Layer Bind Group"),
+        layout: &gpu.composite_pipeline.get_bind_group_layout(0),
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: wgpu::BindingResource::TextureView(&view),
+        }],
+    });
+    GroupTarget { msaa_view, view, bind_group }
+}
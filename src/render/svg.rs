@@ -0,0 +1,466 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use usvg::fontdb::{Database, Family};
+use usvg::tiny_skia_path::{PathSegment, Point, Transform};
+
+use crate::render::path::{FillRule, LineCap, LineJoin, Path, StrokeStyle};
+
+#[derive(Debug, Clone)]
+pub struct SvgError(String);
+
+impl fmt::Display for SvgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to load svg: {}", self.0)
+    }
+}
+
+impl std::error::Error for SvgError {}
+
+/// content an svg has that can't be drawn as authored
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SvgWarning {
+    /// raster and nested svg images, left out
+    Image,
+    /// text no installed font can draw, left out
+    Text,
+    /// gradients, drawn in the average of their stop colors
+    Gradient,
+    /// patterns, drawn black
+    Pattern,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum SvgPaint {
+    Fill(FillRule),
+    Stroke(StrokeStyle),
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct SvgShape {
+    pub(crate) path: Path,
+    pub(crate) paint: SvgPaint,
+    pub(crate) color: [f32; 4],
+}
+
+/// a shape, or the start or end of a group faded as a whole
+#[derive(Clone, Debug)]
+pub(crate) enum SvgItem {
+    Shape(SvgShape),
+    BeginGroup(f32),
+    EndGroup,
+}
+
+/// a parsed svg document flattened into paths in document units. drawn
+/// through the shape pipeline so it stays sharp at any size and scale factor
+#[derive(Clone, Debug)]
+pub struct Svg {
+    pub width: f32,
+    pub height: f32,
+    /// what couldn't be drawn as authored, each kind once
+    pub warnings: Vec<SvgWarning>,
+    pub(crate) items: Vec<SvgItem>,
+}
+
+impl Svg {
+    /// parses an svg, its text is drawn with the system fonts
+    pub fn from_bytes(data: &[u8]) -> Result<Self, SvgError> {
+        // usvg leaves out text it has no font for without saying so
+        let missing_font = AtomicBool::new(false);
+        let select_font = usvg::FontResolver::default_font_selector();
+        let options = usvg::Options {
+            fontdb: system_fonts(),
+            font_resolver: usvg::FontResolver {
+                select_font: Box::new(|font, fonts| {
+                    let id = select_font(font, fonts);
+                    if id.is_none() {
+                        missing_font.store(true, Ordering::Relaxed);
+                    }
+                    id
+                }),
+                ..usvg::FontResolver::default()
+            },
+            ..usvg::Options::default()
+        };
+        let tree = usvg::Tree::from_data(data, &options).map_err(|e| SvgError(e.to_string()))?;
+
+        let mut items = Vec::new();
+        let mut warnings = Vec::new();
+        if missing_font.load(Ordering::Relaxed) {
+            warnings.push(SvgWarning::Text);
+        }
+        collect_group(tree.root(), &mut items, &mut warnings);
+
+        Ok(Self {
+            width: tree.size().width(),
+            height: tree.size().height(),
+            warnings,
+            items,
+        })
+    }
+
+    /// the shapes scaled and moved into the (x, y, w, h) box, with `tint`
+    /// replacing every color while keeping its alpha
+    pub(crate) fn placed(
+        &self,
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        tint: Option<[f32; 4]>,
+    ) -> impl Iterator<Item = SvgItem> + '_ {
+        let sx = if self.width > 0.0 { w / self.width } else { 1.0 };
+        let sy = if self.height > 0.0 { h / self.height } else { 1.0 };
+        let stroke_scale = (sx * sy).abs().sqrt();
+
+        self.items.iter().map(move |item| {
+            let SvgItem::Shape(shape) = item else {
+                return item.clone();
+            };
+            let path = shape.path.map_points(|[px, py]| [x + px * sx, y + py * sy]);
+            let paint = match &shape.paint {
+                SvgPaint::Fill(rule) => SvgPaint::Fill(*rule),
+                SvgPaint::Stroke(style) => {
+                    let mut style = style.clone();
+                    style.width *= stroke_scale;
+                    style.dash_offset *= stroke_scale;
+                    for dash in &mut style.dashes {
+                        *dash *= stroke_scale;
+                    }
+                    SvgPaint::Stroke(style)
+                }
+            };
+            let color = match tint {
+                Some(t) => [t[0], t[1], t[2], t[3] * shape.color[3]],
+                None => shape.color,
+            };
+            SvgItem::Shape(SvgShape { path, paint, color })
+        })
+    }
+}
+
+fn collect_group(group: &usvg::Group, items: &mut Vec<SvgItem>, warnings: &mut Vec<SvgWarning>) {
+    let opacity = group.opacity().get();
+    if opacity < 1.0 {
+        items.push(SvgItem::BeginGroup(opacity));
+    }
+
+    for node in group.children() {
+        match node {
+            usvg::Node::Group(child) => collect_group(child, items, warnings),
+            usvg::Node::Path(path) => {
+                if !path.is_visible() {
+                    continue;
+                }
+                let transform = path.abs_transform();
+                let outline = convert_path(path.data(), transform);
+
+                if let Some(fill) = path.fill() {
+                    let rule = match fill.rule() {
+                        usvg::FillRule::NonZero => FillRule::NonZero,
+                        usvg::FillRule::EvenOdd => FillRule::EvenOdd,
+                    };
+                    items.push(SvgItem::Shape(SvgShape {
+                        path: outline.clone(),
+                        paint: SvgPaint::Fill(rule),
+                        color: paint_color(fill.paint(), fill.opacity().get(), warnings),
+                    }));
+                }
+
+                if let Some(stroke) = path.stroke() {
+                    let scale = (transform.sx * transform.sy - transform.kx * transform.ky)
+                        .abs()
+                        .sqrt();
+                    let join = match stroke.linejoin() {
+                        usvg::LineJoin::Round => LineJoin::Round,
+                        usvg::LineJoin::Bevel => LineJoin::Bevel,
+                        _ => LineJoin::Miter,
+                    };
+                    let cap = match stroke.linecap() {
+                        usvg::LineCap::Butt => LineCap::Butt,
+                        usvg::LineCap::Round => LineCap::Round,
+                        usvg::LineCap::Square => LineCap::Square,
+                    };
+                    let dashes: Vec<f32> = stroke
+                        .dasharray()
+                        .unwrap_or(&[])
+                        .iter()
+                        .map(|d| d * scale)
+                        .collect();
+                    let style = StrokeStyle::new(stroke.width().get() * scale)
+                        .join(join)
+                        .cap(cap)
+                        .miter_limit(stroke.miterlimit().get())
+                        .dashes(&dashes, stroke.dashoffset() * scale);
+                    items.push(SvgItem::Shape(SvgShape {
+                        path: outline,
+                        paint: SvgPaint::Stroke(style),
+                        color: paint_color(stroke.paint(), stroke.opacity().get(), warnings),
+                    }));
+                }
+            }
+            usvg::Node::Text(text) => collect_group(text.flattened(), items, warnings),
+            usvg::Node::Image(_) => warn(warnings, SvgWarning::Image),
+        }
+    }
+
+    if opacity < 1.0 {
+        items.push(SvgItem::EndGroup);
+    }
+}
+
+fn convert_path(data: &usvg::tiny_skia_path::Path, transform: Transform) -> Path {
+    let map = |mut p: Point| {
+        transform.map_point(&mut p);
+        [p.x, p.y]
+    };
+
+    let mut path = Path::new();
+    for segment in data.segments() {
+        match segment {
+            PathSegment::MoveTo(p) => {
+                let [x, y] = map(p);
+                path.move_to(x, y);
+            }
+            PathSegment::LineTo(p) => {
+                let [x, y] = map(p);
+                path.line_to(x, y);
+            }
+            PathSegment::QuadTo(c, p) => {
+                let ([cx, cy], [x, y]) = (map(c), map(p));
+                path.quad_to(cx, cy, x, y);
+            }
+            PathSegment::CubicTo(c1, c2, p) => {
+                let ([c1x, c1y], [c2x, c2y], [x, y]) = (map(c1), map(c2), map(p));
+                path.cubic_to(c1x, c1y, c2x, c2y, x, y);
+            }
+            PathSegment::Close => {
+                path.close();
+            }
+        }
+    }
+    path
+}
+
+fn warn(warnings: &mut Vec<SvgWarning>, warning: SvgWarning) {
+    if !warnings.contains(&warning) {
+        warnings.push(warning);
+    }
+}
+
+/// gradients and patterns aren't supported by the shape pipeline yet, they
+/// fall back to the average of their stop colors and black
+fn paint_color(paint: &usvg::Paint, opacity: f32, warnings: &mut Vec<SvgWarning>) -> [f32; 4] {
+    let stops = match paint {
+        usvg::Paint::Color(c) => {
+            return [
+                c.red as f32 / 255.0,
+                c.green as f32 / 255.0,
+                c.blue as f32 / 255.0,
+                opacity,
+            ];
+        }
+        usvg::Paint::LinearGradient(g) => g.stops(),
+        usvg::Paint::RadialGradient(g) => g.stops(),
+        usvg::Paint::Pattern(_) => {
+            warn(warnings, SvgWarning::Pattern);
+            return [0.0, 0.0, 0.0, opacity];
+        }
+    };
+    warn(warnings, SvgWarning::Gradient);
+
+    if stops.is_empty() {
+        return [0.0, 0.0, 0.0, 0.0];
+    }
+    let mut sum = [0.0; 4];
+    for stop in stops {
+        let c = stop.color();
+        sum[0] += c.red as f32 / 255.0;
+        sum[1] += c.green as f32 / 255.0;
+        sum[2] += c.blue as f32 / 255.0;
+        sum[3] += stop.opacity().get();
+    }
+    let n = stops.len() as f32;
+    [sum[0] / n, sum[1] / n, sum[2] / n, sum[3] / n * opacity]
+}
+
+/// the system fonts for svg text, loaded once on first use. generic
+/// families the system doesn't have fall back to any installed font
+fn system_fonts() -> Arc<Database> {
+    static FONTS: OnceLock<Arc<Database>> = OnceLock::new();
+    FONTS
+        .get_or_init(|| {
+            let mut fonts = Database::new();
+            fonts.load_system_fonts();
+            let installed = |fonts: &Database, name: &str| {
+                fonts.faces().any(|face| face.families.iter().any(|(family, _)| family == name))
+            };
+            let fallback = fonts.faces().next().and_then(|face| face.families.first());
+            if let Some((fallback, _)) = fallback.cloned() {
+                for family in [
+                    Family::Serif,
+                    Family::SansSerif,
+                    Family::Monospace,
+                    Family::Cursive,
+                    Family::Fantasy,
+                ] {
+                    if installed(&fonts, fonts.family_name(&family)) {
+                        continue;
+                    }
+                    match family {
+                        Family::Serif => fonts.set_serif_family(&fallback),
+                        Family::SansSerif => fonts.set_sans_serif_family(&fallback),
+                        Family::Monospace => fonts.set_monospace_family(&fallback),
+                        Family::Cursive => fonts.set_cursive_family(&fallback),
+                        _ => fonts.set_fantasy_family(&fallback),
+                    }
+                }
+            }
+            Arc::new(fonts)
+        })
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(body: &str) -> Svg {
+        let data = format!(r#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="10">{body}</svg>"#);
+        Svg::from_bytes(data.as_bytes()).unwrap()
+    }
+
+    fn shapes(items: impl IntoIterator<Item = SvgItem>) -> Vec<SvgShape> {
+        items
+            .into_iter()
+            .filter_map(|item| match item {
+                SvgItem::Shape(shape) => Some(shape),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// the smallest box around every point of the shape
+    fn bounds(shape: &SvgShape) -> [f32; 4] {
+        let points: Vec<_> = shape.path.flatten(0.01).concat();
+        let fold = |f: fn(f32, f32) -> f32, i: usize, init: f32| points.iter().fold(init, |a, p| f(a, p[i]));
+        [
+            fold(f32::min, 0, f32::MAX),
+            fold(f32::min, 1, f32::MAX),
+            fold(f32::max, 0, f32::MIN),
+            fold(f32::max, 1, f32::MIN),
+        ]
+    }
+
+    #[test]
+    fn shapes_keep_their_colors_and_size() {
+        let svg = parse(r#"<rect x="2" y="3" width="4" height="5" fill="red" stroke="blue" stroke-width="2"/>"#);
+        let shapes = shapes(svg.items.clone());
+
+        assert_eq!((svg.width, svg.height), (20.0, 10.0));
+        assert!(svg.warnings.is_empty());
+        assert_eq!(shapes.len(), 2);
+        assert!(matches!(shapes[0].paint, SvgPaint::Fill(FillRule::NonZero)));
+        assert_eq!(shapes[0].color, [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(bounds(&shapes[0]), [2.0, 3.0, 6.0, 8.0]);
+        assert!(matches!(&shapes[1].paint, SvgPaint::Stroke(style) if style.width == 2.0));
+    }
+
+    #[test]
+    fn faded_groups_are_marked() {
+        let square = r#"<rect width="4" height="4"/>"#;
+        let svg = parse(&format!(r#"<g opacity="0.5">{square}{square}</g>"#));
+
+        assert!(matches!(svg.items[0], SvgItem::BeginGroup(opacity) if opacity == 0.5));
+        assert!(matches!(svg.items[3], SvgItem::EndGroup));
+        assert_eq!(shapes(svg.items.clone()).len(), 2);
+    }
+
+    #[test]
+    fn transforms_scale_strokes_and_dashes() {
+        let svg = parse(
+            r#"<line x2="4" fill="none" stroke="black" stroke-dasharray="1 2" transform="scale(2)"/>"#,
+        );
+        let shapes = shapes(svg.items.clone());
+
+        let SvgPaint::Stroke(style) = &shapes[0].paint else {
+            panic!("expected a stroke");
+        };
+        assert_eq!(style.width, 2.0);
+        assert_eq!(style.dashes, [2.0, 4.0]);
+        assert_eq!(bounds(&shapes[0]), [0.0, 0.0, 8.0, 0.0]);
+    }
+
+    #[test]
+    fn placing_scales_into_the_box() {
+        let svg = parse(r#"<rect width="20" height="10" stroke="black" stroke-width="1"/>"#);
+        let placed = shapes(svg.placed(100.0, 50.0, 40.0, 40.0, None));
+
+        assert_eq!(bounds(&placed[0]), [100.0, 50.0, 140.0, 90.0]);
+        let SvgPaint::Stroke(style) = &placed[1].paint else {
+            panic!("expected a stroke");
+        };
+        // stretched 2x across and 4x down, strokes scale by the average
+        assert!((style.width - 8f32.sqrt()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn tints_replace_colors_but_keep_alpha() {
+        let svg = parse(r#"<rect width="4" height="4" fill="red" fill-opacity="0.5"/>"#);
+        let placed = shapes(svg.placed(0.0, 0.0, 20.0, 10.0, Some([0.0, 1.0, 0.0, 0.8])));
+
+        assert_eq!(placed[0].color, [0.0, 1.0, 0.0, 0.4]);
+    }
+
+    #[test]
+    fn unsupported_paint_is_reported() {
+        let svg = parse(
+            r##"<defs>
+                <linearGradient id="fade"><stop stop-color="#000"/><stop stop-color="#fff"/></linearGradient>
+                <pattern id="dots" width="2" height="2"><rect width="1" height="1"/></pattern>
+            </defs>
+            <rect width="4" height="4" fill="url(#fade)"/>
+            <rect width="4" height="4" fill="url(#dots)"/>"##,
+        );
+        let shapes = shapes(svg.items.clone());
+
+        assert_eq!(svg.warnings, [SvgWarning::Gradient, SvgWarning::Pattern]);
+        assert_eq!(shapes[0].color, [0.5, 0.5, 0.5, 1.0]);
+        assert_eq!(shapes[1].color, [0.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn images_are_reported() {
+        // a 1x1 png
+        let pixel = concat!(
+            "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAQAAAC1HAwCAAAAC0lEQVR42mNkYAAAAAYAAjCB0C8AAAAASUVORK5C",
+            "YII=",
+        );
+        let svg = parse(&format!(r#"<image width="4" height="4" href="data:image/png;base64,{pixel}"/>"#));
+
+        assert_eq!(svg.warnings, [SvgWarning::Image]);
+        assert!(svg.items.is_empty());
+    }
+
+    #[test]
+    fn text_is_drawn_or_reported() {
+        let svg = parse(r#"<text x="2" y="8" font-family="sans-serif" font-size="8">Hi</text>"#);
+        let shapes = shapes(svg.items.clone());
+
+        // machines without fonts can't draw it, but have to say so
+        if svg.warnings.contains(&SvgWarning::Text) {
+            assert!(shapes.is_empty());
+        } else {
+            assert!(svg.warnings.is_empty());
+            assert!(!shapes.is_empty());
+            let [x0, _, _, y1] = bounds(&shapes[0]);
+            assert!(x0 >= 2.0 && y1 <= 8.5, "{:?}", bounds(&shapes[0]));
+        }
+    }
+
+    #[test]
+    fn broken_documents_are_errors() {
+        assert!(Svg::from_bytes(b"<svg").is_err());
+        assert!(Svg::from_bytes(b"not svg at all").is_err());
+    }
+}
@@ -1,10 +1,18 @@
-use crate::Color;
+use crate::render::shape_renderer::{intersect_rects, Layer};
+use crate::{Color, Transform};
 use glyphon::{
     Attrs, Buffer, Cache, Color as GlyphonColor, Family, FontSystem, Metrics, Resolution, Shaping,
     SwashCache, TextArea, TextAtlas, TextBounds, TextRenderer as GlyphonRenderer, Viewport,
 };
+use std::collections::{BTreeMap, HashMap};
 use wgpu;
 
+const MULTISAMPLE: wgpu::MultisampleState = wgpu::MultisampleState {
+    count: 4,
+    mask: !0,
+    alpha_to_coverage_enabled: false,
+};
+
 struct TextEntry {
     buffer: Buffer,
     x: f32,
@@ -14,49 +22,164 @@ struct TextEntry {
     family: String,
     size: f32,
     color: GlyphonColor,
+    clip: Option<[f32; 4]>,
+    group: Option<usize>,
 }
 
-pub struct TextRenderer {
+/// the gpu side of the renderer, missing when running without a gpu
+struct TextGpu {
     cache: Cache,
-    swash_cache: SwashCache,
-    pub atlas: TextAtlas,
+    atlas: TextAtlas,
     viewport: Viewport,
     renderer: GlyphonRenderer,
+    overlay_renderer: GlyphonRenderer,
+    /// one per faded group with text this frame, see `ShapeRenderer::push_group`
+    group_renderers: Vec<GlyphonRenderer>,
+    /// group index to its renderer in `group_renderers`
+    group_slots: HashMap<usize, usize>,
+}
+
+pub struct TextRenderer {
+    gpu: Option<TextGpu>,
+    swash_cache: SwashCache,
     entries: Vec<TextEntry>,
     active: usize,
     screen_width: f32,
     screen_height: f32,
     scale_factor: f64,
+    transform: Transform,
+    transform_stack: Vec<Transform>,
+    clip: Option<[f32; 4]>,
+    clip_stack: Vec<Option<[f32; 4]>>,
+    opacity: f32,
+    opacity_stack: Vec<f32>,
+    /// first entry drawn on the overlay layer
+    overlay_start: Option<usize>,
+    group: Option<usize>,
+    group_stack: Vec<Option<usize>>,
 }
 
 impl TextRenderer {
     pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, format: wgpu::TextureFormat) -> Self {
         let cache = Cache::new(device);
-        let swash_cache = SwashCache::new();
         let mut atlas = TextAtlas::new(device, queue, &cache, format);
-        let renderer = GlyphonRenderer::new(
-            &mut atlas,
-            device,
-            wgpu::MultisampleState {
-                count: 4,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            None,
-        );
+        let renderer = GlyphonRenderer::new(&mut atlas, device, MULTISAMPLE, None);
+        let overlay_renderer = GlyphonRenderer::new(&mut atlas, device, MULTISAMPLE, None);
         let viewport = Viewport::new(device, &cache);
 
-        Self {
+        Self::build(Some(TextGpu {
             cache,
-            swash_cache,
             atlas,
             viewport,
             renderer,
+            overlay_renderer,
+            group_renderers: Vec::new(),
+            group_slots: HashMap::new(),
+        }))
+    }
+
+    /// a renderer that lays out text but never draws, for tests and tools
+    /// that run without a gpu
+    pub fn without_gpu() -> Self {
+        Self::build(None)
+    }
+
+    fn build(gpu: Option<TextGpu>) -> Self {
+        Self {
+            gpu,
+            swash_cache: SwashCache::new(),
             entries: Vec::new(),
             active: 0,
             screen_width: 800.0,
             screen_height: 600.0,
             scale_factor: 1.0,
+            transform: Transform::IDENTITY,
+            transform_stack: Vec::new(),
+            clip: None,
+            clip_stack: Vec::new(),
+            opacity: 1.0,
+            opacity_stack: Vec::new(),
+            overlay_start: None,
+            group: None,
+            group_stack: Vec::new(),
+        }
+    }
+
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    /// the glyph atlas, `None` for a renderer without a gpu. this used to be
+    /// the public `atlas` field
+    pub fn atlas(&self) -> Option<&TextAtlas> {
+        self.gpu.as_ref().map(|gpu| &gpu.atlas)
+    }
+
+    pub fn atlas_mut(&mut self) -> Option<&mut TextAtlas> {
+        self.gpu.as_mut().map(|gpu| &mut gpu.atlas)
+    }
+
+    /// text only follows the translation and overall scale of a transform,
+    /// glyphs are never rotated or skewed
+    pub fn push_transform(&mut self, transform: Transform) {
+        self.transform_stack.push(self.transform);
+        self.transform = transform.then(self.transform);
+    }
+
+    pub fn pop_transform(&mut self) {
+        if let Some(t) = self.transform_stack.pop() {
+            self.transform = t;
+        }
+    }
+
+    pub fn transform(&self) -> Transform {
+        self.transform
+    }
+
+    /// screen space clip rect, same rules as `ShapeRenderer::push_clip`
+    pub fn push_clip(&mut self, x: f32, y: f32, w: f32, h: f32) {
+        self.clip_stack.push(self.clip);
+        self.clip = Some(match self.clip {
+            Some(current) => intersect_rects(current, [x, y, w, h]),
+            None => [x, y, w.max(0.0), h.max(0.0)],
+        });
+    }
+
+    pub fn pop_clip(&mut self) {
+        if let Some(clip) = self.clip_stack.pop() {
+            self.clip = clip;
+        }
+    }
+
+    /// same rules as `ShapeRenderer::push_opacity`
+    pub fn push_opacity(&mut self, opacity: f32) {
+        self.opacity_stack.push(self.opacity);
+        self.opacity *= opacity.clamp(0.0, 1.0);
+    }
+
+    pub fn pop_opacity(&mut self) {
+        if let Some(opacity) = self.opacity_stack.pop() {
+            self.opacity = opacity;
+        }
+    }
+
+    pub fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    /// text drawn until the matching `pop_group` goes into `group`, the
+    /// index `ShapeRenderer::push_group` returned. `None` keeps drawing
+    /// where it did
+    pub fn push_group(&mut self, group: Option<usize>) {
+        self.group_stack.push(self.group);
+        if group.is_some() {
+            self.group = group;
+        }
+    }
+
+    pub fn pop_group(&mut self) {
+        if let Some(group) = self.group_stack.pop() {
+            self.group = group;
         }
     }
 
@@ -66,6 +189,7 @@ impl TextRenderer {
         self.scale_factor = scale_factor;
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn draw(
         &mut self,
         font_system: &mut FontSystem,
@@ -76,14 +200,20 @@ impl TextRenderer {
         y: f32,
         color: Color,
     ) {
-        let glyphon_color = GlyphonColor::rgb(
-            (color.r * 255.0) as u8,
-            (color.g * 255.0) as u8,
-            (color.b * 255.0) as u8,
+        let channel = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let glyphon_color = GlyphonColor::rgba(
+            channel(color.r),
+            channel(color.g),
+            channel(color.b),
+            channel(color.a * self.opacity),
         );
 
-        let scale = self.scale_factor as f32;
+        let (x, y) = self.transform.apply(x, y);
+        let user_scale = self.transform.uniform_scale().max(f32::EPSILON);
+        let scale = self.scale_factor as f32 * user_scale;
         let line_height = size * 1.4;
+        let max_width = (self.screen_width - x) / user_scale;
+        let max_height = (self.screen_height - y) / user_scale;
         let idx = self.active;
         self.active += 1;
 
@@ -93,6 +223,8 @@ impl TextRenderer {
             entry.y = y;
             entry.scale = scale;
             entry.color = glyphon_color;
+            entry.clip = self.clip;
+            entry.group = self.group;
 
             let content_changed =
                 entry.text != text || entry.family != family || entry.size != size;
@@ -103,11 +235,9 @@ impl TextRenderer {
                 entry
                     .buffer
                     .set_metrics(font_system, Metrics::new(size, line_height));
-                entry.buffer.set_size(
-                    font_system,
-                    Some(self.screen_width - x),
-                    Some(self.screen_height - y),
-                );
+                entry
+                    .buffer
+                    .set_size(font_system, Some(max_width), Some(max_height));
                 entry.buffer.set_text(
                     font_system,
                     text,
@@ -118,11 +248,7 @@ impl TextRenderer {
             }
         } else {
             let mut buffer = Buffer::new(font_system, Metrics::new(size, line_height));
-            buffer.set_size(
-                font_system,
-                Some(self.screen_width - x),
-                Some(self.screen_height - y),
-            );
+            buffer.set_size(font_system, Some(max_width), Some(max_height));
             buffer.set_text(
                 font_system,
                 text,
@@ -139,24 +265,54 @@ impl TextRenderer {
                 family,
                 size,
                 color: glyphon_color,
+                clip: self.clip,
+                group: self.group,
             });
         }
     }
 
-    pub fn render<'pass>(
-        &'pass mut self,
+    /// text drawn from now until `clear` goes on the overlay layer
+    pub fn begin_overlay(&mut self) {
+        if self.overlay_start.is_none() {
+            self.overlay_start = Some(self.active);
+        }
+    }
+
+    /// prepares and draws both layers, text in faded groups is left out
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        &mut self,
         font_system: &mut FontSystem,
         screen_width: f32,
         screen_height: f32,
         scale_factor: f64,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
-        pass: &mut wgpu::RenderPass<'pass>,
+        pass: &mut wgpu::RenderPass<'_>,
     ) {
+        self.prepare(font_system, screen_width, screen_height, scale_factor, device, queue);
+        self.render_layer(pass, Layer::Base);
+        self.render_layer(pass, Layer::Overlay);
+    }
+
+    /// lays out this frame's text for both layers and every faded group,
+    /// call once before drawing
+    pub fn prepare(
+        &mut self,
+        font_system: &mut FontSystem,
+        screen_width: f32,
+        screen_height: f32,
+        scale_factor: f64,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        let Some(gpu) = self.gpu.as_mut() else {
+            return;
+        };
         let physical_width = (screen_width * scale_factor as f32) as u32;
         let physical_height = (screen_height * scale_factor as f32) as u32;
 
-        self.viewport.update(
+        gpu.viewport.update(
             queue,
             Resolution {
                 width: physical_width,
@@ -164,50 +320,119 @@ impl TextRenderer {
             },
         );
 
-        if self.active == 0 {
-            return;
+        // one pass over the entries, sorting them into layers and groups
+        let split = self.overlay_start.unwrap_or(self.active).min(self.active);
+        let mut base = Vec::new();
+        let mut overlay = Vec::new();
+        let mut groups: BTreeMap<usize, Vec<&TextEntry>> = BTreeMap::new();
+        for (i, entry) in self.entries[..self.active].iter().enumerate() {
+            match entry.group {
+                Some(group) => groups.entry(group).or_default().push(entry),
+                None if i < split => base.push(entry),
+                None => overlay.push(entry),
+            }
         }
 
-        let text_areas: Vec<TextArea> = self.entries[..self.active]
-            .iter()
-            .map(|entry| TextArea {
-                buffer: &entry.buffer,
-                left: entry.x * entry.scale,
-                top: entry.y * entry.scale,
-                scale: entry.scale,
-                bounds: TextBounds {
-                    left: 0,
-                    top: 0,
-                    right: physical_width as i32,
-                    bottom: physical_height as i32,
-                },
-                default_color: entry.color,
-                custom_glyphs: &[],
-            })
-            .collect();
-
-        self.renderer
-            .prepare(
-                device,
-                queue,
-                font_system,
-                &mut self.atlas,
-                &self.viewport,
-                text_areas,
-                &mut self.swash_cache,
-            )
-            .unwrap();
+        // the pool only keeps as many renderers as there are groups with text
+        gpu.group_renderers.truncate(groups.len());
+        while gpu.group_renderers.len() < groups.len() {
+            let renderer = GlyphonRenderer::new(&mut gpu.atlas, device, MULTISAMPLE, None);
+            gpu.group_renderers.push(renderer);
+        }
+        gpu.group_slots.clear();
+        gpu.group_slots.extend(groups.keys().enumerate().map(|(slot, &group)| (group, slot)));
+
+        let mut layers: Vec<(&mut GlyphonRenderer, Vec<&TextEntry>)> =
+            vec![(&mut gpu.renderer, base), (&mut gpu.overlay_renderer, overlay)];
+        layers.extend(gpu.group_renderers.iter_mut().zip(groups.into_values()));
+        for (renderer, entries) in layers {
+            let text_areas: Vec<TextArea> = entries
+                .into_iter()
+                .map(|entry| TextArea {
+                    buffer: &entry.buffer,
+                    left: entry.x * scale_factor as f32,
+                    top: entry.y * scale_factor as f32,
+                    scale: entry.scale,
+                    bounds: match entry.clip {
+                        Some([x, y, w, h]) => {
+                            let scale = scale_factor as f32;
+                            TextBounds {
+                                left: (x * scale).floor() as i32,
+                                top: (y * scale).floor() as i32,
+                                right: ((x + w) * scale).ceil() as i32,
+                                bottom: ((y + h) * scale).ceil() as i32,
+                            }
+                        }
+                        None => TextBounds {
+                            left: 0,
+                            top: 0,
+                            right: physical_width as i32,
+                            bottom: physical_height as i32,
+                        },
+                    },
+                    default_color: entry.color,
+                    custom_glyphs: &[],
+                })
+                .collect();
 
-        self.renderer
-            .render(&self.atlas, &self.viewport, pass)
-            .unwrap();
+            renderer
+                .prepare(
+                    device,
+                    queue,
+                    font_system,
+                    &mut gpu.atlas,
+                    &gpu.viewport,
+                    text_areas,
+                    &mut self.swash_cache,
+                )
+                .unwrap();
+        }
+    }
+
+    pub fn render_layer(&self, pass: &mut wgpu::RenderPass<'_>, layer: Layer) {
+        let Some(gpu) = self.gpu.as_ref() else {
+            return;
+        };
+        let renderer = match layer {
+            Layer::Base => &gpu.renderer,
+            Layer::Overlay => &gpu.overlay_renderer,
+        };
+        renderer.render(&gpu.atlas, &gpu.viewport, pass).unwrap();
+    }
+
+    /// draws the text of a faded group into its layer
+    pub fn render_group(&self, pass: &mut wgpu::RenderPass<'_>, group: usize) {
+        let Some(gpu) = self.gpu.as_ref() else {
+            return;
+        };
+        if let Some(renderer) = gpu.group_slots.get(&group).map(|&slot| &gpu.group_renderers[slot]) {
+            renderer.render(&gpu.atlas, &gpu.viewport, pass).unwrap();
+        }
     }
 
     pub fn trim_atlas(&mut self) {
-        self.atlas.trim();
+        if let Some(gpu) = self.gpu.as_mut() {
+            gpu.atlas.trim();
+        }
+    }
+
+    /// the strings drawn this frame with their logical positions, in draw order
+    pub fn drawn_text(&self) -> impl Iterator<Item = (&str, f32, f32)> {
+        self.entries[..self.active]
+            .iter()
+            .map(|entry| (entry.text.as_str(), entry.x, entry.y))
     }
 
     pub fn clear(&mut self) {
         self.active = 0;
+        self.transform = Transform::IDENTITY;
+        self.transform_stack.clear();
+        self.clip = None;
+        self.clip_stack.clear();
+        self.opacity = 1.0;
+        self.opacity_stack.clear();
+        self.overlay_start = None;
+        self.group = None;
+        self.group_stack.clear();
     }
 }
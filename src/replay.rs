@@ -0,0 +1,126 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path as FsPath;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::InputEvent;
+
+/// one input event and when it happened, in seconds since recording started
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    pub time: f64,
+    pub event: InputEvent,
+}
+
+/// a session of input, stored as json so bug reports can attach it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    /// logical window size and scale factor when recording started
+    pub width: f32,
+    pub height: f32,
+    pub scale_factor: f64,
+    pub events: Vec<RecordedEvent>,
+}
+
+impl Recording {
+    pub fn new(width: f32, height: f32, scale_factor: f64) -> Self {
+        Self {
+            width,
+            height,
+            scale_factor,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, time: f64, event: InputEvent) {
+        self.events.push(RecordedEvent { time, event });
+    }
+
+    /// seconds from the start to the last event
+    pub fn duration(&self) -> f64 {
+        self.events.last().map_or(0.0, |e| e.time)
+    }
+
+    pub fn save(&self, path: impl AsRef<FsPath>) -> io::Result<()> {
+        let file = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(file, self).map_err(io::Error::other)
+    }
+
+    pub fn load(path: impl AsRef<FsPath>) -> io::Result<Self> {
+        let file = BufReader::new(File::open(path)?);
+        serde_json::from_reader(file).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// timestamps events as they come in
+pub struct Recorder {
+    start: Instant,
+    pub recording: Recording,
+}
+
+impl Recorder {
+    pub fn new(width: f32, height: f32, scale_factor: f64) -> Self {
+        Self {
+            start: Instant::now(),
+            recording: Recording::new(width, height, scale_factor),
+        }
+    }
+
+    pub fn record(&mut self, event: &InputEvent) {
+        let time = self.start.elapsed().as_secs_f64();
+        self.recording.push(time, event.clone());
+    }
+}
+
+/// walks a recording either in real time or one event at a time
+pub struct Replay {
+    pub recording: Recording,
+    next: usize,
+}
+
+impl Replay {
+    pub fn new(recording: Recording) -> Self {
+        Self { recording, next: 0 }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.next >= self.recording.events.len()
+    }
+
+    /// index of the next event to replay
+    pub fn position(&self) -> usize {
+        self.next
+    }
+
+    pub fn peek(&self) -> Option<&RecordedEvent> {
+        self.recording.events.get(self.next)
+    }
+
+    /// the next event regardless of its timestamp, for stepping
+    pub fn step(&mut self) -> Option<&RecordedEvent> {
+        let event = self.recording.events.get(self.next)?;
+        self.next += 1;
+        Some(event)
+    }
+
+    /// the next event if it's due `elapsed` into the replay
+    pub fn next_due(&mut self, elapsed: Duration) -> Option<&RecordedEvent> {
+        let event = self.recording.events.get(self.next)?;
+        if event.time > elapsed.as_secs_f64() {
+            return None;
+        }
+        self.next += 1;
+        Some(event)
+    }
+
+    /// how long after the start the next event is due
+    pub fn next_time(&self) -> Option<Duration> {
+        self.peek().map(|e| Duration::from_secs_f64(e.time.max(0.0)))
+    }
+
+    pub fn rewind(&mut self) {
+        self.next = 0;
+    }
+}
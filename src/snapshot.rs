@@ -0,0 +1,380 @@
+use std::fmt;
+use std::io;
+use std::path::{Path as FsPath, PathBuf};
+
+use crate::RgbaImage;
+
+/// set to anything but "0" to overwrite references with the actual images
+pub const BLESS_ENV: &str = "BENTO_BLESS";
+
+/// the largest possible yiq delta between two colors
+const MAX_YIQ_DELTA: f32 = 35215.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SnapshotOptions {
+    /// perceptual color difference from 0 to 1 a pixel may have before it
+    /// counts as different
+    pub threshold: f32,
+    /// how many differing pixels are tolerated, for antialiasing noise
+    /// between adapters
+    pub max_differing_pixels: usize,
+}
+
+impl Default for SnapshotOptions {
+    fn default() -> Self {
+        Self {
+            threshold: 0.1,
+            max_differing_pixels: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageDiff {
+    pub differing_pixels: usize,
+    /// highest perceptual delta found, 0 to 1
+    pub max_delta: f32,
+    /// the expected image faded out with differing pixels in red
+    pub image: RgbaImage,
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(PathBuf, io::Error),
+    MissingReference {
+        reference: PathBuf,
+        actual: PathBuf,
+    },
+    SizeMismatch {
+        expected: (u32, u32),
+        actual: (u32, u32),
+        actual_path: PathBuf,
+    },
+    Mismatch {
+        differing_pixels: usize,
+        max_delta: f32,
+        actual_path: PathBuf,
+        diff_path: PathBuf,
+    },
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(path, e) => write!(f, "{}: {e}", path.display()),
+            SnapshotError::MissingReference { reference, actual } => write!(
+                f,
+                "no reference image at {}, wrote {} (run with {BLESS_ENV}=1 to accept it)",
+                reference.display(),
+                actual.display()
+            ),
+            SnapshotError::SizeMismatch {
+                expected,
+                actual,
+                actual_path,
+            } => write!(
+                f,
+                "expected a {}x{} image but got {}x{}, wrote {}",
+                expected.0,
+                expected.1,
+                actual.0,
+                actual.1,
+                actual_path.display()
+            ),
+            SnapshotError::Mismatch {
+                differing_pixels,
+                max_delta,
+                actual_path,
+                diff_path,
+            } => write!(
+                f,
+                "{differing_pixels} pixels differ (max delta {max_delta:.3}), wrote {} and {}",
+                actual_path.display(),
+                diff_path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+/// compares `actual` against the png at `reference`. on failure the actual
+/// image and a diff are written next to the reference as `<name>.actual.png`
+/// and `<name>.diff.png`. with `BENTO_BLESS` set the reference is replaced
+pub fn check_snapshot(
+    reference: impl AsRef<FsPath>,
+    actual: &RgbaImage,
+    options: &SnapshotOptions,
+) -> Result<(), SnapshotError> {
+    check(reference.as_ref(), actual, options, blessing())
+}
+
+fn check(
+    reference: &FsPath,
+    actual: &RgbaImage,
+    options: &SnapshotOptions,
+    bless: bool,
+) -> Result<(), SnapshotError> {
+    let actual_path = sibling(reference, "actual");
+    let diff_path = sibling(reference, "diff");
+
+    if bless {
+        if let Some(dir) = reference.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| SnapshotError::Io(dir.to_path_buf(), e))?;
+        }
+        save(actual, reference)?;
+        let _ = std::fs::remove_file(&actual_path);
+        let _ = std::fs::remove_file(&diff_path);
+        return Ok(());
+    }
+
+    if !reference.exists() {
+        save(actual, &actual_path)?;
+        return Err(SnapshotError::MissingReference {
+            reference: reference.to_path_buf(),
+            actual: actual_path,
+        });
+    }
+
+    let expected =
+        RgbaImage::load_png(reference).map_err(|e| SnapshotError::Io(reference.to_path_buf(), e))?;
+    if (expected.width, expected.height) != (actual.width, actual.height) {
+        save(actual, &actual_path)?;
+        return Err(SnapshotError::SizeMismatch {
+            expected: (expected.width, expected.height),
+            actual: (actual.width, actual.height),
+            actual_path,
+        });
+    }
+
+    let diff = diff_images(&expected, actual, options.threshold);
+    if diff.differing_pixels > options.max_differing_pixels {
+        save(actual, &actual_path)?;
+        save(&diff.image, &diff_path)?;
+        return Err(SnapshotError::Mismatch {
+            differing_pixels: diff.differing_pixels,
+            max_delta: diff.max_delta,
+            actual_path,
+            diff_path,
+        });
+    }
+
+    let _ = std::fs::remove_file(&actual_path);
+    let _ = std::fs::remove_file(&diff_path);
+    Ok(())
+}
+
+/// `check_snapshot` with default options, panicking on mismatch
+#[track_caller]
+pub fn assert_snapshot(reference: impl AsRef<FsPath>, actual: &RgbaImage) {
+    if let Err(e) = check_snapshot(reference, actual, &SnapshotOptions::default()) {
+        panic!("snapshot mismatch: {e}");
+    }
+}
+
+/// per-pixel perceptual diff of two same-sized images
+pub fn diff_images(expected: &RgbaImage, actual: &RgbaImage, threshold: f32) -> ImageDiff {
+    assert_eq!(
+        (expected.width, expected.height),
+        (actual.width, actual.height),
+        "can only diff images of the same size"
+    );
+
+    let mut image = RgbaImage::new(expected.width, expected.height);
+    let mut differing_pixels = 0;
+    let mut max_delta: f32 = 0.0;
+
+    for y in 0..expected.height {
+        for x in 0..expected.width {
+            let a = expected.pixel(x, y);
+            let b = actual.pixel(x, y);
+            let delta = color_delta(a, b) / MAX_YIQ_DELTA;
+            max_delta = max_delta.max(delta);
+
+            let out = if delta > threshold * threshold {
+                differing_pixels += 1;
+                [255, 0, 0, 255]
+            } else {
+                let gray = (luma(blend_white(a)) * 0.1 + 255.0 * 0.9) as u8;
+                [gray, gray, gray, 255]
+            };
+            image.set_pixel(x, y, out);
+        }
+    }
+
+    ImageDiff {
+        differing_pixels,
+        max_delta: max_delta.sqrt(),
+        image,
+    }
+}
+
+fn blessing() -> bool {
+    std::env::var(BLESS_ENV).is_ok_and(|v| !v.is_empty() && v != "0")
+}
+
+fn sibling(reference: &FsPath, suffix: &str) -> PathBuf {
+    let stem = reference
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    reference.with_file_name(format!("{stem}.{suffix}.png"))
+}
+
+fn save(image: &RgbaImage, path: &FsPath) -> Result<(), SnapshotError> {
+    image
+        .save_png(path)
+        .map_err(|e| SnapshotError::Io(path.to_path_buf(), e))
+}
+
+fn blend_white(p: [u8; 4]) -> [f32; 3] {
+    let a = p[3] as f32 / 255.0;
+    [
+        255.0 + (p[0] as f32 - 255.0) * a,
+        255.0 + (p[1] as f32 - 255.0) * a,
+        255.0 + (p[2] as f32 - 255.0) * a,
+    ]
+}
+
+fn luma(c: [f32; 3]) -> f32 {
+    c[0] * 0.298_895 + c[1] * 0.586_622 + c[2] * 0.114_482
+}
+
+fn chroma_i(c: [f32; 3]) -> f32 {
+    c[0] * 0.595_978 - c[1] * 0.274_176 - c[2] * 0.321_802
+}
+
+fn chroma_q(c: [f32; 3]) -> f32 {
+    c[0] * 0.211_470 - c[1] * 0.522_617 + c[2] * 0.311_147
+}
+
+/// squared distance in yiq space, weighted like pixelmatch
+fn color_delta(a: [u8; 4], b: [u8; 4]) -> f32 {
+    if a == b {
+        return 0.0;
+    }
+    let (a, b) = (blend_white(a), blend_white(b));
+    let y = luma(a) - luma(b);
+    let i = chroma_i(a) - chroma_i(b);
+    let q = chroma_q(a) - chroma_q(b);
+    0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filled(width: u32, height: u32, rgba: [u8; 4]) -> RgbaImage {
+        let mut image = RgbaImage::new(width, height);
+        for pixel in image.pixels.chunks_mut(4) {
+            pixel.copy_from_slice(&rgba);
+        }
+        image
+    }
+
+    /// a fresh directory per test so they can run in parallel
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("bento-snapshot-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn identical_images_match() {
+        let image = filled(4, 3, [10, 200, 30, 255]);
+        let diff = diff_images(&image, &image, 0.0);
+
+        assert_eq!(diff.differing_pixels, 0);
+        assert_eq!(diff.max_delta, 0.0);
+        assert_eq!((diff.image.width, diff.image.height), (4, 3));
+    }
+
+    #[test]
+    fn small_changes_stay_under_the_threshold() {
+        let expected = filled(4, 4, [100, 100, 100, 255]);
+        let mut actual = expected.clone();
+        actual.set_pixel(1, 1, [102, 101, 100, 255]);
+
+        let loose = diff_images(&expected, &actual, 0.1);
+        assert_eq!(loose.differing_pixels, 0);
+        assert!(loose.max_delta > 0.0);
+
+        let strict = diff_images(&expected, &actual, 0.001);
+        assert_eq!(strict.differing_pixels, 1);
+        assert_eq!(strict.image.pixel(1, 1), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn a_few_differing_pixels_can_be_tolerated() {
+        let dir = temp_dir("tolerance");
+        let reference = dir.join("button.png");
+        filled(8, 8, [255, 255, 255, 255]).save_png(&reference).unwrap();
+        let mut actual = filled(8, 8, [255, 255, 255, 255]);
+        actual.set_pixel(0, 0, [0, 0, 0, 255]);
+        actual.set_pixel(7, 7, [0, 0, 0, 255]);
+
+        let strict = SnapshotOptions::default();
+        match check(&reference, &actual, &strict, false) {
+            Err(SnapshotError::Mismatch { differing_pixels, diff_path, .. }) => {
+                assert_eq!(differing_pixels, 2);
+                assert!(diff_path.exists());
+            }
+            other => panic!("expected a mismatch, got {other:?}"),
+        }
+
+        let tolerant = SnapshotOptions {
+            max_differing_pixels: 2,
+            ..strict
+        };
+        check(&reference, &actual, &tolerant, false).unwrap();
+        // a passing check cleans up after the failed one
+        assert!(!dir.join("button.actual.png").exists());
+        assert!(!dir.join("button.diff.png").exists());
+    }
+
+    #[test]
+    fn size_mismatch_writes_the_actual_image() {
+        let dir = temp_dir("size");
+        let reference = dir.join("panel.png");
+        filled(4, 4, [0, 0, 0, 255]).save_png(&reference).unwrap();
+
+        let actual = filled(5, 4, [0, 0, 0, 255]);
+        match check(&reference, &actual, &SnapshotOptions::default(), false) {
+            Err(SnapshotError::SizeMismatch { expected, actual: size, actual_path }) => {
+                assert_eq!(expected, (4, 4));
+                assert_eq!(size, (5, 4));
+                assert_eq!(RgbaImage::load_png(actual_path).unwrap(), actual);
+            }
+            other => panic!("expected a size mismatch, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn missing_reference_is_an_error() {
+        let dir = temp_dir("missing");
+        let reference = dir.join("new.png");
+        let actual = filled(2, 2, [1, 2, 3, 255]);
+
+        let result = check(&reference, &actual, &SnapshotOptions::default(), false);
+        assert!(matches!(result, Err(SnapshotError::MissingReference { .. })));
+        assert!(!reference.exists());
+        assert!(dir.join("new.actual.png").exists());
+    }
+
+    #[test]
+    fn blessing_writes_the_reference() {
+        let dir = temp_dir("bless");
+        let reference = dir.join("nested").join("icon.png");
+        let old = filled(2, 2, [0, 0, 0, 255]);
+        let new = filled(3, 3, [9, 8, 7, 255]);
+        std::fs::create_dir_all(reference.parent().unwrap()).unwrap();
+        old.save_png(&reference).unwrap();
+        assert!(check(&reference, &new, &SnapshotOptions::default(), false).is_err());
+
+        check(&reference, &new, &SnapshotOptions::default(), true).unwrap();
+        assert_eq!(RgbaImage::load_png(&reference).unwrap(), new);
+        assert!(!dir.join("nested").join("icon.actual.png").exists());
+        check(&reference, &new, &SnapshotOptions::default(), false).unwrap();
+    }
+}
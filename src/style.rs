@@ -0,0 +1,514 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use winit::window::CursorIcon;
+
+use crate::ui::Ui;
+use crate::{Color, FontSpec};
+
+/// a set of visual properties, `None` leaves a property alone. used for
+/// named classes with `Ui::define_style` and for inline overrides with
+/// `Ui::set_style`. properties an element doesn't have are ignored
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Style {
+    /// fill of rects, buttons and canvases
+    pub background: Option<Color>,
+    pub background_hover: Option<Color>,
+    pub background_pressed: Option<Color>,
+    pub background_disabled: Option<Color>,
+    /// color of texts and button labels
+    pub text_color: Option<Color>,
+    pub text_color_hover: Option<Color>,
+    pub text_color_pressed: Option<Color>,
+    pub text_color_disabled: Option<Color>,
+    pub border_color: Option<Color>,
+    pub border_color_hover: Option<Color>,
+    pub border_color_pressed: Option<Color>,
+    pub border_color_disabled: Option<Color>,
+    pub border_color_focus: Option<Color>,
+    pub border_width: Option<f32>,
+    pub border_width_hover: Option<f32>,
+    pub border_width_pressed: Option<f32>,
+    pub border_width_focus: Option<f32>,
+    pub radius: Option<f32>,
+    pub padding: Option<f32>,
+    pub font: Option<FontSpec>,
+    pub opacity: Option<f32>,
+    pub transition: Option<Duration>,
+    pub cursor: Option<CursorIcon>,
+}
+
+/// calls `$apply!` with the name of every property
+macro_rules! fields {
+    ($apply:ident) => {
+        $apply!(
+            background,
+            background_hover,
+            background_pressed,
+            background_disabled,
+            text_color,
+            text_color_hover,
+            text_color_pressed,
+            text_color_disabled,
+            border_color,
+            border_color_hover,
+            border_color_pressed,
+            border_color_disabled,
+            border_color_focus,
+            border_width,
+            border_width_hover,
+            border_width_pressed,
+            border_width_focus,
+            radius,
+            padding,
+            font,
+            opacity,
+            transition,
+            cursor
+        )
+    };
+}
+
+impl Style {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// also sets the hover, pressed and disabled shades buttons derive
+    /// from it, set those after to pick them by hand
+    pub fn background(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self.background_hover = Some(color.darken(0.8));
+        self.background_pressed = Some(color.darken(0.65));
+        self.background_disabled = Some(color.faded(0.45));
+        self
+    }
+
+    pub fn background_hover(mut self, color: Color) -> Self {
+        self.background_hover = Some(color);
+        self
+    }
+
+    pub fn background_pressed(mut self, color: Color) -> Self {
+        self.background_pressed = Some(color);
+        self
+    }
+
+    pub fn background_disabled(mut self, color: Color) -> Self {
+        self.background_disabled = Some(color);
+        self
+    }
+
+    /// derives the other states like `background`
+    pub fn text_color(mut self, color: Color) -> Self {
+        self.text_color = Some(color);
+        self.text_color_hover = Some(color.darken(0.8));
+        self.text_color_pressed = Some(color.darken(0.65));
+        self.text_color_disabled = Some(color.faded(0.45));
+        self
+    }
+
+    pub fn text_color_hover(mut self, color: Color) -> Self {
+        self.text_color_hover = Some(color);
+        self
+    }
+
+    pub fn text_color_pressed(mut self, color: Color) -> Self {
+        self.text_color_pressed = Some(color);
+        self
+    }
+
+    pub fn text_color_disabled(mut self, color: Color) -> Self {
+        self.text_color_disabled = Some(color);
+        self
+    }
+
+    /// derives the other states like `background`, focus keeps the color
+    pub fn border(mut self, color: Color, width: f32) -> Self {
+        self.border_color = Some(color);
+        self.border_color_hover = Some(color.darken(0.8));
+        self.border_color_pressed = Some(color.darken(0.65));
+        self.border_color_disabled = Some(color.faded(0.45));
+        self.border_color_focus = Some(color);
+        self.border_width = Some(width);
+        self.border_width_hover = Some(width);
+        self.border_width_pressed = Some(width);
+        self.border_width_focus = Some(width);
+        self
+    }
+
+    pub fn border_color_hover(mut self, color: Color) -> Self {
+        self.border_color_hover = Some(color);
+        self
+    }
+
+    pub fn border_color_pressed(mut self, color: Color) -> Self {
+        self.border_color_pressed = Some(color);
+        self
+    }
+
+    pub fn border_color_disabled(mut self, color: Color) -> Self {
+        self.border_color_disabled = Some(color);
+        self
+    }
+
+    pub fn border_color_focus(mut self, color: Color) -> Self {
+        self.border_color_focus = Some(color);
+        self
+    }
+
+    pub fn border_width_hover(mut self, width: f32) -> Self {
+        self.border_width_hover = Some(width);
+        self
+    }
+
+    pub fn border_width_pressed(mut self, width: f32) -> Self {
+        self.border_width_pressed = Some(width);
+        self
+    }
+
+    pub fn border_width_focus(mut self, width: f32) -> Self {
+        self.border_width_focus = Some(width);
+        self
+    }
+
+    pub fn radius(mut self, radius: f32) -> Self {
+        self.radius = Some(radius);
+        self
+    }
+
+    pub fn padding(mut self, padding: f32) -> Self {
+        self.padding = Some(padding);
+        self
+    }
+
+    pub fn font(mut self, family: &str, size: f32) -> Self {
+        self.font = Some(FontSpec::new(family, size));
+        self
+    }
+
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.opacity = Some(opacity);
+        self
+    }
+
+    pub fn transition(mut self, duration: Duration) -> Self {
+        self.transition = Some(duration);
+        self
+    }
+
+    pub fn cursor(mut self, cursor: CursorIcon) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
+
+    /// properties set in `other` win
+    pub fn merge(&mut self, other: &Style) {
+        macro_rules! take {
+            ($($field:ident),*) => {
+                $(if other.$field.is_some() {
+                    self.$field = other.$field.clone();
+                })*
+            };
+        }
+        fields!(take);
+    }
+
+    /// takes every property that differs between `before` and `after`, for
+    /// picking up what changed on an element behind the style's back
+    fn keep_changes(&mut self, before: &Style, after: &Style) {
+        macro_rules! keep {
+            ($($field:ident),*) => {
+                $(if before.$field != after.$field {
+                    self.$field = after.$field.clone();
+                })*
+            };
+        }
+        fields!(keep);
+    }
+}
+
+/// the classes and inline style of one element, and how it looked before
+/// either was applied so they can be taken off again
+#[derive(Clone, Debug)]
+pub(crate) struct Styling {
+    /// how the element looks without styles, kept up to date with changes
+    /// made to its fields directly
+    pub base: Style,
+    pub classes: Vec<String>,
+    pub inline: Style,
+    /// the element's properties right after the style was last applied
+    pub applied: Style,
+}
+
+/// named classes and the elements styled with them, on `Ui`
+#[derive(Default)]
+pub(crate) struct Styles {
+    pub classes: HashMap<String, Style>,
+    pub elements: HashMap<String, Styling>,
+}
+
+impl Ui {
+    /// defines or replaces the class `name`, restyling elements that use it
+    pub fn define_style(&mut self, name: &str, style: Style) {
+        self.styles.classes.insert(name.to_string(), style);
+        let ids: Vec<_> = self
+            .styles
+            .elements
+            .iter()
+            .filter(|(_, s)| s.classes.iter().any(|c| c == name))
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in ids {
+            self.apply_style(&id);
+        }
+    }
+
+    pub fn style(&self, name: &str) -> Option<&Style> {
+        self.styles.classes.get(name)
+    }
+
+    /// adds a class to the element, later classes win over earlier ones.
+    /// a class may be used before it's defined
+    pub fn add_class(&mut self, id: &str, class: &str) {
+        let Some(styling) = self.styling(id) else {
+            return;
+        };
+        if !styling.classes.iter().any(|c| c == class) {
+            styling.classes.push(class.to_string());
+        }
+        self.apply_style(id);
+    }
+
+    pub fn remove_class(&mut self, id: &str, class: &str) {
+        if let Some(styling) = self.styles.elements.get_mut(id) {
+            styling.classes.retain(|c| c != class);
+            self.apply_style(id);
+        }
+    }
+
+    pub fn has_class(&self, id: &str, class: &str) -> bool {
+        self.styles
+            .elements
+            .get(id)
+            .is_some_and(|s| s.classes.iter().any(|c| c == class))
+    }
+
+    /// the element's classes in the order they apply
+    pub fn classes(&self, id: &str) -> &[String] {
+        self.styles.elements.get(id).map_or(&[], |s| &s.classes)
+    }
+
+    /// inline overrides for the element, winning over its classes. replaces
+    /// the previous inline style, `Style::default()` clears it
+    pub fn set_style(&mut self, id: &str, style: Style) {
+        let Some(styling) = self.styling(id) else {
+            return;
+        };
+        styling.inline = style;
+        self.apply_style(id);
+    }
+
+    /// the element's styling, remembering how it looks now the first time.
+    /// `None` if there's no such element
+    fn styling(&mut self, id: &str) -> Option<&mut Styling> {
+        if !self.styles.elements.contains_key(id) {
+            let base = read(self, id)?;
+            self.styles.elements.insert(
+                id.to_string(),
+                Styling {
+                    applied: base.clone(),
+                    base,
+                    classes: Vec::new(),
+                    inline: Style::default(),
+                },
+            );
+        }
+        self.styles.elements.get_mut(id)
+    }
+
+    /// writes base, classes and inline style onto the element's fields.
+    /// fields changed since the last time, e.g. by `set_opacity` or an
+    /// animation, become part of the base first, so they stay unless a
+    /// class or the inline style sets them
+    pub(crate) fn apply_style(&mut self, id: &str) {
+        let Some(current) = read(self, id) else {
+            return;
+        };
+        let Some(styling) = self.styles.elements.get_mut(id) else {
+            return;
+        };
+        styling.base.keep_changes(&styling.applied, &current);
+        let mut style = styling.base.clone();
+        for class in &styling.classes {
+            if let Some(class) = self.styles.classes.get(class) {
+                style.merge(class);
+            }
+        }
+        style.merge(&styling.inline);
+        write(self, id, &style);
+        if let Some(applied) = read(self, id)
+            && let Some(styling) = self.styles.elements.get_mut(id)
+        {
+            styling.applied = applied;
+        }
+        self.mark_dirty();
+    }
+
+    /// runs `f` with every styled element showing its base style, then
+    /// takes what `f` changed into the bases and styles them again
+    pub(crate) fn unstyled(&mut self, f: impl FnOnce(&mut Ui)) {
+        let ids: Vec<String> = self.styles.elements.keys().cloned().collect();
+        for id in &ids {
+            let Some(current) = read(self, id) else {
+                continue;
+            };
+            let Some(styling) = self.styles.elements.get_mut(id) else {
+                continue;
+            };
+            styling.base.keep_changes(&styling.applied, &current);
+            let base = styling.base.clone();
+            write(self, id, &base);
+        }
+        f(self);
+        for id in &ids {
+            if let Some(base) = read(self, id)
+                && let Some(styling) = self.styles.elements.get_mut(id)
+            {
+                styling.applied = base.clone();
+                styling.base = base;
+            }
+            self.apply_style(id);
+        }
+    }
+}
+
+/// everything about an element a style can set
+fn read(ui: &mut Ui, id: &str) -> Option<Style> {
+    if let Some(r) = ui.rects.iter().find(|r| r.id == id) {
+        return Some(Style {
+            background: Some(r.color),
+            border_color: Some(r.outline_color),
+            border_width: Some(r.outline_thickness),
+            radius: Some(r.radius),
+            opacity: Some(r.opacity),
+            cursor: r.cursor,
+            ..Style::default()
+        });
+    }
+    if let Some(t) = ui.texts.iter().find(|t| t.id == id) {
+        return Some(Style {
+            text_color: Some(t.color),
+            font: Some(FontSpec::new(&t.font_family, t.font_size)),
+            opacity: Some(t.opacity),
+            ..Style::default()
+        });
+    }
+    if let Some(b) = ui.buttons.iter().find(|b| b.id == id) {
+        let font = ui.fonts.get(b.font_id);
+        return Some(Style {
+            background: Some(b.bg_color),
+            background_hover: Some(b.bg_color_hover),
+            background_pressed: Some(b.bg_color_pressed),
+            background_disabled: Some(b.bg_color_disabled),
+            text_color: Some(b.text_color),
+            text_color_hover: Some(b.text_color_hover),
+            text_color_pressed: Some(b.text_color_pressed),
+            text_color_disabled: Some(b.text_color_disabled),
+            border_color: Some(b.outline_color),
+            border_color_hover: Some(b.outline_color_hover),
+            border_color_pressed: Some(b.outline_color_pressed),
+            border_color_disabled: Some(b.outline_color_disabled),
+            border_color_focus: Some(b.outline_color_focus),
+            border_width: Some(b.outline_thickness),
+            border_width_hover: Some(b.outline_thickness_hover),
+            border_width_pressed: Some(b.outline_thickness_pressed),
+            border_width_focus: Some(b.outline_thickness_focus),
+            radius: Some(b.radius),
+            padding: Some(b.padding),
+            font: Some(FontSpec::new(&font.family, font.size)),
+            opacity: Some(b.opacity),
+            transition: Some(b.transition),
+            cursor: b.cursor,
+        });
+    }
+    if let Some(c) = ui.canvases.iter().find(|c| c.id == id) {
+        return Some(Style {
+            background: Some(c.color),
+            border_color: Some(c.outline_color),
+            border_width: Some(c.outline_thickness),
+            opacity: Some(c.opacity),
+            cursor: c.cursor,
+            ..Style::default()
+        });
+    }
+    None
+}
+
+fn write(ui: &mut Ui, id: &str, style: &Style) {
+    let font = style.font.as_ref().map(|spec| {
+        let name = format!("{}@{}", spec.family, spec.size);
+        ui.fonts.add(&name, &spec.family, spec.size)
+    });
+    macro_rules! set {
+        ($target:expr, $value:expr) => {
+            if let Some(value) = $value {
+                $target = value;
+            }
+        };
+    }
+
+    if let Some(r) = ui.rects.iter_mut().find(|r| r.id == id) {
+        set!(r.color, style.background);
+        set!(r.outline_color, style.border_color);
+        set!(r.outline_thickness, style.border_width);
+        set!(r.radius, style.radius);
+        set!(r.opacity, style.opacity);
+        r.cursor = style.cursor;
+    } else if let Some(t) = ui.texts.iter_mut().find(|t| t.id == id) {
+        set!(t.color, style.text_color);
+        set!(t.opacity, style.opacity);
+        if let (Some(font), Some(spec)) = (font, &style.font) {
+            t.font_id = font;
+            t.font_family = spec.family.clone();
+            t.font_size = spec.size;
+        }
+    } else if let Some(b) = ui.buttons.iter_mut().find(|b| b.id == id) {
+        set!(b.bg_color, style.background);
+        set!(b.bg_color_hover, style.background_hover);
+        set!(b.bg_color_pressed, style.background_pressed);
+        set!(b.bg_color_disabled, style.background_disabled);
+        set!(b.text_color, style.text_color);
+        set!(b.text_color_hover, style.text_color_hover);
+        set!(b.text_color_pressed, style.text_color_pressed);
+        set!(b.text_color_disabled, style.text_color_disabled);
+        set!(b.outline_color, style.border_color);
+        set!(b.outline_color_hover, style.border_color_hover);
+        set!(b.outline_color_pressed, style.border_color_pressed);
+        set!(b.outline_color_disabled, style.border_color_disabled);
+        set!(b.outline_color_focus, style.border_color_focus);
+        set!(b.outline_thickness, style.border_width);
+        set!(b.outline_thickness_hover, style.border_width_hover);
+        set!(b.outline_thickness_pressed, style.border_width_pressed);
+        set!(b.outline_thickness_focus, style.border_width_focus);
+        set!(b.radius, style.radius);
+        set!(b.opacity, style.opacity);
+        set!(b.transition, style.transition);
+        b.cursor = style.cursor;
+        // the button is sized to its label
+        let (padding, font_id) = (b.padding, b.font_id);
+        set!(b.padding, style.padding);
+        set!(b.font_id, font);
+        if b.padding != padding || b.font_id != font_id {
+            let (w, h) = ui.fonts.measure(&b.text, b.font_id);
+            b.w = w + b.padding * 2.0;
+            b.h = h + b.padding * 2.0;
+        }
+    } else if let Some(c) = ui.canvases.iter_mut().find(|c| c.id == id) {
+        set!(c.color, style.background);
+        set!(c.outline_color, style.border_color);
+        set!(c.outline_thickness, style.border_width);
+        set!(c.opacity, style.opacity);
+        c.cursor = style.cursor;
+    }
+}
@@ -0,0 +1,427 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ui::Ui;
+use crate::{Color, Ctx, FontId};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThemeMode {
+    Light,
+    Dark,
+}
+
+impl From<winit::window::Theme> for ThemeMode {
+    fn from(theme: winit::window::Theme) -> Self {
+        match theme {
+            winit::window::Theme::Light => ThemeMode::Light,
+            winit::window::Theme::Dark => ThemeMode::Dark,
+        }
+    }
+}
+
+/// what a piece of text is for, each theme picks a font per role
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FontRole {
+    Body,
+    Heading,
+    Caption,
+    Mono,
+}
+
+impl FontRole {
+    pub const ALL: [FontRole; 4] = [FontRole::Body, FontRole::Heading, FontRole::Caption, FontRole::Mono];
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontSpec {
+    pub family: String,
+    pub size: f32,
+}
+
+impl FontSpec {
+    pub fn new(family: &str, size: f32) -> Self {
+        Self {
+            family: family.to_string(),
+            size,
+        }
+    }
+}
+
+/// a color of the theme. fields that took one remember it, so switching
+/// themes moves exactly those to the new theme's color
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ThemeToken {
+    Background,
+    Surface,
+    SurfaceRaised,
+    Primary,
+    OnPrimary,
+    Text,
+    TextMuted,
+    Border,
+    Focus,
+    Accent,
+    Selection,
+}
+
+/// how a state derives its color from a token, like a button's hover
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Shade {
+    Base,
+    Hover,
+    Pressed,
+    Disabled,
+    /// the see-through drag preview
+    Preview,
+}
+
+/// which theme color a field was given
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Themed {
+    pub token: ThemeToken,
+    pub shade: Shade,
+}
+
+impl Themed {
+    pub(crate) const fn new(token: ThemeToken, shade: Shade) -> Self {
+        Self { token, shade }
+    }
+
+    /// the normal, hover, pressed and disabled shades buttons use
+    pub(crate) fn states(token: ThemeToken) -> [Option<Themed>; 4] {
+        [Shade::Base, Shade::Hover, Shade::Pressed, Shade::Disabled].map(|shade| Some(Self::new(token, shade)))
+    }
+}
+
+/// semantic colors, sizes and fonts that widgets take their defaults from.
+/// the ui starts with `Theme::dark`, switch with `Ctx::set_theme`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub mode: ThemeMode,
+    /// behind everything, the window is cleared to it
+    pub background: Color,
+    /// panels, headers and popups
+    pub surface: Color,
+    /// inputs and boxes sitting on a surface
+    pub surface_raised: Color,
+    /// buttons and other things to press
+    pub primary: Color,
+    /// text on `primary`
+    pub on_primary: Color,
+    pub text: Color,
+    /// labels and hints that shouldn't stand out
+    pub text_muted: Color,
+    pub border: Color,
+    /// the focus ring and drag preview
+    pub focus: Color,
+    /// drop targets and other positive highlights
+    pub accent: Color,
+    /// selected rows
+    pub selection: Color,
+    pub radius_small: f32,
+    /// corner radius of buttons
+    pub radius: f32,
+    /// corner radius of popups like the command palette
+    pub radius_large: f32,
+    /// gap between related elements
+    pub spacing: f32,
+    /// between a control's edge and its content
+    pub padding: f32,
+    pub body_font: FontSpec,
+    pub heading_font: FontSpec,
+    pub caption_font: FontSpec,
+    pub mono_font: FontSpec,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            mode: ThemeMode::Dark,
+            background: Color::rgb(0.09, 0.10, 0.12),
+            surface: Color::rgb(0.13, 0.14, 0.17),
+            surface_raised: Color::rgb(0.2, 0.21, 0.25),
+            primary: Color::rgb(0.27, 0.51, 0.50),
+            on_primary: Color::WHITE,
+            text: Color::rgb(0.9, 0.92, 0.95),
+            text_muted: Color::rgb(0.6, 0.62, 0.68),
+            border: Color::rgb(0.25, 0.28, 0.35),
+            focus: Color::rgb(0.35, 0.6, 1.0),
+            accent: Color::rgb(0.35, 0.8, 0.5),
+            selection: Color::rgb(0.22, 0.36, 0.55),
+            radius_small: 2.0,
+            radius: 4.0,
+            radius_large: 6.0,
+            spacing: 8.0,
+            padding: 8.0,
+            body_font: FontSpec::new("Arial", 14.0),
+            heading_font: FontSpec::new("Arial", 20.0),
+            caption_font: FontSpec::new("Arial", 12.0),
+            mono_font: FontSpec::new("Courier New", 14.0),
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            mode: ThemeMode::Light,
+            background: Color::rgb(0.95, 0.95, 0.96),
+            surface: Color::WHITE,
+            surface_raised: Color::rgb(0.91, 0.92, 0.94),
+            primary: Color::rgb(0.16, 0.45, 0.44),
+            on_primary: Color::WHITE,
+            text: Color::rgb(0.1, 0.11, 0.13),
+            text_muted: Color::rgb(0.42, 0.44, 0.5),
+            border: Color::rgb(0.8, 0.82, 0.86),
+            focus: Color::rgb(0.2, 0.45, 0.9),
+            accent: Color::rgb(0.2, 0.6, 0.35),
+            selection: Color::rgb(0.78, 0.86, 0.97),
+            // same sizes and fonts
+            ..Self::dark()
+        }
+    }
+
+    /// the built-in theme for `mode`
+    pub fn for_mode(mode: ThemeMode) -> Self {
+        match mode {
+            ThemeMode::Light => Self::light(),
+            ThemeMode::Dark => Self::dark(),
+        }
+    }
+
+    pub fn font(&self, role: FontRole) -> &FontSpec {
+        match role {
+            FontRole::Body => &self.body_font,
+            FontRole::Heading => &self.heading_font,
+            FontRole::Caption => &self.caption_font,
+            FontRole::Mono => &self.mono_font,
+        }
+    }
+
+    pub fn color(&self, token: ThemeToken) -> Color {
+        match token {
+            ThemeToken::Background => self.background,
+            ThemeToken::Surface => self.surface,
+            ThemeToken::SurfaceRaised => self.surface_raised,
+            ThemeToken::Primary => self.primary,
+            ThemeToken::OnPrimary => self.on_primary,
+            ThemeToken::Text => self.text,
+            ThemeToken::TextMuted => self.text_muted,
+            ThemeToken::Border => self.border,
+            ThemeToken::Focus => self.focus,
+            ThemeToken::Accent => self.accent,
+            ThemeToken::Selection => self.selection,
+        }
+    }
+
+    pub(crate) fn themed(&self, themed: Themed) -> Color {
+        let color = self.color(themed.token);
+        match themed.shade {
+            Shade::Base => color,
+            Shade::Hover => color.darken(0.8),
+            Shade::Pressed => color.darken(0.65),
+            Shade::Disabled => color.faded(0.45),
+            Shade::Preview => color.faded(0.4),
+        }
+    }
+}
+
+/// moves `color` to the new theme if it still shows the old theme's color
+/// for `themed`. a color changed since then is the app's and loses its token
+fn retheme(color: &mut Color, themed: &mut Option<Themed>, old: &Theme, new: &Theme) {
+    let Some(t) = *themed else {
+        return;
+    };
+    if *color == old.themed(t) {
+        *color = new.themed(t);
+    } else {
+        *themed = None;
+    }
+}
+
+impl Ui {
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// the current theme's font for `role`, registered on first use.
+    /// elements using it switch along with the theme
+    pub fn font(&mut self, role: FontRole) -> FontId {
+        let spec = self.theme.font(role);
+        let name = format!("{}@{}", spec.family, spec.size);
+        let id = self.fonts.add(&name, &spec.family, spec.size);
+        self.theme_fonts.entry(id).or_insert(role);
+        id
+    }
+
+    /// paints element `id` in a theme color that follows theme switches:
+    /// the fill of a rect or canvas, the color of a text, or the background
+    /// of a button along with its hover, pressed and disabled shades
+    pub fn set_theme_color(&mut self, id: &str, token: ThemeToken) {
+        let theme = &self.theme;
+        let themed = Themed::new(token, Shade::Base);
+        if let Some(rect) = self.rects.iter_mut().find(|r| r.id == id) {
+            rect.color = theme.themed(themed);
+            rect.theme_colors[0] = Some(themed);
+        } else if let Some(canvas) = self.canvases.iter_mut().find(|c| c.id == id) {
+            canvas.color = theme.themed(themed);
+            canvas.theme_colors[0] = Some(themed);
+        } else if let Some(text) = self.texts.iter_mut().find(|t| t.id == id) {
+            text.color = theme.themed(themed);
+            text.theme_color = Some(themed);
+        } else if let Some(button) = self.buttons.iter_mut().find(|b| b.id == id) {
+            let states = Themed::states(token);
+            for ((color, slot), themed) in button.themed_colors().into_iter().zip(states) {
+                *color = theme.themed(themed.unwrap());
+                *slot = themed;
+            }
+        }
+        self.mark_dirty();
+    }
+
+    /// like `set_theme_color` for the outline of a rect, canvas or button
+    pub fn set_theme_outline(&mut self, id: &str, token: ThemeToken) {
+        let theme = &self.theme;
+        let themed = Themed::new(token, Shade::Base);
+        if let Some(rect) = self.rects.iter_mut().find(|r| r.id == id) {
+            rect.outline_color = theme.themed(themed);
+            rect.theme_colors[1] = Some(themed);
+        } else if let Some(canvas) = self.canvases.iter_mut().find(|c| c.id == id) {
+            canvas.outline_color = theme.themed(themed);
+            canvas.theme_colors[1] = Some(themed);
+        } else if let Some(button) = self.buttons.iter_mut().find(|b| b.id == id) {
+            // focus keeps the plain color, like `Style::border`
+            let states = Themed::states(token).into_iter().chain([Some(themed)]);
+            for ((color, slot), themed) in button.themed_colors().into_iter().skip(8).zip(states) {
+                *color = theme.themed(themed.unwrap());
+                *slot = themed;
+            }
+        }
+        self.mark_dirty();
+    }
+
+    /// switches theme and moves every element field that took a value from
+    /// the old theme to the new one. values the app set itself stay as
+    /// they are
+    pub(crate) fn restyle(&mut self, theme: Theme) {
+        self.padding();
+        let old = std::mem::replace(&mut self.theme, theme);
+        self.sync_default_padding();
+        self.unstyled(|ui| ui.retheme(&old));
+        self.mark_dirty();
+    }
+
+    /// the theme's padding, after taking over a value the app wrote to the
+    /// deprecated `Fonts::default_padding`
+    #[allow(deprecated)]
+    pub(crate) fn padding(&mut self) -> f32 {
+        if self.fonts.default_padding != self.theme.padding {
+            self.theme.padding = self.fonts.default_padding;
+        }
+        self.theme.padding
+    }
+
+    #[allow(deprecated)]
+    pub(crate) fn sync_default_padding(&mut self) {
+        self.fonts.default_padding = self.theme.padding;
+    }
+
+    fn retheme(&mut self, old: &Theme) {
+        let fonts: HashMap<FontRole, FontId> = FontRole::ALL.into_iter().map(|role| (role, self.font(role))).collect();
+        let refont = |theme_fonts: &HashMap<FontId, FontRole>, id: FontId| {
+            theme_fonts.get(&id).map(|role| fonts[role]).filter(|&new| new != id)
+        };
+        let new = &self.theme;
+
+        for rect in &mut self.rects {
+            for (color, themed) in rect.themed_colors() {
+                retheme(color, themed, old, new);
+            }
+        }
+        for canvas in &mut self.canvases {
+            for (color, themed) in canvas.themed_colors() {
+                retheme(color, themed, old, new);
+            }
+        }
+        for text in &mut self.texts {
+            retheme(&mut text.color, &mut text.theme_color, old, new);
+            if let Some(id) = refont(&self.theme_fonts, text.font_id) {
+                let entry = self.fonts.get(id);
+                text.font_id = id;
+                text.font_family = entry.family.clone();
+                text.font_size = entry.size;
+            }
+        }
+        for button in &mut self.buttons {
+            for (color, themed) in button.themed_colors() {
+                retheme(color, themed, old, new);
+            }
+            let resize = |value: &mut f32, themed: &mut bool, from: f32, to: f32| {
+                if !*themed || *value != from {
+                    *themed = false;
+                    return false;
+                }
+                *value = to;
+                from != to
+            };
+            resize(&mut button.radius, &mut button.radius_themed, old.radius, new.radius);
+            let mut relayout = resize(&mut button.padding, &mut button.padding_themed, old.padding, new.padding);
+            if let Some(id) = refont(&self.theme_fonts, button.font_id) {
+                button.font_id = id;
+                relayout = true;
+            }
+            if relayout {
+                let (w, h) = self.fonts.measure(&button.text, button.font_id);
+                button.w = w + button.padding * 2.0;
+                button.h = h + button.padding * 2.0;
+            }
+        }
+
+        let overlays = [
+            (&mut self.focus_ring_color, Themed::new(ThemeToken::Focus, Shade::Base)),
+            (&mut self.drop_highlight_color, Themed::new(ThemeToken::Accent, Shade::Base)),
+            (&mut self.drag_preview_color, Themed::new(ThemeToken::Focus, Shade::Preview)),
+        ];
+        for (color, themed) in overlays {
+            retheme(color, &mut Some(themed), old, new);
+        }
+    }
+}
+
+impl Ctx {
+    /// switches the ui to `theme`, restyling existing elements and the
+    /// command palette
+    pub fn set_theme(&mut self, theme: Theme) {
+        let old = self.ui.theme.clone();
+        self.ui.restyle(theme);
+        let new = &self.ui.theme;
+        let palette = &mut self.palette;
+        for (color, token) in [
+            (&mut palette.background, ThemeToken::Surface),
+            (&mut palette.input_background, ThemeToken::SurfaceRaised),
+            (&mut palette.selected_background, ThemeToken::Selection),
+            (&mut palette.text_color, ThemeToken::Text),
+            (&mut palette.hint_color, ThemeToken::TextMuted),
+        ] {
+            retheme(color, &mut Some(Themed::new(token, Shade::Base)), &old, new);
+        }
+    }
+
+    /// the os theme, once the window reported it
+    pub fn system_theme(&self) -> Option<ThemeMode> {
+        self.system_theme
+    }
+
+    /// remembers the os theme and follows it if `follow_system_theme` is on
+    pub(crate) fn system_theme_changed(&mut self, mode: ThemeMode) {
+        self.system_theme = Some(mode);
+        if self.follow_system_theme && self.ui.theme.mode != mode {
+            self.set_theme(Theme::for_mode(mode));
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use std::time::Duration;
+
+use winit::event::{MouseButton, TouchPhase};
+
+use crate::{Ctx, InputEvent};
+
+/// which device a pointer belongs to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PointerId {
+    Mouse,
+    Touch(u64),
+}
+
+/// a pressed mouse button or a finger on the screen
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pointer {
+    pub id: PointerId,
+    pub x: f32,
+    pub y: f32,
+    /// `Started` and `Ended` only for the event they happen on
+    pub phase: TouchPhase,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// two fingers moving apart or together during this event
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pinch {
+    /// change in finger distance, above 1 when spreading
+    pub scale: f32,
+    /// midpoint between the fingers
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TouchSettings {
+    /// how far a finger may move and still tap
+    pub tap_slop: f32,
+    pub swipe_distance: f32,
+    /// longest a swipe may take
+    pub swipe_time: Duration,
+}
+
+impl Default for TouchSettings {
+    fn default() -> Self {
+        Self {
+            tap_slop: 10.0,
+            swipe_distance: 50.0,
+            swipe_time: Duration::from_millis(300),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Touch {
+    id: u64,
+    x: f32,
+    y: f32,
+    start_x: f32,
+    start_y: f32,
+    start_time: Duration,
+    phase: TouchPhase,
+}
+
+/// fingers on the screen and what they're doing. the first finger drives
+/// the mouse so clicks, drags and hover work unchanged, a second one turns
+/// it into a pinch or pan. lives on `Ctx::touches`
+#[derive(Default)]
+pub struct Touches {
+    pub settings: TouchSettings,
+    pub pinch: Option<Pinch>,
+    /// how far the midpoint of two fingers moved during this event
+    pub pan: Option<(f32, f32)>,
+    pub swipe: Option<SwipeDirection>,
+
+    touches: Vec<Touch>,
+    /// the finger standing in for the mouse
+    primary: Option<u64>,
+    /// whether the primary finger moved far enough to press the button
+    pressing: bool,
+    /// a second finger came down, so the primary can't tap anymore
+    multi: bool,
+}
+
+impl Touches {
+    fn clear_flags(&mut self) {
+        self.pinch = None;
+        self.pan = None;
+        self.swipe = None;
+        // lifted fingers are reported for one event, then forgotten
+        self.touches
+            .retain(|t| !matches!(t.phase, TouchPhase::Ended | TouchPhase::Cancelled));
+        for touch in &mut self.touches {
+            touch.phase = TouchPhase::Moved;
+        }
+    }
+
+    /// midpoint and distance of the first two fingers
+    fn span(&self) -> Option<((f32, f32), f32)> {
+        let [a, b, ..] = self.touches.as_slice() else {
+            return None;
+        };
+        let center = ((a.x + b.x) * 0.5, (a.y + b.y) * 0.5);
+        Some((center, (a.x - b.x).hypot(a.y - b.y)))
+    }
+}
+
+impl Ctx {
+    /// every pressed mouse button and finger on the screen, in the order
+    /// they went down
+    pub fn pointers(&self) -> Vec<Pointer> {
+        let mut pointers = Vec::new();
+        let mouse = &self.mouse;
+        if (mouse.left_pressed || mouse.left_just_released) && self.touches.primary.is_none() {
+            let phase = if mouse.left_just_pressed {
+                TouchPhase::Started
+            } else if mouse.left_just_released {
+                TouchPhase::Ended
+            } else {
+                TouchPhase::Moved
+            };
+            pointers.push(Pointer {
+                id: PointerId::Mouse,
+                x: mouse.x,
+                y: mouse.y,
+                phase,
+            });
+        }
+        pointers.extend(self.touches.touches.iter().map(|t| Pointer {
+            id: PointerId::Touch(t.id),
+            x: t.x,
+            y: t.y,
+            phase: t.phase,
+        }));
+        pointers
+    }
+
+    pub fn pinch(&self) -> Option<Pinch> {
+        self.touches.pinch
+    }
+
+    pub fn two_finger_pan(&self) -> Option<(f32, f32)> {
+        self.touches.pan
+    }
+
+    /// a quick one finger flick that ended during this event
+    pub fn swipe(&self) -> Option<SwipeDirection> {
+        self.touches.swipe
+    }
+
+    /// tracks fingers and recognizes gestures. returns the mouse events the
+    /// primary finger stands for, dispatched right after the touch itself
+    pub(crate) fn update_touch(&mut self, event: &InputEvent) -> Vec<InputEvent> {
+        let InputEvent::Touch { id, phase, x, y } = *event else {
+            return Vec::new();
+        };
+        let now = self.time();
+        let settings = self.touches.settings;
+        let before = self.touches.span();
+        let t = &mut self.touches;
+        let mut emulated = Vec::new();
+        let cursor = InputEvent::CursorMoved { x, y };
+        let press = |pressed| InputEvent::MouseButton {
+            button: MouseButton::Left,
+            pressed,
+        };
+
+        match phase {
+            TouchPhase::Started => {
+                t.touches.push(Touch {
+                    id,
+                    x,
+                    y,
+                    start_x: x,
+                    start_y: y,
+                    start_time: now,
+                    phase,
+                });
+                if t.primary.is_none() && t.touches.len() == 1 {
+                    t.primary = Some(id);
+                    t.pressing = false;
+                    t.multi = false;
+                    emulated.push(cursor);
+                } else {
+                    t.multi = true;
+                    if t.pressing {
+                        t.pressing = false;
+                        emulated.push(press(false));
+                    }
+                }
+            }
+            TouchPhase::Moved => {
+                let Some(touch) = t.touches.iter_mut().find(|t| t.id == id) else {
+                    return emulated;
+                };
+                touch.x = x;
+                touch.y = y;
+                touch.phase = phase;
+                let moved = (x - touch.start_x).hypot(y - touch.start_y);
+                if t.primary == Some(id) && !t.multi {
+                    if !t.pressing && moved > settings.tap_slop {
+                        // press where the finger went down, so drags start there
+                        t.pressing = true;
+                        emulated.push(InputEvent::CursorMoved {
+                            x: touch.start_x,
+                            y: touch.start_y,
+                        });
+                        emulated.push(press(true));
+                    }
+                    emulated.push(cursor);
+                }
+                if let (Some((c0, d0)), Some((c1, d1))) = (before, t.span()) {
+                    if d0 > 0.0 {
+                        t.pinch = Some(Pinch {
+                            scale: d1 / d0,
+                            x: c1.0,
+                            y: c1.1,
+                        });
+                    }
+                    t.pan = Some((c1.0 - c0.0, c1.1 - c0.1));
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                let Some(touch) = t.touches.iter_mut().find(|t| t.id == id) else {
+                    return emulated;
+                };
+                touch.x = x;
+                touch.y = y;
+                touch.phase = phase;
+                let (dx, dy) = (x - touch.start_x, y - touch.start_y);
+                let quick = now.saturating_sub(touch.start_time) <= settings.swipe_time;
+
+                if t.primary == Some(id) {
+                    t.primary = None;
+                    let ended = phase == TouchPhase::Ended;
+                    if t.pressing {
+                        t.pressing = false;
+                        emulated.push(cursor);
+                        emulated.push(press(false));
+                    } else if ended && !t.multi {
+                        emulated.extend([cursor, press(true), press(false)]);
+                    }
+                    if ended && !t.multi && quick && dx.hypot(dy) >= settings.swipe_distance {
+                        t.swipe = Some(match dx.abs() >= dy.abs() {
+                            true if dx > 0.0 => SwipeDirection::Right,
+                            true => SwipeDirection::Left,
+                            false if dy > 0.0 => SwipeDirection::Down,
+                            false => SwipeDirection::Up,
+                        });
+                    }
+                }
+            }
+        }
+
+        self.touch_canvases();
+        emulated
+    }
+
+    /// pinching zooms and two fingers pan the canvas between them
+    fn touch_canvases(&mut self) {
+        let Some(((x, y), _)) = self.touches.span() else {
+            return;
+        };
+        let Some(canvas) = self
+            .ui
+            .canvases
+            .iter_mut()
+            .rev()
+            .find(|c| c.visible && x >= c.x && x <= c.x + c.w && y >= c.y && y <= c.y + c.h)
+        else {
+            return;
+        };
+        if let Some((dx, dy)) = self.touches.pan
+            && canvas.pannable
+        {
+            canvas.pan(dx, dy);
+            self.ui.dirty = true;
+        }
+        if let Some(pinch) = self.touches.pinch
+            && canvas.zoomable
+        {
+            canvas.zoom_at(pinch.x, pinch.y, pinch.scale);
+            self.ui.dirty = true;
+        }
+    }
+
+    pub(crate) fn end_touch(&mut self) {
+        self.touches.clear_flags();
+    }
+}
@@ -0,0 +1,130 @@
+/// 2d affine transform, maps (x, y) to
+/// (sx * x + kx * y + tx, ky * x + sy * y + ty)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub sx: f32,
+    pub ky: f32,
+    pub kx: f32,
+    pub sy: f32,
+    pub tx: f32,
+    pub ty: f32,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self {
+        sx: 1.0,
+        ky: 0.0,
+        kx: 0.0,
+        sy: 1.0,
+        tx: 0.0,
+        ty: 0.0,
+    };
+
+    pub const fn translate(x: f32, y: f32) -> Self {
+        Self { tx: x, ty: y, ..Self::IDENTITY }
+    }
+
+    pub const fn scale(sx: f32, sy: f32) -> Self {
+        Self { sx, sy, ..Self::IDENTITY }
+    }
+
+    /// clockwise on screen, in radians
+    pub fn rotate(radians: f32) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        Self {
+            sx: cos,
+            ky: sin,
+            kx: -sin,
+            sy: cos,
+            tx: 0.0,
+            ty: 0.0,
+        }
+    }
+
+    /// skew angles in radians along the x and y axes
+    pub fn skew(x_radians: f32, y_radians: f32) -> Self {
+        Self {
+            kx: x_radians.tan(),
+            ky: y_radians.tan(),
+            ..Self::IDENTITY
+        }
+    }
+
+    /// `self` applied around (cx, cy) instead of the origin
+    pub fn around(self, cx: f32, cy: f32) -> Self {
+        Self::translate(-cx, -cy)
+            .then(self)
+            .then(Self::translate(cx, cy))
+    }
+
+    /// `self` followed by `next`
+    pub fn then(self, next: Self) -> Self {
+        Self {
+            sx: next.sx * self.sx + next.kx * self.ky,
+            ky: next.ky * self.sx + next.sy * self.ky,
+            kx: next.sx * self.kx + next.kx * self.sy,
+            sy: next.ky * self.kx + next.sy * self.sy,
+            tx: next.sx * self.tx + next.kx * self.ty + next.tx,
+            ty: next.ky * self.tx + next.sy * self.ty + next.ty,
+        }
+    }
+
+    pub fn translated(self, x: f32, y: f32) -> Self {
+        self.then(Self::translate(x, y))
+    }
+
+    pub fn scaled(self, sx: f32, sy: f32) -> Self {
+        self.then(Self::scale(sx, sy))
+    }
+
+    pub fn rotated(self, radians: f32) -> Self {
+        self.then(Self::rotate(radians))
+    }
+
+    pub fn skewed(self, x_radians: f32, y_radians: f32) -> Self {
+        self.then(Self::skew(x_radians, y_radians))
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::IDENTITY
+    }
+
+    pub fn determinant(&self) -> f32 {
+        self.sx * self.sy - self.kx * self.ky
+    }
+
+    /// average scale, used for stroke widths and font sizes
+    pub fn uniform_scale(&self) -> f32 {
+        self.determinant().abs().sqrt()
+    }
+
+    pub fn invert(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
+        let inv = 1.0 / det;
+        Some(Self {
+            sx: self.sy * inv,
+            ky: -self.ky * inv,
+            kx: -self.kx * inv,
+            sy: self.sx * inv,
+            tx: (self.kx * self.ty - self.sy * self.tx) * inv,
+            ty: (self.ky * self.tx - self.sx * self.ty) * inv,
+        })
+    }
+
+    #[inline(always)]
+    pub fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.sx * x + self.kx * y + self.tx,
+            self.ky * x + self.sy * y + self.ty,
+        )
+    }
+}
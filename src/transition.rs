@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::{Button, Color, Ctx, Easing};
+
+/// how a button is drawn in one state
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Look {
+    pub bg: Color,
+    pub text: Color,
+    pub outline: Color,
+    pub outline_thickness: f32,
+}
+
+impl Look {
+    fn lerp(self, to: Look, t: f32) -> Look {
+        let mix = |a: Color, b: Color| {
+            Color::new(
+                a.r + (b.r - a.r) * t,
+                a.g + (b.g - a.g) * t,
+                a.b + (b.b - a.b) * t,
+                a.a + (b.a - a.a) * t,
+            )
+        };
+        Look {
+            bg: mix(self.bg, to.bg),
+            text: mix(self.text, to.text),
+            outline: mix(self.outline, to.outline),
+            outline_thickness: self.outline_thickness
+                + (to.outline_thickness - self.outline_thickness) * t,
+        }
+    }
+}
+
+struct Transition {
+    from: Look,
+    to: Look,
+    start: Duration,
+}
+
+/// state changes in progress per button id
+#[derive(Default)]
+pub(crate) struct Transitions {
+    running: HashMap<String, Transition>,
+}
+
+impl Transitions {
+    /// forgets buttons that weren't drawn
+    pub(crate) fn retain(&mut self, ids: &[String]) {
+        self.running.retain(|id, _| ids.contains(id));
+    }
+
+    /// what to draw now for a button heading to `target`, and whether it's
+    /// still blending. a new target starts from wherever the last blend got
+    pub(crate) fn blend(&mut self, id: &str, target: Look, duration: Duration, now: Duration) -> (Look, bool) {
+        let progress = |t: &Transition| {
+            if duration.is_zero() {
+                1.0
+            } else {
+                now.saturating_sub(t.start).as_secs_f32() / duration.as_secs_f32()
+            }
+        };
+        let current = |t: &Transition| t.from.lerp(t.to, Easing::QuadOut.apply(progress(t)));
+
+        match self.running.get_mut(id) {
+            Some(t) if t.to != target => {
+                *t = Transition {
+                    from: current(t),
+                    to: target,
+                    start: now,
+                };
+            }
+            Some(_) => {}
+            // first time it's drawn, nothing to blend from
+            None => {
+                self.running.insert(
+                    id.to_string(),
+                    Transition {
+                        from: target,
+                        to: target,
+                        start: now,
+                    },
+                );
+            }
+        }
+        let t = &self.running[id];
+        (current(t), progress(t) < 1.0)
+    }
+}
+
+impl Ctx {
+    /// the look a button's state calls for: disabled, else pressed, else
+    /// hovered, with focus changing the outline
+    pub(crate) fn button_look(&self, b: &Button) -> Look {
+        if !b.enabled {
+            return Look {
+                bg: b.bg_color_disabled,
+                text: b.text_color_disabled,
+                outline: b.outline_color_disabled,
+                outline_thickness: b.outline_thickness,
+            };
+        }
+        let hovered = self.is_hovered(&b.id);
+        let pressed = hovered
+            && self.mouse.left_pressed
+            && self.gestures.target.as_deref() == Some(b.id.as_str());
+        let focused = self.is_focused(&b.id);
+
+        let mut look = Look {
+            bg: b.bg_color,
+            text: b.text_color,
+            outline: b.outline_color,
+            outline_thickness: b.outline_thickness,
+        };
+        if focused {
+            look.outline = b.outline_color_focus;
+            look.outline_thickness = b.outline_thickness_focus;
+        }
+        if pressed {
+            look = Look {
+                bg: b.bg_color_pressed,
+                text: b.text_color_pressed,
+                outline: b.outline_color_pressed,
+                outline_thickness: b.outline_thickness_pressed,
+            };
+        } else if hovered {
+            look.bg = b.bg_color_hover;
+            look.text = b.text_color_hover;
+            if !focused {
+                look.outline = b.outline_color_hover;
+                look.outline_thickness = b.outline_thickness_hover;
+            }
+        }
+        look
+    }
+}
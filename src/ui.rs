@@ -1,4 +1,11 @@
-use crate::{Color, FontId, Fonts, ShapeRenderer, TextRenderer};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use winit::window::CursorIcon;
+
+use crate::style::Styles;
+use crate::theme::{ThemeToken, Themed};
+use crate::{Color, FillRule, FontId, FontRole, Fonts, Path, ShapeRenderer, StrokeStyle, Svg, TextRenderer, Theme, Transform};
 
 pub enum Width {
     Fixed(f32),
@@ -23,9 +30,32 @@ pub struct Rect {
     pub color: Color,
     pub outline_color: Color,
     pub outline_thickness: f32,
+    /// corner radius, square at 0
+    pub radius: f32,
     pub visible: bool,
-    width_mode: Width,
-    height_mode: Height,
+    /// multiplies the alpha of everything the element draws, 0 is invisible
+    /// but still hit
+    pub opacity: f32,
+    /// applied around the center of the rect
+    pub transform: Transform,
+    /// whether clicks and Tab can focus it, off by default
+    pub focusable: bool,
+    /// Tab order like html: positive indices first in ascending order, then
+    /// 0 in layout order. negative only focuses programmatically
+    pub tab_index: i32,
+    /// pointer shown while hovered, `None` leaves it to what's below
+    pub cursor: Option<CursorIcon>,
+    pub(crate) width_mode: Width,
+    pub(crate) height_mode: Height,
+    /// theme colors of `color` and `outline_color`, see `Ui::set_theme_color`
+    pub(crate) theme_colors: [Option<Themed>; 2],
+}
+
+impl Rect {
+    pub(crate) fn themed_colors(&mut self) -> [(&mut Color, &mut Option<Themed>); 2] {
+        let [color, outline] = &mut self.theme_colors;
+        [(&mut self.color, color), (&mut self.outline_color, outline)]
+    }
 }
 
 pub struct Text {
@@ -38,6 +68,12 @@ pub struct Text {
     pub font_size: f32,
     pub font_family: String,
     pub visible: bool,
+    /// see `Rect::opacity`
+    pub opacity: f32,
+    /// applied around the center of the text, only translation and scale
+    /// affect glyphs
+    pub transform: Transform,
+    pub(crate) theme_color: Option<Themed>,
 }
 
 pub struct Button {
@@ -54,12 +90,152 @@ pub struct Button {
     pub text_color_hover: Color,
     pub outline_color: Color,
     pub outline_color_hover: Color,
+    /// while the left button is held down on it
+    pub bg_color_pressed: Color,
+    pub text_color_pressed: Color,
+    pub outline_color_pressed: Color,
+    pub bg_color_disabled: Color,
+    pub text_color_disabled: Color,
+    pub outline_color_disabled: Color,
+    /// outline while focused, on top of the focus ring
+    pub outline_color_focus: Color,
     pub outline_thickness: f32,
+    pub outline_thickness_hover: f32,
+    pub outline_thickness_pressed: f32,
+    pub outline_thickness_focus: f32,
+    /// corner radius, square at 0
+    pub radius: f32,
+    /// how long colors and outline take to blend into a new state, zero
+    /// switches instantly
+    pub transition: Duration,
     pub padding: f32,
     pub visible: bool,
+    /// see `Rect::opacity`
+    pub opacity: f32,
+    /// disabled buttons can't be clicked, focused or activated
+    pub enabled: bool,
+    /// applied around the center of the button
+    pub transform: Transform,
+    /// on by default, a focused button is clicked by Enter or Space
+    pub focusable: bool,
+    /// see `Rect::tab_index`
+    pub tab_index: i32,
+    /// the pointing hand by default
+    pub cursor: Option<CursorIcon>,
+    /// theme colors in the order of `themed_colors`
+    pub(crate) theme_colors: [Option<Themed>; 13],
+    /// whether `radius` and `padding` still come from the theme
+    pub(crate) radius_themed: bool,
+    pub(crate) padding_themed: bool,
+}
+
+impl Button {
+    pub(crate) fn themed_colors(&mut self) -> [(&mut Color, &mut Option<Themed>); 13] {
+        let [t0, t1, t2, t3, t4, t5, t6, t7, t8, t9, t10, t11, t12] = &mut self.theme_colors;
+        [
+            (&mut self.bg_color, t0),
+            (&mut self.bg_color_hover, t1),
+            (&mut self.bg_color_pressed, t2),
+            (&mut self.bg_color_disabled, t3),
+            (&mut self.text_color, t4),
+            (&mut self.text_color_hover, t5),
+            (&mut self.text_color_pressed, t6),
+            (&mut self.text_color_disabled, t7),
+            (&mut self.outline_color, t8),
+            (&mut self.outline_color_hover, t9),
+            (&mut self.outline_color_pressed, t10),
+            (&mut self.outline_color_disabled, t11),
+            (&mut self.outline_color_focus, t12),
+        ]
+    }
+}
+
+/// a pannable, zoomable area with its own world coordinates. draw into it
+/// with `Ui::draw_canvas`
+pub struct Canvas {
+    pub id: String,
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+    pub color: Color,
+    pub outline_color: Color,
+    pub outline_thickness: f32,
+    /// screen offset of the world origin from the canvas top left
+    pub offset_x: f32,
+    pub offset_y: f32,
+    pub zoom: f32,
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+    /// zoom factor per scroll unit is `1.0 + zoom_speed`
+    pub zoom_speed: f32,
+    pub pannable: bool,
+    pub zoomable: bool,
+    pub visible: bool,
+    /// fades the canvas and everything drawn into it with `draw_canvas`
+    pub opacity: f32,
+    /// off by default
+    pub focusable: bool,
+    /// see `Rect::tab_index`
+    pub tab_index: i32,
+    /// see `Rect::cursor`, shows a closed hand while panning regardless
+    pub cursor: Option<CursorIcon>,
+    pub(crate) width_mode: Width,
+    pub(crate) height_mode: Height,
+    /// see `Rect::theme_colors`
+    pub(crate) theme_colors: [Option<Themed>; 2],
+    /// the group the canvas was faded in this frame, `draw_canvas` draws
+    /// into it so the background and content fade together
+    pub(crate) group: Option<usize>,
+}
+
+impl Canvas {
+    pub(crate) fn themed_colors(&mut self) -> [(&mut Color, &mut Option<Themed>); 2] {
+        let [color, outline] = &mut self.theme_colors;
+        [(&mut self.color, color), (&mut self.outline_color, outline)]
+    }
+
+    /// maps world coordinates to screen coordinates
+    pub fn world_transform(&self) -> Transform {
+        Transform::scale(self.zoom, self.zoom).translated(self.x + self.offset_x, self.y + self.offset_y)
+    }
+
+    pub fn screen_to_world(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            (x - self.x - self.offset_x) / self.zoom,
+            (y - self.y - self.offset_y) / self.zoom,
+        )
+    }
+
+    pub fn world_to_screen(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            x * self.zoom + self.x + self.offset_x,
+            y * self.zoom + self.y + self.offset_y,
+        )
+    }
+
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        self.offset_x += dx;
+        self.offset_y += dy;
+    }
+
+    /// zooms by `factor` keeping the world point under (x, y) in place
+    pub fn zoom_at(&mut self, x: f32, y: f32, factor: f32) {
+        let (wx, wy) = self.screen_to_world(x, y);
+        self.zoom = (self.zoom * factor).clamp(self.min_zoom, self.max_zoom);
+        self.offset_x = x - self.x - wx * self.zoom;
+        self.offset_y = y - self.y - wy * self.zoom;
+    }
+
+    /// centers the world point (x, y) in the canvas
+    pub fn look_at(&mut self, x: f32, y: f32) {
+        self.offset_x = self.w * 0.5 - x * self.zoom;
+        self.offset_y = self.h * 0.5 - y * self.zoom;
+    }
 }
 
 impl UiElement for Button {}
+impl UiElement for Canvas {}
 impl UiElement for Rect {}
 impl UiElement for Text {}
 
@@ -71,26 +247,50 @@ pub struct Ui {
     pub rects: Vec<Rect>,
     pub texts: Vec<Text>,
     pub buttons: Vec<Button>,
+    pub canvases: Vec<Canvas>,
 
     pub dirty: bool,
-    
+
+    /// outline drawn around the focused element after keyboard navigation
+    pub focus_ring_color: Color,
+    pub focus_ring_thickness: f32,
+    /// outline of the drop target under a drag
+    pub drop_highlight_color: Color,
+    /// box following the cursor while dragging
+    pub drag_preview_color: Color,
+
+    pub(crate) theme: Theme,
+    /// fonts handed out by `font`, by the role they were for
+    pub(crate) theme_fonts: HashMap<FontId, FontRole>,
+    pub(crate) styles: Styles,
     window_width: f32,
     window_height: f32,
 }
 
 impl Ui {
     pub fn new(text_renderer: TextRenderer, shape_renderer: ShapeRenderer, fonts: Fonts) -> Self {
-        Self {
+        let theme = Theme::dark();
+        let mut ui = Self {
             text_renderer,
             shape_renderer,
             fonts,
             rects: Vec::new(),
             texts: Vec::new(),
             buttons: Vec::new(),
+            canvases: Vec::new(),
             dirty: false,
+            focus_ring_color: theme.focus,
+            focus_ring_thickness: 2.0,
+            drop_highlight_color: theme.accent,
+            drag_preview_color: theme.focus.faded(0.4),
+            theme,
+            theme_fonts: HashMap::new(),
+            styles: Styles::default(),
             window_width: 0.0,
             window_height: 0.0,
-        }
+        };
+        ui.sync_default_padding();
+        ui
     }
 
     pub fn mark_dirty(&mut self) {
@@ -103,6 +303,7 @@ impl Ui {
         d
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn rect(
         &mut self,
         id: &str,
@@ -139,9 +340,16 @@ impl Ui {
             color,
             outline_color,
             outline_thickness,
+            radius: 0.0,
             visible: true,
+            opacity: 1.0,
+            transform: Transform::IDENTITY,
+            focusable: false,
+            tab_index: 0,
+            cursor: None,
             width_mode: w,
             height_mode: h,
+            theme_colors: [None; 2],
         };
         self.rects.push(new_rect);
         self.mark_dirty();
@@ -166,6 +374,9 @@ impl Ui {
             font_size: size,
             font_family: family,
             visible: true,
+            opacity: 1.0,
+            transform: Transform::IDENTITY,
+            theme_color: None,
         };
         self.texts.push(new_text);
         self.mark_dirty();
@@ -176,11 +387,12 @@ impl Ui {
             panic!("Element with id '{}' already exists!", id);
         }
 
-        let padding = self.fonts.default_padding;
+        let padding = self.padding();
+        let radius = self.theme.radius;
 
-        let font_id = self.fonts.default();
-        let bg_color = Color::rgb(0.27, 0.51, 0.50);
-        let text_color = Color::WHITE;
+        let font_id = self.font(FontRole::Body);
+        let bg_color = self.theme.primary;
+        let text_color = self.theme.on_primary;
         let outline_color = Color::TRANSPARENT;
         let outline_thickness = 0.0;
 
@@ -189,10 +401,15 @@ impl Ui {
         let button_width = text_width + padding * 2.0;
         let button_height = text_height + padding * 2.0;
 
-        let bg_color_hover = Color::from_array(bg_color.to_array().map(|c| c * 0.8));
-        let text_color_hover = Color::from_array(text_color.to_array().map(|c| c * 0.8));
-        let outline_color_hover = Color::from_array(outline_color.to_array().map(|c| c * 0.8));
+        let bg_color_hover = bg_color.darken(0.8);
+        let text_color_hover = text_color.darken(0.8);
+        let outline_color_hover = outline_color.darken(0.8);
+        let bg_color_pressed = bg_color.darken(0.65);
+        let text_color_pressed = text_color.darken(0.65);
+        let outline_color_pressed = outline_color.darken(0.65);
 
+        let [bg, bg_hover, bg_pressed, bg_disabled] = Themed::states(ThemeToken::Primary);
+        let [label, label_hover, label_pressed, label_disabled] = Themed::states(ThemeToken::OnPrimary);
         let new_button = Button {
             id: id.to_string(),
             text: text.to_string(),
@@ -207,15 +424,125 @@ impl Ui {
             text_color_hover,
             outline_color,
             outline_color_hover,
+            bg_color_pressed,
+            text_color_pressed,
+            outline_color_pressed,
+            bg_color_disabled: bg_color.faded(0.45),
+            text_color_disabled: text_color.faded(0.45),
+            outline_color_disabled: outline_color.faded(0.45),
+            outline_color_focus: outline_color,
             outline_thickness,
+            outline_thickness_hover: outline_thickness,
+            outline_thickness_pressed: outline_thickness,
+            outline_thickness_focus: outline_thickness,
+            radius,
+            transition: Duration::ZERO,
             padding,
             visible: true,
+            opacity: 1.0,
+            enabled: true,
+            transform: Transform::IDENTITY,
+            focusable: true,
+            tab_index: 0,
+            cursor: Some(CursorIcon::Pointer),
+            theme_colors: [
+                bg,
+                bg_hover,
+                bg_pressed,
+                bg_disabled,
+                label,
+                label_hover,
+                label_pressed,
+                label_disabled,
+                None,
+                None,
+                None,
+                None,
+                None,
+            ],
+            radius_themed: true,
+            padding_themed: true,
         };
 
         self.buttons.push(new_button);
         self.mark_dirty();
     }
 
+    pub fn canvas(&mut self, id: &str, x: f32, y: f32, w: Width, h: Height, color: Color) {
+        if self.id_exists(id) {
+            panic!("Element with id '{}' already exists!", id);
+        }
+
+        let width = match w {
+            Width::Fixed(val) => val,
+            Width::Full => self.window_width,
+            Width::Percent(p) => self.window_width * p,
+        };
+
+        let height = match h {
+            Height::Fixed(val) => val,
+            Height::Full => self.window_height,
+            Height::Percent(p) => self.window_height * p,
+        };
+
+        let new_canvas = Canvas {
+            id: id.to_string(),
+            x,
+            y,
+            w: width,
+            h: height,
+            color,
+            outline_color: Color::TRANSPARENT,
+            outline_thickness: 0.0,
+            offset_x: 0.0,
+            offset_y: 0.0,
+            zoom: 1.0,
+            min_zoom: 0.1,
+            max_zoom: 10.0,
+            zoom_speed: 0.1,
+            pannable: true,
+            zoomable: true,
+            visible: true,
+            opacity: 1.0,
+            focusable: false,
+            tab_index: 0,
+            cursor: None,
+            width_mode: w,
+            height_mode: h,
+            theme_colors: [None; 2],
+            group: None,
+        };
+        self.canvases.push(new_canvas);
+        self.mark_dirty();
+    }
+
+    /// runs `draw` with the canvas world transform pushed and everything
+    /// clipped to the canvas bounds. does nothing for hidden canvases.
+    /// a faded canvas is one layer with its background, drawn where the
+    /// canvas is, so its content stays under texts and buttons on top of it
+    pub fn draw_canvas(&mut self, id: &str, draw: impl FnOnce(&mut Ui)) {
+        let Some(canvas) = self.canvases.iter().find(|c| c.id == id && c.visible) else {
+            return;
+        };
+        let (x, y, w, h) = (canvas.x, canvas.y, canvas.w, canvas.h);
+        let transform = canvas.world_transform();
+        let (opacity, group) = (canvas.opacity, canvas.group);
+
+        self.push_clip(x, y, w, h);
+        self.push_transform(transform);
+        match group {
+            Some(group) => {
+                self.shape_renderer.resume_group(group);
+                self.text_renderer.push_group(Some(group));
+            }
+            None => self.push_opacity(opacity),
+        }
+        draw(self);
+        self.pop_opacity();
+        self.pop_transform();
+        self.pop_clip();
+    }
+
     pub fn circle(
         &mut self,
         cx: f32,
@@ -229,6 +556,7 @@ impl Ui {
             .circle(cx, cy, radius, color, outline_color, outline_thickness);
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn rounded_rect(
         &mut self,
         x: f32,
@@ -252,10 +580,81 @@ impl Ui {
         );
     }
 
+    /// immediate-mode text, drawn for this frame only
+    pub fn draw_text(&mut self, text: &str, font_id: FontId, x: f32, y: f32, color: Color) {
+        let entry = self.fonts.get(font_id);
+        let family = entry.family.clone();
+        let size = entry.size;
+        self.text_renderer
+            .draw(&mut self.fonts.font_system, family, size, text, x, y, color);
+    }
+
+    pub fn fill_path(&mut self, path: &Path, rule: FillRule, color: [f32; 4]) {
+        self.shape_renderer.fill_path(path, rule, color);
+    }
+
+    pub fn stroke_path(&mut self, path: &Path, style: &StrokeStyle, color: [f32; 4]) {
+        self.shape_renderer.stroke_path(path, style, color);
+    }
+
+    pub fn svg(&mut self, svg: &Svg, x: f32, y: f32, w: f32, h: f32) {
+        self.shape_renderer.svg(svg, x, y, w, h);
+    }
+
+    pub fn svg_tinted(&mut self, svg: &Svg, x: f32, y: f32, w: f32, h: f32, tint: [f32; 4]) {
+        self.shape_renderer.svg_tinted(svg, x, y, w, h, tint);
+    }
+
+    /// applies to every immediate-mode shape and text drawn until the
+    /// matching `pop_transform`
+    pub fn push_transform(&mut self, transform: Transform) {
+        self.shape_renderer.push_transform(transform);
+        self.text_renderer.push_transform(transform);
+    }
+
+    pub fn pop_transform(&mut self) {
+        self.shape_renderer.pop_transform();
+        self.text_renderer.pop_transform();
+    }
+
+    /// fades immediate-mode shapes and text until the matching
+    /// `pop_opacity` as one group: they're drawn into a layer of their own
+    /// that's faded as a whole, so overlapping shapes don't show through
+    /// each other. nested opacities multiply
+    pub fn push_opacity(&mut self, opacity: f32) {
+        let group = self.shape_renderer.push_group(opacity);
+        self.text_renderer.push_group(group);
+    }
+
+    pub fn pop_opacity(&mut self) {
+        self.shape_renderer.pop_group();
+        self.text_renderer.pop_group();
+    }
+
+    /// immediate-mode drawing from here to the end of the frame lands on top
+    /// of everything else, text included
+    pub fn begin_overlay(&mut self) {
+        self.shape_renderer.begin_overlay();
+        self.text_renderer.begin_overlay();
+    }
+
+    /// clips immediate-mode shapes and text to a screen space rect until
+    /// the matching `pop_clip`
+    pub fn push_clip(&mut self, x: f32, y: f32, w: f32, h: f32) {
+        self.shape_renderer.push_clip(x, y, w, h);
+        self.text_renderer.push_clip(x, y, w, h);
+    }
+
+    pub fn pop_clip(&mut self) {
+        self.shape_renderer.pop_clip();
+        self.text_renderer.pop_clip();
+    }
+
     fn id_exists(&self, id: &str) -> bool {
         self.rects.iter().any(|r| r.id == id)
             || self.texts.iter().any(|t| t.id == id)
             || self.buttons.iter().any(|b| b.id == id)
+            || self.canvases.iter().any(|c| c.id == id)
     }
 
     pub fn show(&mut self, id: &str) {
@@ -280,6 +679,13 @@ impl Ui {
                 return;
             }
         }
+        for canvas in &mut self.canvases {
+            if canvas.id == id {
+                canvas.visible = true;
+                self.mark_dirty();
+                return;
+            }
+        }
     }
 
     pub fn hide(&mut self, id: &str) {
@@ -304,6 +710,13 @@ impl Ui {
                 return;
             }
         }
+        for canvas in &mut self.canvases {
+            if canvas.id == id {
+                canvas.visible = false;
+                self.mark_dirty();
+                return;
+            }
+        }
     }
 
     pub fn toggle(&mut self, id: &str) {
@@ -328,6 +741,61 @@ impl Ui {
                 return;
             }
         }
+        for canvas in &mut self.canvases {
+            if canvas.id == id {
+                canvas.visible = !canvas.visible;
+                self.mark_dirty();
+                return;
+            }
+        }
+    }
+
+    /// sets `opacity` of any element, clamped to 0..=1
+    pub fn set_opacity(&mut self, id: &str, opacity: f32) {
+        let opacity = opacity.clamp(0.0, 1.0);
+        if let Some(rect) = self.rects.iter_mut().find(|r| r.id == id) {
+            rect.opacity = opacity;
+        } else if let Some(text) = self.texts.iter_mut().find(|t| t.id == id) {
+            text.opacity = opacity;
+        } else if let Some(button) = self.buttons.iter_mut().find(|b| b.id == id) {
+            button.opacity = opacity;
+        } else if let Some(canvas) = self.canvases.iter_mut().find(|c| c.id == id) {
+            canvas.opacity = opacity;
+        } else {
+            return;
+        }
+        self.mark_dirty();
+    }
+
+    pub fn opacity(&self, id: &str) -> Option<f32> {
+        self.rects
+            .iter()
+            .find(|r| r.id == id)
+            .map(|r| r.opacity)
+            .or_else(|| self.texts.iter().find(|t| t.id == id).map(|t| t.opacity))
+            .or_else(|| self.buttons.iter().find(|b| b.id == id).map(|b| b.opacity))
+            .or_else(|| self.canvases.iter().find(|c| c.id == id).map(|c| c.opacity))
+    }
+
+    /// lets a disabled button be clicked again
+    pub fn enable(&mut self, id: &str) {
+        self.set_enabled(id, true);
+    }
+
+    pub fn disable(&mut self, id: &str) {
+        self.set_enabled(id, false);
+    }
+
+    fn set_enabled(&mut self, id: &str, enabled: bool) {
+        if let Some(button) = self.buttons.iter_mut().find(|b| b.id == id) {
+            button.enabled = enabled;
+            self.mark_dirty();
+        }
+    }
+
+    /// false only for disabled buttons
+    pub fn is_enabled(&self, id: &str) -> bool {
+        !self.buttons.iter().any(|b| b.id == id && !b.enabled)
     }
 
     pub fn is_visible(&self, id: &str) -> bool {
@@ -346,13 +814,51 @@ impl Ui {
                 return button.visible;
             }
         }
+        for canvas in &self.canvases {
+            if canvas.id == id {
+                return canvas.visible;
+            }
+        }
         false
     }
 
+    pub fn set_transform(&mut self, id: &str, transform: Transform) {
+        if let Some(rect) = self.rects.iter_mut().find(|r| r.id == id) {
+            rect.transform = transform;
+        } else if let Some(text) = self.texts.iter_mut().find(|t| t.id == id) {
+            text.transform = transform;
+        } else if let Some(button) = self.buttons.iter_mut().find(|b| b.id == id) {
+            button.transform = transform;
+        } else {
+            return;
+        }
+        self.mark_dirty();
+    }
+
+    /// the element transform resolved around its center, as used for
+    /// rendering and hit testing
+    pub fn screen_transform(&mut self, id: &str) -> Option<Transform> {
+        if let Some(rect) = self.rects.iter().find(|r| r.id == id) {
+            return Some(rect.transform.around(rect.x + rect.w * 0.5, rect.y + rect.h * 0.5));
+        }
+        if let Some(button) = self.buttons.iter().find(|b| b.id == id) {
+            return Some(button.transform.around(button.x + button.w * 0.5, button.y + button.h * 0.5));
+        }
+        let (text, font_id, x, y, transform) = self
+            .texts
+            .iter()
+            .find(|t| t.id == id)
+            .map(|t| (t.text.clone(), t.font_id, t.x, t.y, t.transform))?;
+        let (w, h) = self.fonts.measure(&text, font_id);
+        Some(transform.around(x + w * 0.5, y + h * 0.5))
+    }
+
     pub fn remove(&mut self, id: &str) {
         self.rects.retain(|r| r.id != id);
         self.texts.retain(|t| t.id != id);
         self.buttons.retain(|b| b.id != id);
+        self.canvases.retain(|c| c.id != id);
+        self.styles.elements.remove(id);
         self.mark_dirty();
     }
 
@@ -371,6 +877,10 @@ impl Ui {
             self.buttons.iter()
                 .find(|b| b.id == id)
                 .map(|b| unsafe { &*(b as *const Button as *const T) })
+        } else if std::any::TypeId::of::<T>() == std::any::TypeId::of::<Canvas>() {
+            self.canvases.iter()
+                .find(|c| c.id == id)
+                .map(|c| unsafe { &*(c as *const Canvas as *const T) })
         } else {
             None
         }
@@ -389,6 +899,10 @@ impl Ui {
             self.buttons.iter_mut()
                 .find(|b| b.id == id)
                 .map(|b| unsafe { &mut *(b as *mut Button as *mut T) })
+        } else if std::any::TypeId::of::<T>() == std::any::TypeId::of::<Canvas>() {
+            self.canvases.iter_mut()
+                .find(|c| c.id == id)
+                .map(|c| unsafe { &mut *(c as *mut Canvas as *mut T) })
         } else {
             None
         }
@@ -414,6 +928,18 @@ impl Ui {
                 Height::Percent(p) => window_height * p,
             };
         }
+        for canvas in &mut self.canvases {
+            canvas.w = match canvas.width_mode {
+                Width::Fixed(w) => w,
+                Width::Full => window_width,
+                Width::Percent(p) => window_width * p,
+            };
+            canvas.h = match canvas.height_mode {
+                Height::Fixed(h) => h,
+                Height::Full => window_height,
+                Height::Percent(p) => window_height * p,
+            };
+        }
         self.mark_dirty();
     }
 
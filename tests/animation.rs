@@ -0,0 +1,145 @@
+use std::time::Duration;
+
+use bento::*;
+
+/// a panel to move around, counting finished animations
+#[derive(Default)]
+struct Moving {
+    finished: u32,
+}
+
+impl BentoApp for Moving {
+    fn once(&mut self, ctx: &mut Ctx) {
+        ctx.ui.rect("panel", 0.0, 0.0, Width::Fixed(50.0), Height::Fixed(50.0), Color::BLACK, Color::TRANSPARENT, 0.0);
+    }
+
+    fn update(&mut self, ctx: &mut Ctx) {
+        if ctx.animation_finished("panel", Property::X) {
+            self.finished += 1;
+        }
+    }
+}
+
+const EASINGS: [Easing; 11] = [
+    Easing::Linear,
+    Easing::QuadIn,
+    Easing::QuadOut,
+    Easing::QuadInOut,
+    Easing::CubicIn,
+    Easing::CubicOut,
+    Easing::CubicInOut,
+    Easing::SPRING,
+    Easing::Spring { damping: 0.1 },
+    Easing::Spring { damping: 2.0 },
+    Easing::Bounce,
+];
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+fn harness() -> Harness<Moving> {
+    Harness::new(Moving::default(), 300.0, 200.0)
+}
+
+fn x(h: &Harness<Moving>) -> f32 {
+    h.get::<Rect>("panel").unwrap().x
+}
+
+fn slide(h: &mut Harness<Moving>, to: f32) {
+    assert!(h.ctx_mut().animate("panel", Property::X, to, ms(1000), Easing::Linear));
+}
+
+#[test]
+fn easings_start_at_0_and_end_at_1() {
+    for easing in EASINGS {
+        assert!(easing.apply(0.0).abs() < 1e-6, "{easing:?}");
+        assert_eq!(easing.apply(1.0), 1.0, "{easing:?}");
+        // progress outside 0..=1 is clamped
+        assert_eq!(easing.apply(-1.0), easing.apply(0.0), "{easing:?}");
+        assert_eq!(easing.apply(2.0), 1.0, "{easing:?}");
+    }
+    assert_eq!(Easing::QuadInOut.apply(0.5), 0.5);
+    assert_eq!(Easing::CubicInOut.apply(0.5), 0.5);
+}
+
+#[test]
+fn springs_settle_by_the_end() {
+    let samples = |easing: Easing| (0..=100).map(move |i| easing.apply(i as f32 / 100.0));
+
+    // underdamped springs overshoot, then settle
+    assert!(samples(Easing::SPRING).any(|v| v > 1.01));
+    for easing in EASINGS.into_iter().filter(|e| matches!(e, Easing::Spring { .. })) {
+        assert!((easing.apply(0.99) - 1.0).abs() < 0.01, "{easing:?}");
+    }
+    // stiff ones never do
+    let stiff: Vec<f32> = samples(Easing::Spring { damping: 2.0 }).collect();
+    assert!(stiff.windows(2).all(|w| w[0] <= w[1] && w[1] <= 1.0));
+}
+
+#[test]
+fn tweens_follow_the_clock() {
+    let mut h = harness();
+    slide(&mut h, 100.0);
+    assert!(h.ctx().is_animating("panel"));
+
+    h.advance(ms(250));
+    assert!((x(&h) - 25.0).abs() < 0.01, "{}", x(&h));
+    h.advance(ms(500));
+    assert!((x(&h) - 75.0).abs() < 0.01, "{}", x(&h));
+}
+
+#[test]
+fn finished_tweens_land_on_the_target_and_report_once() {
+    let mut h = harness();
+    slide(&mut h, 100.0);
+    h.advance(ms(600)).advance(ms(600));
+
+    assert_eq!(x(&h), 100.0);
+    assert_eq!(h.app.finished, 1);
+    assert!(!h.ctx().is_animating("panel"));
+    h.advance(ms(100));
+    assert_eq!(h.app.finished, 1);
+}
+
+#[test]
+fn retargeting_starts_from_the_current_value() {
+    let mut h = harness();
+    slide(&mut h, 100.0);
+    h.advance(ms(500));
+    slide(&mut h, 0.0);
+    h.advance(ms(500));
+
+    // halfway from 50 back to 0
+    assert!((x(&h) - 25.0).abs() < 0.01, "{}", x(&h));
+    h.advance(ms(600));
+    assert_eq!(x(&h), 0.0);
+    assert_eq!(h.app.finished, 1);
+}
+
+#[test]
+fn stopped_tweens_stay_put_without_finishing() {
+    let mut h = harness();
+    slide(&mut h, 100.0);
+    h.advance(ms(500));
+    h.ctx_mut().stop_animation("panel", Property::X);
+    h.advance(ms(1000));
+
+    assert!((x(&h) - 50.0).abs() < 0.01, "{}", x(&h));
+    assert_eq!(h.app.finished, 0);
+}
+
+#[test]
+fn missing_elements_and_properties_dont_animate() {
+    let mut h = harness();
+    assert!(!h.ctx_mut().animate("nope", Property::X, 1.0, ms(100), Easing::Linear));
+    assert!(!h.ctx_mut().animate("panel", Property::TextColor, Color::RED, ms(100), Easing::Linear));
+    assert!(!h.ctx().is_animating("panel"));
+}
+
+#[test]
+#[should_panic(expected = "can't animate")]
+fn the_wrong_kind_of_value_panics() {
+    let mut h = harness();
+    h.ctx_mut().animate("panel", Property::X, Color::RED, ms(100), Easing::Linear);
+}
@@ -0,0 +1,89 @@
+use bento::*;
+use winit::keyboard::KeyCode;
+
+/// counts the edit commands it sees fire
+#[derive(Default)]
+struct Editor {
+    copies: u32,
+    pastes: u32,
+}
+
+impl BentoApp for Editor {
+    fn once(&mut self, _ctx: &mut Ctx) {}
+
+    fn update(&mut self, ctx: &mut Ctx) {
+        if ctx.commands.triggered(COPY_COMMAND) {
+            self.copies += 1;
+        }
+        if ctx.commands.triggered(PASTE_COMMAND) {
+            self.pastes += 1;
+        }
+    }
+}
+
+/// a backend that only does text
+#[derive(Default)]
+struct TextOnly(Option<String>);
+
+impl ClipboardBackend for TextOnly {
+    fn get_text(&mut self) -> Result<Option<String>, ClipboardError> {
+        Ok(self.0.clone())
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<(), ClipboardError> {
+        self.0 = Some(text.to_string());
+        Ok(())
+    }
+}
+
+#[test]
+fn memory_clipboards_hold_one_kind_of_data() {
+    let mut clipboard = MemoryClipboard::default();
+    assert_eq!(clipboard.get_text().unwrap(), None);
+
+    clipboard.set_text("hello").unwrap();
+    assert_eq!(clipboard.get_text().unwrap().as_deref(), Some("hello"));
+
+    let image = RgbaImage::new(2, 2);
+    clipboard.set_image(&image).unwrap();
+    assert_eq!(clipboard.get_text().unwrap(), None);
+    assert_eq!(clipboard.get_image().unwrap().map(|i| (i.width, i.height)), Some((2, 2)));
+
+    clipboard.set_html("<b>hi</b>", "hi").unwrap();
+    assert_eq!(clipboard.get_html().unwrap().as_deref(), Some("<b>hi</b>"));
+    assert_eq!(clipboard.get_text().unwrap().as_deref(), Some("hi"));
+    assert!(clipboard.get_image().unwrap().is_none());
+}
+
+#[test]
+fn clipboards_go_through_their_backend() {
+    let mut clipboard = Clipboard::memory();
+    clipboard.set_text("first").unwrap();
+    assert_eq!(clipboard.get_text().unwrap().as_deref(), Some("first"));
+
+    clipboard.set_backend(TextOnly(Some("other".to_string())));
+    assert_eq!(clipboard.get_text().unwrap().as_deref(), Some("other"));
+    assert!(matches!(clipboard.get_image(), Err(ClipboardError::Unsupported)));
+    assert!(matches!(clipboard.set_html("<b>x</b>", "x"), Err(ClipboardError::Unsupported)));
+}
+
+#[test]
+fn harness_contexts_use_a_memory_clipboard() {
+    let mut h = Harness::new(Editor::default(), 200.0, 200.0);
+    h.ctx_mut().clipboard.set_text("copied").unwrap();
+    assert_eq!(h.ctx_mut().clipboard.get_text().unwrap().as_deref(), Some("copied"));
+}
+
+#[test]
+fn edit_commands_are_opt_in() {
+    let mut h = Harness::new(Editor::default(), 200.0, 200.0);
+    assert!(h.ctx().commands.get(COPY_COMMAND).is_none());
+    assert!(h.ctx().commands.conflicts().is_empty());
+    h.chord(&[KeyCode::ControlLeft, KeyCode::KeyC]).frame();
+    assert_eq!(h.app.copies, 0);
+
+    h.ctx_mut().commands.register_edit_commands();
+    h.chord(&[KeyCode::ControlLeft, KeyCode::KeyC]).frame();
+    h.chord(&[KeyCode::ControlLeft, KeyCode::KeyV]).frame();
+    assert_eq!((h.app.copies, h.app.pastes), (1, 1));
+}
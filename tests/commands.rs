@@ -0,0 +1,140 @@
+use std::path::PathBuf;
+
+use bento::*;
+use winit::keyboard::KeyCode;
+
+/// registers a few commands and remembers which fired
+#[derive(Default)]
+struct Editor {
+    fired: Vec<String>,
+}
+
+impl BentoApp for Editor {
+    fn once(&mut self, ctx: &mut Ctx) {
+        ctx.commands.register("file.save", "Save", &["Ctrl+S"]);
+        ctx.commands.register("file.save_all", "Save All", &["Ctrl+K Ctrl+S"]);
+        ctx.commands.register("file.print", "Print", &["Ctrl+P"]);
+    }
+
+    fn update(&mut self, ctx: &mut Ctx) {
+        let commands = &ctx.commands;
+        let fired = commands.iter().filter(|c| commands.triggered(&c.id));
+        self.fired.extend(fired.map(|c| c.id.clone()));
+    }
+}
+
+fn harness() -> Harness<Editor> {
+    Harness::new(Editor::default(), 200.0, 200.0)
+}
+
+fn ctrl(h: &mut Harness<Editor>, key: KeyCode) {
+    h.chord(&[KeyCode::ControlLeft, key]);
+}
+
+fn bindings(h: &Harness<Editor>, id: &str) -> Vec<String> {
+    let command = h.ctx().commands.get(id).unwrap();
+    command.bindings.iter().map(|b| b.to_string()).collect()
+}
+
+/// a fresh file path per test so they can run in parallel
+fn temp_file(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("bento-commands-{}-{name}.json", std::process::id()))
+}
+
+#[test]
+fn chords_fire_after_their_last_stroke() {
+    let mut h = harness();
+    ctrl(&mut h, KeyCode::KeyK);
+    assert!(h.ctx().commands.chord_pending());
+    assert_eq!(h.ctx().commands.pending_text(), "Ctrl+K");
+    assert!(h.app.fired.is_empty());
+
+    ctrl(&mut h, KeyCode::KeyS);
+    assert_eq!(h.app.fired, ["file.save_all"]);
+    assert!(!h.ctx().commands.chord_pending());
+}
+
+#[test]
+fn a_broken_chord_retries_its_last_stroke() {
+    let mut h = harness();
+    ctrl(&mut h, KeyCode::KeyK);
+    ctrl(&mut h, KeyCode::KeyP);
+
+    assert_eq!(h.app.fired, ["file.print"]);
+    assert!(!h.ctx().commands.chord_pending());
+}
+
+#[test]
+fn the_newest_command_wins_a_shared_binding() {
+    let mut h = harness();
+    h.ctx_mut().commands.register("app.save", "Save Project", &["Ctrl+S"]);
+    ctrl(&mut h, KeyCode::KeyS);
+
+    assert_eq!(h.app.fired, ["app.save"]);
+}
+
+#[test]
+fn conflicts_list_shared_bindings_and_prefixes() {
+    let mut h = harness();
+    h.ctx_mut().commands.register("app.save", "Save Project", &["Ctrl+S"]);
+    h.ctx_mut().commands.register("app.kill", "Kill", &["Ctrl+K"]);
+    let conflicts = h.ctx().commands.conflicts();
+
+    assert_eq!(
+        conflicts,
+        [
+            BindingConflict {
+                binding: "Ctrl+K".to_string(),
+                commands: vec!["file.save_all".to_string(), "app.kill".to_string()],
+            },
+            BindingConflict {
+                binding: "Ctrl+S".to_string(),
+                commands: vec!["file.save".to_string(), "app.save".to_string()],
+            },
+        ]
+    );
+}
+
+#[test]
+fn saved_bindings_load_back() {
+    let path = temp_file("round-trip");
+    let mut h = harness();
+    h.ctx_mut().commands.rebind("file.save", &["Ctrl+Shift+S", "F2"]);
+    h.ctx().commands.save(&path).unwrap();
+    // only changed bindings are saved
+    let saved = std::fs::read_to_string(&path).unwrap();
+    assert!(!saved.contains("file.print"));
+
+    h.ctx_mut().commands.reset_all();
+    assert_eq!(bindings(&h, "file.save"), ["Ctrl+S"]);
+    h.ctx_mut().commands.load(&path).unwrap();
+    assert_eq!(bindings(&h, "file.save"), ["Ctrl+Shift+S", "F2"]);
+    assert_eq!(bindings(&h, "file.print"), ["Ctrl+P"]);
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn loading_skips_unknown_commands() {
+    let path = temp_file("unknown");
+    std::fs::write(&path, r#"{ "gone": ["Ctrl+G"], "file.print": ["Ctrl+Alt+P"] }"#).unwrap();
+    let mut h = harness();
+    h.ctx_mut().commands.load(&path).unwrap();
+
+    assert_eq!(bindings(&h, "file.print"), ["Ctrl+Alt+P"]);
+    assert!(h.ctx().commands.get("gone").is_none());
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn a_bad_binding_loads_nothing() {
+    let path = temp_file("bad");
+    // entries apply in id order, the good one comes first
+    std::fs::write(&path, r#"{ "file.print": ["Ctrl+Alt+P"], "file.save": ["Ctrl+Banana"] }"#).unwrap();
+    let mut h = harness();
+    let error = h.ctx_mut().commands.load(&path).unwrap_err();
+
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!(bindings(&h, "file.print"), ["Ctrl+P"]);
+    assert_eq!(bindings(&h, "file.save"), ["Ctrl+S"]);
+    let _ = std::fs::remove_file(path);
+}
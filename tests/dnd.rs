@@ -0,0 +1,74 @@
+use std::path::PathBuf;
+
+use bento::*;
+
+/// a file bin on the left, remembering what was dropped where
+#[derive(Default)]
+struct Files {
+    dropped: Vec<(PathBuf, f32, f32)>,
+    on_bin: Vec<PathBuf>,
+}
+
+impl BentoApp for Files {
+    fn once(&mut self, ctx: &mut Ctx) {
+        ctx.ui.rect("bin", 0.0, 0.0, Width::Fixed(100.0), Height::Fixed(100.0), Color::BLACK, Color::TRANSPARENT, 0.0);
+        ctx.accept_files("bin");
+    }
+
+    fn update(&mut self, ctx: &mut Ctx) {
+        if let Some((path, x, y)) = ctx.dropped_file() {
+            self.dropped.push((path.to_path_buf(), x, y));
+        }
+        if let Some(path) = ctx.dropped_file_on("bin") {
+            self.on_bin.push(path.to_path_buf());
+        }
+    }
+}
+
+fn harness() -> Harness<Files> {
+    Harness::new(Files::default(), 300.0, 200.0)
+}
+
+#[test]
+fn dropped_files_reach_the_target_under_the_cursor() {
+    let mut h = harness();
+    h.drop_files(50.0, 50.0, &["a.txt", "b.txt"]);
+
+    assert_eq!(h.app.on_bin, [PathBuf::from("a.txt"), PathBuf::from("b.txt")]);
+    assert_eq!(h.app.dropped[0], (PathBuf::from("a.txt"), 50.0, 50.0));
+    assert!(h.ctx().hovered_files().is_empty());
+    assert!(!h.ctx().is_file_hovered("bin"));
+}
+
+#[test]
+fn hovering_files_highlights_the_target() {
+    let mut h = harness();
+    h.move_mouse(50.0, 50.0).send(InputEvent::FileHovered("a.txt".into()));
+    assert!(h.ctx().is_file_hovered("bin"));
+    assert_eq!(h.ctx().hovered_files(), [PathBuf::from("a.txt")]);
+
+    h.move_mouse(200.0, 50.0);
+    assert!(!h.ctx().is_file_hovered("bin"));
+    h.send(InputEvent::FileHoverCancelled);
+    assert!(h.ctx().hovered_files().is_empty());
+    assert!(h.app.dropped.is_empty());
+}
+
+#[test]
+fn drops_without_a_hover_still_find_their_target() {
+    let mut h = harness();
+    h.move_mouse(50.0, 50.0).send(InputEvent::FileDropped("a.txt".into()));
+
+    assert_eq!(h.app.on_bin, [PathBuf::from("a.txt")]);
+    assert_eq!(h.app.dropped, [(PathBuf::from("a.txt"), 50.0, 50.0)]);
+}
+
+#[test]
+fn drops_land_where_the_cursor_was_last_seen() {
+    let mut h = harness();
+    // no cursor moves during the os drag, so the drop uses the old position
+    h.move_mouse(200.0, 50.0).send(InputEvent::FileDropped("a.txt".into()));
+
+    assert_eq!(h.app.dropped, [(PathBuf::from("a.txt"), 200.0, 50.0)]);
+    assert!(h.app.on_bin.is_empty());
+}
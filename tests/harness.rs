@@ -0,0 +1,154 @@
+use bento::*;
+use winit::keyboard::KeyCode;
+
+/// a counter with a button, a label showing the count and a save shortcut
+#[derive(Default)]
+struct Counter {
+    count: u32,
+    saved: u32,
+    typed: String,
+}
+
+impl BentoApp for Counter {
+    fn once(&mut self, ctx: &mut Ctx) {
+        let font = ctx.ui.font(FontRole::Body);
+        let color = ctx.ui.theme().text;
+        ctx.ui.button("inc", "Add", 20.0, 20.0);
+        ctx.ui.text("label", "0", font, 20.0, 80.0, color);
+        ctx.commands.register("app.save", "Save", &["Ctrl+S"]);
+    }
+
+    fn update(&mut self, ctx: &mut Ctx) {
+        if ctx.is_clicked("inc") {
+            self.count += 1;
+            ctx.ui.get_mut::<Text>("label").unwrap().text = self.count.to_string();
+            ctx.mark_dirty();
+        }
+        if ctx.command("app.save") {
+            self.saved += 1;
+        }
+        self.typed.push_str(&ctx.input.text);
+    }
+}
+
+#[test]
+fn clicking_a_button_updates_the_app_and_the_ui() {
+    let mut h = Harness::new(Counter::default(), 300.0, 200.0);
+    h.click_element("inc").click_element("inc");
+
+    assert_eq!(h.app.count, 2);
+    assert_eq!(h.get::<Text>("label").unwrap().text, "2");
+    h.frame();
+    assert!(h.drawn_text().contains(&"2".to_string()));
+}
+
+#[test]
+fn clicks_outside_an_element_miss_it() {
+    let mut h = Harness::new(Counter::default(), 300.0, 200.0);
+    h.click(250.0, 150.0);
+
+    assert_eq!(h.app.count, 0);
+    assert!(!h.is_hovered("inc"));
+}
+
+#[test]
+fn hover_follows_the_cursor() {
+    let mut h = Harness::new(Counter::default(), 300.0, 200.0);
+    let (x, y, w, h_) = h.bounds("inc").unwrap();
+
+    h.move_mouse(x + w * 0.5, y + h_ * 0.5);
+    assert!(h.is_hovered("inc"));
+    h.move_mouse(x + w + 10.0, y);
+    assert!(!h.is_hovered("inc"));
+}
+
+#[test]
+fn keys_and_text_reach_the_app() {
+    let mut h = Harness::new(Counter::default(), 300.0, 200.0);
+    h.chord(&[KeyCode::ControlLeft, KeyCode::KeyS]).type_text("hi");
+
+    assert_eq!(h.app.saved, 1);
+    assert_eq!(h.app.typed, "hi");
+}
+
+#[test]
+fn events_keep_their_order_until_the_frame() {
+    let mut h = Harness::new(Counter::default(), 300.0, 200.0);
+    h.click(250.0, 150.0).type_text("a");
+
+    let events = &h.ctx().events;
+    assert!(matches!(events[0], InputEvent::CursorMoved { .. }));
+    assert!(matches!(events[1], InputEvent::MouseButton { pressed: true, .. }));
+    assert!(matches!(events[2], InputEvent::MouseButton { pressed: false, .. }));
+    assert_eq!(events[3], InputEvent::Text("a".to_string()));
+    h.frame();
+    assert!(h.ctx().events.is_empty());
+}
+
+#[test]
+#[ignore = "needs a gpu adapter, run with --ignored"]
+fn rendering_shows_the_theme_background() {
+    let mut h = Harness::with_gpu(Counter::default(), 300, 200, 1.0).unwrap();
+    let image = h.render().unwrap();
+    // the clear color is linear, the readback srgb
+    let srgb = |c: f32| 1.055 * c.powf(1.0 / 2.4) - 0.055;
+    let [r, g, b, _] = Theme::dark().background.to_array();
+    let expected = [r, g, b].map(|c| (srgb(c) * 255.0).round() as i32);
+    let pixel = image.pixel(290, 190);
+
+    assert_eq!((image.width, image.height), (300, 200));
+    for (actual, expected) in pixel.iter().zip(expected) {
+        assert!((*actual as i32 - expected).abs() <= 1, "{pixel:?}");
+    }
+}
+
+/// two overlapping squares faded together in one group
+struct Faded;
+
+impl BentoApp for Faded {
+    fn once(&mut self, _ctx: &mut Ctx) {}
+
+    fn update(&mut self, ctx: &mut Ctx) {
+        ctx.ui.push_opacity(0.5);
+        ctx.ui.rounded_rect(20.0, 20.0, 60.0, 60.0, 0.0, Color::WHITE.to_array(), [0.0; 4], 0.0);
+        ctx.ui.rounded_rect(50.0, 50.0, 60.0, 60.0, 0.0, Color::WHITE.to_array(), [0.0; 4], 0.0);
+        ctx.ui.pop_opacity();
+    }
+}
+
+#[test]
+#[ignore = "needs a gpu adapter, run with --ignored"]
+fn faded_groups_dont_show_through_themselves() {
+    let mut h = Harness::with_gpu(Faded, 200, 200, 1.0).unwrap();
+    let image = h.render().unwrap();
+    let (single, overlap, outside) = (image.pixel(30, 30), image.pixel(65, 65), image.pixel(150, 150));
+
+    assert_eq!(single, overlap);
+    assert!(single[0] > outside[0] + 50, "{single:?} {outside:?}");
+}
+
+/// a faded white canvas with a white square drawn into it
+struct FadedCanvas;
+
+impl BentoApp for FadedCanvas {
+    fn once(&mut self, ctx: &mut Ctx) {
+        ctx.ui.canvas("board", 20.0, 20.0, Width::Fixed(100.0), Height::Fixed(100.0), Color::WHITE);
+        ctx.ui.get_mut::<Canvas>("board").unwrap().opacity = 0.5;
+    }
+
+    fn update(&mut self, ctx: &mut Ctx) {
+        ctx.ui.draw_canvas("board", |ui| {
+            ui.rounded_rect(40.0, 40.0, 40.0, 40.0, 0.0, Color::WHITE.to_array(), [0.0; 4], 0.0);
+        });
+    }
+}
+
+#[test]
+#[ignore = "needs a gpu adapter, run with --ignored"]
+fn faded_canvases_fade_with_their_content() {
+    let mut h = Harness::with_gpu(FadedCanvas, 200, 200, 1.0).unwrap();
+    let image = h.render().unwrap();
+    let (background, content) = (image.pixel(30, 30), image.pixel(80, 80));
+
+    assert_eq!(background, content);
+}
@@ -0,0 +1,115 @@
+use bento::*;
+use winit::keyboard::{Key, KeyCode, NamedKey};
+
+/// remembers every shortcut it watches and every command that fired
+#[derive(Default)]
+struct Shortcuts {
+    fired: Vec<&'static str>,
+    commands: Vec<String>,
+}
+
+const WATCHED: [&str; 6] = ["Ctrl+Z", "Ctrl++", "Ctrl+/", "Ctrl+?", "Ctrl+Shift+=", "Ctrl+1"];
+
+impl BentoApp for Shortcuts {
+    fn once(&mut self, _ctx: &mut Ctx) {}
+
+    fn update(&mut self, ctx: &mut Ctx) {
+        for shortcut in WATCHED {
+            if ctx.input.shortcut(shortcut) {
+                self.fired.push(shortcut);
+            }
+        }
+        let commands = &ctx.commands;
+        let fired = commands.iter().filter(|c| commands.triggered(&c.id));
+        self.commands.extend(fired.map(|c| c.id.clone()));
+    }
+}
+
+fn harness() -> Harness<Shortcuts> {
+    Harness::new(Shortcuts::default(), 200.0, 200.0)
+}
+
+/// presses `code` producing `logical` with `held` down, then lets go of everything
+fn press(h: &mut Harness<Shortcuts>, held: &[KeyCode], code: KeyCode, logical: &str) -> Vec<&'static str> {
+    h.app.fired.clear();
+    for key in held {
+        h.key_down(*key);
+    }
+    h.key_down_logical(code, Key::Character(logical.into())).key_up(code);
+    for key in held.iter().rev() {
+        h.key_up(*key);
+    }
+    std::mem::take(&mut h.app.fired)
+}
+
+#[test]
+fn shortcuts_parse_and_print_canonically() {
+    let save = Shortcut::parse("shift+control+s").unwrap();
+    assert_eq!(save.modifiers, Modifiers { shift: true, ctrl: true, ..Modifiers::NONE });
+    assert_eq!(save.to_string(), "Ctrl+Shift+S");
+
+    assert_eq!(Shortcut::parse("Ctrl++").unwrap().to_string(), "Ctrl++");
+    assert_eq!(Shortcut::parse("Alt+Enter").unwrap().to_string(), "Alt+Enter");
+    assert_eq!(Shortcut::parse("Space").unwrap().modifiers, Modifiers::NONE);
+}
+
+#[test]
+fn malformed_shortcuts_are_errors() {
+    for text in ["Ctrl+", "Hyper+S", "Ctrl+Banana", ""] {
+        assert!(Shortcut::parse(text).is_err(), "{text}");
+    }
+}
+
+#[test]
+fn letters_follow_the_layout() {
+    let mut h = harness();
+    // the key labelled z on azerty sits where w is on qwerty
+    assert_eq!(press(&mut h, &[KeyCode::ControlLeft], KeyCode::KeyW, "z"), ["Ctrl+Z"]);
+    assert_eq!(press(&mut h, &[KeyCode::ControlLeft], KeyCode::KeyZ, "w"), [] as [&str; 0]);
+    // shift is part of a letter shortcut
+    let shifted = press(&mut h, &[KeyCode::ControlLeft, KeyCode::ShiftLeft], KeyCode::KeyW, "Z");
+    assert_eq!(shifted, [] as [&str; 0]);
+}
+
+#[test]
+fn letters_fall_back_to_the_physical_key() {
+    let mut h = harness();
+    // cyrillic layouts still reach latin shortcuts by position
+    assert_eq!(press(&mut h, &[KeyCode::ControlLeft], KeyCode::KeyZ, "я"), ["Ctrl+Z"]);
+}
+
+#[test]
+fn shifted_symbols_ignore_shift() {
+    let mut h = harness();
+    let ctrl_shift = [KeyCode::ControlLeft, KeyCode::ShiftLeft];
+    // plus is shift+= on us keyboards, which also still is "Ctrl+Shift+="
+    assert_eq!(press(&mut h, &ctrl_shift, KeyCode::Equal, "+"), ["Ctrl++", "Ctrl+Shift+="]);
+    assert_eq!(press(&mut h, &ctrl_shift, KeyCode::Slash, "?"), ["Ctrl+?"]);
+    // slash is shift+7 on german keyboards, one is shift+& on french ones
+    assert_eq!(press(&mut h, &ctrl_shift, KeyCode::Digit7, "/"), ["Ctrl+/"]);
+    assert_eq!(press(&mut h, &ctrl_shift, KeyCode::Digit1, "1"), ["Ctrl+1"]);
+    // other modifiers still have to match
+    let alt = [KeyCode::ControlLeft, KeyCode::AltLeft];
+    assert_eq!(press(&mut h, &alt, KeyCode::Slash, "/"), [] as [&str; 0]);
+}
+
+#[test]
+fn commands_fire_on_shifted_symbols() {
+    let mut h = harness();
+    h.ctx_mut().commands.register("zoom.in", "Zoom In", &["Ctrl++"]);
+    h.key_down(KeyCode::ControlLeft).key_down(KeyCode::ShiftLeft);
+    h.key_down_logical(KeyCode::Equal, Key::Character("+".into()));
+
+    assert_eq!(h.app.commands, ["zoom.in"]);
+}
+
+#[test]
+fn named_keys_match_without_a_logical_key() {
+    let mut h = harness();
+    h.ctx_mut().commands.register("app.run", "Run", &["Ctrl+Enter"]);
+    h.key_down(KeyCode::ControlLeft).key_down(KeyCode::Enter).key_up(KeyCode::Enter);
+    assert_eq!(h.app.commands, ["app.run"]);
+
+    h.key_down_logical(KeyCode::NumpadEnter, Key::Named(NamedKey::Enter));
+    assert_eq!(h.app.commands, ["app.run", "app.run"]);
+}
@@ -0,0 +1,136 @@
+use bento::*;
+use winit::keyboard::KeyCode;
+
+/// twelve commands, more than the palette shows at once
+#[derive(Default)]
+struct Editor {
+    fired: Vec<String>,
+}
+
+const COMMANDS: [(&str, &str); 12] = [
+    ("file.save", "Save File"),
+    ("file.save_all", "Save All"),
+    ("window.close", "Close Window"),
+    ("view.sidebar", "Toggle Sidebar"),
+    ("view.zoom_in", "Zoom In"),
+    ("view.zoom_out", "Zoom Out"),
+    ("edit.undo", "Undo"),
+    ("edit.redo", "Redo"),
+    ("edit.find", "Find"),
+    ("edit.replace", "Replace"),
+    ("go.line", "Go to Line"),
+    ("go.file", "Go to File"),
+];
+
+impl BentoApp for Editor {
+    fn once(&mut self, ctx: &mut Ctx) {
+        for (id, title) in COMMANDS {
+            ctx.commands.register(id, title, &[]);
+        }
+    }
+
+    fn update(&mut self, ctx: &mut Ctx) {
+        let commands = &ctx.commands;
+        let fired = commands.iter().filter(|c| commands.triggered(&c.id));
+        self.fired.extend(fired.map(|c| c.id.clone()));
+    }
+}
+
+fn harness() -> Harness<Editor> {
+    let mut h = Harness::new(Editor::default(), 800.0, 600.0);
+    h.ctx_mut().palette.open();
+    h
+}
+
+fn titles(h: &Harness<Editor>) -> Vec<String> {
+    let ctx = h.ctx();
+    ctx.palette.items(&ctx.commands).into_iter().map(|item| item.title).collect()
+}
+
+fn press(h: &mut Harness<Editor>, key: KeyCode, times: usize) -> usize {
+    for _ in 0..times {
+        h.key_down(key).key_up(key);
+    }
+    h.ctx().palette.selected
+}
+
+#[test]
+fn word_starts_rank_first() {
+    let mut h = harness();
+    h.type_text("sa");
+    let titles = titles(&h);
+
+    // ties keep registration order, commands without the letters drop out
+    assert_eq!(titles[..2], ["Save File", "Save All"]);
+    assert!(titles.contains(&"Toggle Sidebar".to_string()));
+    assert!(!titles.contains(&"Close Window".to_string()));
+    assert!(fuzzy_score("gf", "Go to File") > fuzzy_score("gf", "Bigfoot"));
+    assert_eq!(fuzzy_score("xyz", "Save File"), None);
+}
+
+#[test]
+fn the_palette_lists_itself_nowhere() {
+    let h = harness();
+    let ctx = h.ctx();
+    let items = ctx.palette.items(&ctx.commands);
+
+    assert_eq!(items.len(), COMMANDS.len());
+    assert!(items.iter().all(|item| item.id != PALETTE_COMMAND));
+}
+
+#[test]
+fn recent_commands_come_first() {
+    let mut h = harness();
+    h.type_text("close");
+    press(&mut h, KeyCode::Enter, 1);
+    h.ctx_mut().palette.open();
+    h.type_text("redo");
+    press(&mut h, KeyCode::Enter, 1);
+
+    assert_eq!(h.app.fired, ["window.close", "edit.redo"]);
+    assert!(!h.ctx().palette.is_open());
+    h.ctx_mut().palette.open();
+    assert_eq!(titles(&h)[..3], ["Redo", "Close Window", "Save File"]);
+}
+
+#[test]
+fn arrows_wrap_around() {
+    let mut h = harness();
+    assert_eq!(press(&mut h, KeyCode::ArrowUp, 1), 11);
+    assert_eq!(press(&mut h, KeyCode::ArrowDown, 1), 0);
+    assert_eq!(press(&mut h, KeyCode::ArrowDown, 2), 2);
+}
+
+#[test]
+fn paging_stops_at_the_ends() {
+    let mut h = harness();
+    assert_eq!(press(&mut h, KeyCode::PageUp, 1), 0);
+    assert_eq!(press(&mut h, KeyCode::PageDown, 1), 8);
+    assert_eq!(press(&mut h, KeyCode::PageDown, 1), 11);
+    assert_eq!(press(&mut h, KeyCode::PageDown, 1), 11);
+    assert_eq!(press(&mut h, KeyCode::PageUp, 1), 3);
+    assert_eq!(press(&mut h, KeyCode::PageUp, 1), 0);
+}
+
+#[test]
+fn enter_runs_the_selection_and_escape_closes() {
+    let mut h = harness();
+    press(&mut h, KeyCode::ArrowDown, 1);
+    press(&mut h, KeyCode::Enter, 1);
+    assert_eq!(h.app.fired, ["file.save_all"]);
+
+    h.ctx_mut().palette.open();
+    press(&mut h, KeyCode::Escape, 1);
+    assert!(!h.ctx().palette.is_open());
+    assert_eq!(h.app.fired, ["file.save_all"]);
+}
+
+#[test]
+fn typing_resets_the_selection() {
+    let mut h = harness();
+    press(&mut h, KeyCode::PageDown, 1);
+    h.type_text("z");
+
+    assert_eq!(h.ctx().palette.selected, 0);
+    assert_eq!(titles(&h)[..2], ["Zoom In", "Zoom Out"]);
+}
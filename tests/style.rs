@@ -0,0 +1,101 @@
+use bento::*;
+
+/// a plain black panel and a button to style
+struct Styled;
+
+impl BentoApp for Styled {
+    fn once(&mut self, ctx: &mut Ctx) {
+        ctx.ui.rect("panel", 0.0, 0.0, Width::Fixed(50.0), Height::Fixed(50.0), Color::BLACK, Color::TRANSPARENT, 0.0);
+        ctx.ui.button("btn", "Go", 100.0, 0.0);
+        ctx.ui.define_style("red", Style::new().background(Color::RED));
+        ctx.ui.define_style("blue", Style::new().background(Color::BLUE).border(Color::WHITE, 2.0));
+    }
+
+    fn update(&mut self, _ctx: &mut Ctx) {}
+}
+
+fn harness() -> Harness<Styled> {
+    Harness::new(Styled, 300.0, 200.0)
+}
+
+fn panel(h: &Harness<Styled>) -> &Rect {
+    h.get::<Rect>("panel").unwrap()
+}
+
+#[test]
+fn later_classes_win() {
+    let mut h = harness();
+    let ui = &mut h.ctx_mut().ui;
+    ui.add_class("panel", "blue");
+    ui.add_class("panel", "red");
+    assert_eq!(panel(&h).color, Color::RED);
+    // the border only comes from blue
+    assert_eq!(panel(&h).outline_color, Color::WHITE);
+
+    h.ctx_mut().ui.remove_class("panel", "red");
+    assert_eq!(panel(&h).color, Color::BLUE);
+    assert_eq!(h.ctx().ui.classes("panel"), ["blue"]);
+}
+
+#[test]
+fn inline_styles_beat_classes() {
+    let mut h = harness();
+    let ui = &mut h.ctx_mut().ui;
+    ui.set_style("panel", Style::new().background(Color::GREEN));
+    ui.add_class("panel", "red");
+
+    assert_eq!(panel(&h).color, Color::GREEN);
+}
+
+#[test]
+fn removing_every_class_restores_the_base() {
+    let mut h = harness();
+    let ui = &mut h.ctx_mut().ui;
+    ui.add_class("panel", "blue");
+    ui.remove_class("panel", "blue");
+
+    let panel = panel(&h);
+    assert_eq!(panel.color, Color::BLACK);
+    assert_eq!(panel.outline_color, Color::TRANSPARENT);
+    assert_eq!(panel.outline_thickness, 0.0);
+}
+
+#[test]
+fn direct_changes_survive_a_restyle() {
+    let mut h = harness();
+    h.ctx_mut().ui.add_class("panel", "red");
+    let rect = h.ctx_mut().ui.get_mut::<Rect>("panel").unwrap();
+    rect.outline_color = Color::GREEN;
+    rect.color = Color::WHITE;
+
+    // redefining the class applies it again
+    h.ctx_mut().ui.define_style("red", Style::new().background(Color::RED));
+    assert_eq!(panel(&h).outline_color, Color::GREEN);
+    assert_eq!(panel(&h).color, Color::RED);
+
+    // the changed background became the base the class covers
+    h.ctx_mut().ui.remove_class("panel", "red");
+    assert_eq!(panel(&h).color, Color::WHITE);
+    assert_eq!(panel(&h).outline_color, Color::GREEN);
+}
+
+#[test]
+fn redefining_a_class_restyles_its_elements() {
+    let mut h = harness();
+    h.ctx_mut().ui.add_class("btn", "red");
+    assert_eq!(h.get::<Button>("btn").unwrap().bg_color, Color::RED);
+
+    h.ctx_mut().ui.define_style("red", Style::new().background(Color::BLUE).padding(20.0));
+    let button = h.get::<Button>("btn").unwrap();
+    assert_eq!(button.bg_color, Color::BLUE);
+    assert_eq!(button.padding, 20.0);
+}
+
+#[test]
+fn merging_keeps_what_the_other_style_leaves_out() {
+    let mut style = Style::new().background(Color::RED).radius(4.0);
+    style.merge(&Style::new().radius(8.0));
+
+    assert_eq!(style.background, Some(Color::RED));
+    assert_eq!(style.radius, Some(8.0));
+}
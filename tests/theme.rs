@@ -0,0 +1,95 @@
+use bento::*;
+
+/// a button, a themed panel and a rect the app colored itself
+struct Themed;
+
+impl BentoApp for Themed {
+    fn once(&mut self, ctx: &mut Ctx) {
+        let text = ctx.ui.theme().text;
+        ctx.ui.button("btn", "Go", 20.0, 20.0);
+        ctx.ui.rect("panel", 0.0, 100.0, Width::Fixed(50.0), Height::Fixed(50.0), Color::BLACK, Color::TRANSPARENT, 0.0);
+        ctx.ui.set_theme_color("panel", ThemeToken::Surface);
+        ctx.ui.rect("mine", 100.0, 100.0, Width::Fixed(50.0), Height::Fixed(50.0), text, Color::TRANSPARENT, 0.0);
+    }
+
+    fn update(&mut self, _ctx: &mut Ctx) {}
+}
+
+fn harness() -> Harness<Themed> {
+    Harness::new(Themed, 300.0, 200.0)
+}
+
+#[test]
+fn buttons_keep_their_tokens_across_switches() {
+    let mut h = harness();
+    // light surface and on_primary are the same white
+    assert_eq!(Theme::light().surface, Theme::light().on_primary);
+    h.ctx_mut().set_theme(Theme::light());
+    h.ctx_mut().set_theme(Theme::dark());
+
+    let dark = Theme::dark();
+    let button = h.get::<Button>("btn").unwrap();
+    assert_eq!(button.text_color, dark.on_primary);
+    assert_eq!(button.bg_color, dark.primary);
+    assert_eq!(button.bg_color_hover, dark.primary.darken(0.8));
+}
+
+#[test]
+fn only_themed_fields_follow_the_theme() {
+    let mut h = harness();
+    h.ctx_mut().set_theme(Theme::light());
+
+    let light = Theme::light();
+    assert_eq!(h.get::<Rect>("panel").unwrap().color, light.surface);
+    // the app picked this color, even if it came from the theme
+    assert_eq!(h.get::<Rect>("mine").unwrap().color, Theme::dark().text);
+}
+
+#[test]
+fn changing_a_themed_field_takes_it_off_the_theme() {
+    let mut h = harness();
+    h.ctx_mut().ui.get_mut::<Button>("btn").unwrap().bg_color = Color::BLACK;
+    h.ctx_mut().set_theme(Theme::light());
+    h.ctx_mut().set_theme(Theme::dark());
+
+    let button = h.get::<Button>("btn").unwrap();
+    assert_eq!(button.bg_color, Color::BLACK);
+    assert_eq!(button.text_color, Theme::dark().on_primary);
+}
+
+#[test]
+fn styled_buttons_switch_underneath_their_classes() {
+    let mut h = harness();
+    let ui = &mut h.ctx_mut().ui;
+    ui.define_style("loud", Style::new().text_color(Color::RED));
+    ui.add_class("btn", "loud");
+    h.ctx_mut().set_theme(Theme::light());
+
+    let light = Theme::light();
+    let button = h.get::<Button>("btn").unwrap();
+    assert_eq!(button.text_color, Color::RED);
+    assert_eq!(button.bg_color, light.primary);
+
+    h.ctx_mut().ui.remove_class("btn", "loud");
+    assert_eq!(h.get::<Button>("btn").unwrap().text_color, light.on_primary);
+}
+
+#[test]
+#[allow(deprecated)]
+fn the_old_default_padding_forwards_to_the_theme() {
+    let mut h = harness();
+    assert_eq!(h.ctx().ui.fonts.default_padding, Theme::dark().padding);
+
+    h.ctx_mut().ui.fonts.default_padding = 20.0;
+    h.ctx_mut().ui.button("big", "Go", 0.0, 100.0);
+    assert_eq!(h.get::<Button>("big").unwrap().padding, 20.0);
+    assert_eq!(h.ctx().ui.theme().padding, 20.0);
+
+    let roomy = Theme {
+        padding: 12.0,
+        ..Theme::light()
+    };
+    h.ctx_mut().set_theme(roomy);
+    assert_eq!(h.ctx().ui.fonts.default_padding, 12.0);
+    assert_eq!(h.get::<Button>("big").unwrap().padding, 12.0);
+}
@@ -0,0 +1,155 @@
+use bento::*;
+use winit::event::TouchPhase;
+
+/// a button and a canvas, remembering what touch reported each update
+#[derive(Default)]
+struct Touchy {
+    clicks: u32,
+    drag_started: bool,
+    drag_total: Option<(f32, f32)>,
+    pinches: Vec<Pinch>,
+    pans: Vec<(f32, f32)>,
+    swipes: Vec<SwipeDirection>,
+}
+
+impl BentoApp for Touchy {
+    fn once(&mut self, ctx: &mut Ctx) {
+        ctx.ui.button("btn", "Press", 20.0, 20.0);
+        ctx.ui.canvas("canvas", 0.0, 100.0, Width::Fixed(400.0), Height::Fixed(300.0), Color::BLACK);
+    }
+
+    fn update(&mut self, ctx: &mut Ctx) {
+        if ctx.is_clicked("btn") {
+            self.clicks += 1;
+        }
+        if ctx.is_drag_started("btn") {
+            self.drag_started = true;
+        }
+        if let Some(total) = ctx.drag_total("btn") {
+            self.drag_total = Some(total);
+        }
+        self.pinches.extend(ctx.pinch());
+        self.pans.extend(ctx.two_finger_pan());
+        self.swipes.extend(ctx.swipe());
+    }
+}
+
+fn harness() -> Harness<Touchy> {
+    Harness::new(Touchy::default(), 400.0, 400.0)
+}
+
+fn center(h: &Harness<Touchy>, id: &str) -> (f32, f32) {
+    let (x, y, w, h) = h.bounds(id).unwrap();
+    (x + w * 0.5, y + h * 0.5)
+}
+
+#[test]
+fn tap_clicks() {
+    let mut h = harness();
+    let (x, y) = center(&h, "btn");
+    h.tap(x, y);
+
+    assert_eq!(h.app.clicks, 1);
+    assert!(h.ctx().pointers().is_empty());
+}
+
+#[test]
+fn small_wobble_still_taps() {
+    let mut h = harness();
+    let (x, y) = center(&h, "btn");
+    h.touch(0, TouchPhase::Started, x, y)
+        .touch(0, TouchPhase::Moved, x + 4.0, y + 3.0)
+        .touch(0, TouchPhase::Ended, x + 4.0, y + 3.0);
+
+    assert_eq!(h.app.clicks, 1);
+    assert!(!h.app.drag_started);
+}
+
+#[test]
+fn moving_past_the_slop_drags() {
+    let mut h = harness();
+    let (x, y) = center(&h, "btn");
+    h.touch(0, TouchPhase::Started, x, y)
+        .touch(0, TouchPhase::Moved, x + 30.0, y);
+
+    assert!(h.app.drag_started);
+    assert!(h.ctx().mouse.left_pressed);
+    let pointers = h.ctx().pointers();
+    assert_eq!(pointers.len(), 1);
+    assert_eq!(pointers[0].id, PointerId::Touch(0));
+
+    h.touch(0, TouchPhase::Moved, x + 50.0, y + 10.0)
+        .touch(0, TouchPhase::Ended, x + 50.0, y + 10.0);
+    assert_eq!(h.app.drag_total, Some((50.0, 10.0)));
+    assert!(!h.ctx().mouse.left_pressed);
+}
+
+#[test]
+fn pinch_reports_scale_and_center() {
+    let mut h = harness();
+    h.pinch(200.0, 250.0, 100.0, 200.0, 2);
+
+    // fingers 100 apart, then 150 and 200, moving one at a time
+    let pinches = &h.app.pinches;
+    assert_eq!(pinches.len(), 4);
+    let scale: f32 = pinches.iter().map(|p| p.scale).product();
+    assert!((scale - 2.0).abs() < 1e-4, "{scale}");
+    let last = pinches.last().unwrap();
+    assert_eq!((last.x, last.y), (200.0, 250.0));
+    assert_eq!(h.app.clicks, 0);
+}
+
+#[test]
+fn pinching_zooms_the_canvas_under_the_fingers() {
+    let mut h = harness();
+    let before = h.get::<Canvas>("canvas").unwrap().zoom;
+    h.pinch(200.0, 250.0, 100.0, 200.0, 4);
+
+    let zoom = h.get::<Canvas>("canvas").unwrap().zoom;
+    assert!((zoom / before - 2.0).abs() < 1e-3, "{zoom}");
+}
+
+#[test]
+fn two_fingers_pan() {
+    let mut h = harness();
+    h.touch(0, TouchPhase::Started, 100.0, 200.0)
+        .touch(1, TouchPhase::Started, 200.0, 200.0)
+        .touch(0, TouchPhase::Moved, 100.0, 240.0)
+        .touch(1, TouchPhase::Moved, 200.0, 240.0);
+
+    // the midpoint moves half as far as each finger
+    assert_eq!(h.app.pans, vec![(0.0, 20.0), (0.0, 20.0)]);
+    // the fingers end as far apart as they started
+    let scale: f32 = h.app.pinches.iter().map(|p| p.scale).product();
+    assert!((scale - 1.0).abs() < 1e-4, "{scale}");
+    assert_eq!(h.ctx().pointers().len(), 2);
+}
+
+#[test]
+fn quick_flicks_swipe() {
+    let flick = |dx: f32, dy: f32| {
+        let mut h = harness();
+        h.touch(0, TouchPhase::Started, 200.0, 200.0)
+            .touch(0, TouchPhase::Moved, 200.0 + dx * 0.5, 200.0 + dy * 0.5)
+            .touch(0, TouchPhase::Ended, 200.0 + dx, 200.0 + dy);
+        h.app.swipes.clone()
+    };
+
+    assert_eq!(flick(120.0, 10.0), vec![SwipeDirection::Right]);
+    assert_eq!(flick(-120.0, 10.0), vec![SwipeDirection::Left]);
+    assert_eq!(flick(5.0, 120.0), vec![SwipeDirection::Down]);
+    assert_eq!(flick(5.0, -120.0), vec![SwipeDirection::Up]);
+    // too short to count
+    assert!(flick(20.0, 0.0).is_empty());
+}
+
+#[test]
+fn slow_moves_dont_swipe() {
+    let mut h = harness();
+    h.touch(0, TouchPhase::Started, 200.0, 200.0);
+    h.advance(std::time::Duration::from_millis(600));
+    h.touch(0, TouchPhase::Moved, 260.0, 200.0)
+        .touch(0, TouchPhase::Ended, 320.0, 200.0);
+
+    assert!(h.app.swipes.is_empty());
+}